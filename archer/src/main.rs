@@ -1,14 +1,37 @@
-use polybar::components::{Clock, Date};
+use polybar::components::{Clock, Date, Label};
 use polybar::prelude::*;
-use wtm::TilingManager;
+use std::thread;
+use wtm::{Notification, TilingManager};
 
 fn main() {
 	simple_logger::init().unwrap();
 
-	let tm = TilingManager::create();
+	let mut tm = TilingManager::create();
+	let notifications = tm.subscribe();
+
+	// The workspace is a single number, the profile name follows it
+	let workspace = Label::new("Workspace", 10);
+	let workspace_text = workspace.text();
+
+	let profile = Label::new("Profile", 50);
+	let profile_text = profile.text();
+
 	let bar = PolyBar::create()
 		.with_component(Box::new(Clock::default()))
-		.with_component(Box::new(Date::default()));
+		.with_component(Box::new(Date::default()))
+		.with_component(Box::new(profile))
+		.with_component(Box::new(workspace));
+
+	thread::spawn(move || {
+		for notification in notifications {
+			match notification {
+				Notification::ProfileChanged { profile, .. } => profile_text.set(profile),
+				Notification::WorkspaceChanged { workspace, .. } => {
+					workspace_text.set(workspace.to_string())
+				}
+			}
+		}
+	});
 
 	tm.start();
 	bar.start();
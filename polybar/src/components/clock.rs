@@ -16,14 +16,14 @@ impl Component for Clock {
 	fn draw(&self, data: &DrawData, mut dc: DeviceContext) -> WinApiResult<()> {
 		let text = format!("{}", chrono::Local::now().format("%T"));
 
-		dc.set_font(*data.font);
+		dc.set_font(data.font);
 		let size = dc.get_text_extent(text.clone())?;
 
 		dc.rect.left = data.display.width / 2 - (size.cx / 2) - 10;
 		dc.rect.right = data.display.width / 2 + (size.cx / 2) + 10;
 
 		dc.set_text_color(0x00ffffff);
-		dc.set_background_color(*data.bg_color as u32);
+		dc.set_background_color(data.bg_color as u32);
 
 		dc.draw_text(text, TextOptions::default())?;
 
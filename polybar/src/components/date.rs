@@ -16,9 +16,9 @@ impl Component for Date {
 	fn draw(&self, data: &DrawData, mut dc: DeviceContext) -> WinApiResult<()> {
 		let text = format!("{}", chrono::Local::now().format("%e %b %Y"));
 
-		dc.set_font(*data.font);
+		dc.set_font(data.font);
 		dc.set_text_color(0x00ffffff);
-		dc.set_background_color(*data.bg_color as u32);
+		dc.set_background_color(data.bg_color as u32);
 
 		let size = dc.get_text_extent(text.clone())?;
 
@@ -0,0 +1,85 @@
+use crate::{
+	poly_bar::{DrawData, RedrawReason},
+	Component,
+};
+use std::{
+	sync::{Arc, Mutex},
+	time::Duration,
+};
+use winsapi::*;
+
+/// Shows text that can be changed from other threads, starting `x` pixels from the left edge
+#[derive(Debug, Default)]
+pub struct Label {
+	reason: RedrawReason,
+	x: i32,
+	text: LabelText,
+	drawn_width: Mutex<i32>,
+}
+
+impl Label {
+	/// Creates a label whose text starts `x` pixels from the left edge of the bar
+	///
+	/// Labels next to each other need to be far enough apart for their longest text.
+	pub fn new(reason: &str, x: i32) -> Self {
+		Self {
+			reason: reason.to_owned(),
+			x,
+			..Self::default()
+		}
+	}
+
+	/// A handle to change the text of the label
+	pub fn text(&self) -> LabelText {
+		self.text.clone()
+	}
+}
+
+impl Component for Label {
+	fn interval(&self) -> Duration {
+		Duration::from_millis(250)
+	}
+
+	fn draw(&self, data: &DrawData, mut dc: DeviceContext) -> WinApiResult<()> {
+		let text = self.text.0.lock().unwrap().clone();
+		let mut drawn_width = self.drawn_width.lock().unwrap();
+
+		dc.set_font(data.font);
+		dc.set_text_color(0x00ffffff);
+		dc.set_background_color(data.bg_color as u32);
+
+		let width = if text.is_empty() {
+			0
+		} else {
+			dc.get_text_extent(text.clone())?.cx
+		};
+
+		// Clear what is left of a longer text
+		dc.rect.left = self.x;
+		dc.rect.right = dc.rect.left + width.max(*drawn_width);
+		dc.fill(data.bg_color as u32)?;
+
+		*drawn_width = width;
+
+		if !text.is_empty() {
+			dc.rect.right = dc.rect.left + width;
+			dc.draw_text(text, TextOptions::default())?;
+		}
+
+		Ok(())
+	}
+
+	fn reason(&self) -> RedrawReason {
+		self.reason.clone()
+	}
+}
+
+/// Handle to the text of a [`Label`](struct.Label.html)
+#[derive(Debug, Default, Clone)]
+pub struct LabelText(Arc<Mutex<String>>);
+
+impl LabelText {
+	pub fn set(&self, text: impl Into<String>) {
+		*self.0.lock().unwrap() = text.into();
+	}
+}
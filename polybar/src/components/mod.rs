@@ -4,9 +4,11 @@ use winsapi::{DeviceContext, WinApiResult};
 
 mod clock;
 mod date;
+mod label;
 
 pub use clock::Clock;
 pub use date::Date;
+pub use label::{Label, LabelText};
 
 #[allow(unused_variables, unused_mut)]
 pub trait Component: Debug + Send + Sync {
@@ -1,18 +1,122 @@
-#[derive(Debug, Copy, Clone)]
+use serde::{de, Deserialize, Deserializer};
+use std::{
+	error::Error,
+	fmt, fs, io,
+	path::{Path, PathBuf},
+	str::FromStr,
+};
+use winsapi::Color;
+
+/// Name of the config file inside of the `archer` config directory
+const CONFIG_FILE: &str = "polybar.toml";
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct Config {
 	pub height: i32,
+	#[serde(deserialize_with = "deserialize_color")]
 	pub bg_color: i32,
-	pub font_name: &'static str,
+	pub font_name: String,
 	pub font_size: i32,
 }
 
+impl Config {
+	/// The location of the config file in the user's config directory
+	pub fn path() -> Option<PathBuf> {
+		dirs::config_dir().map(|dir| dir.join("archer").join(CONFIG_FILE))
+	}
+
+	/// Loads the config from the user's config directory
+	///
+	/// The default config is returned if the file doesn't exist.
+	pub fn load() -> Result<Self, ConfigError> {
+		match Self::path() {
+			Some(path) if path.exists() => Self::load_from(&path),
+			_ => Ok(Self::default()),
+		}
+	}
+
+	pub fn load_from(path: &Path) -> Result<Self, ConfigError> {
+		fs::read_to_string(path)
+			.map_err(|err| ConfigError::Io(path.to_owned(), err))?
+			.parse()
+	}
+
+	fn validate(&self) -> Result<(), ConfigError> {
+		if self.height < 1 || self.height > 200 {
+			return Err(ConfigError::Invalid {
+				key: "height",
+				reason: format!("{} is not between 1 and 200", self.height),
+			});
+		}
+
+		if self.font_size < 1 {
+			return Err(ConfigError::Invalid {
+				key: "font_size",
+				reason: format!("{} is not a valid font size", self.font_size),
+			});
+		}
+
+		if self.font_name.is_empty() || self.font_name.len() >= 32 {
+			return Err(ConfigError::Invalid {
+				key: "font_name",
+				reason: "must be between 1 and 31 characters long".to_owned(),
+			});
+		}
+
+		Ok(())
+	}
+}
+
+impl FromStr for Config {
+	type Err = ConfigError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let config: Config = toml::from_str(s).map_err(ConfigError::Parse)?;
+		config.validate()?;
+
+		Ok(config)
+	}
+}
+
 impl Default for Config {
 	fn default() -> Self {
 		Self {
 			height: 20,
 			bg_color: 0x2C2427,
-			font_name: "Consolas",
+			font_name: "Consolas".to_owned(),
 			font_size: 18,
 		}
 	}
 }
+
+#[derive(Debug)]
+pub enum ConfigError {
+	Io(PathBuf, io::Error),
+	Parse(toml::de::Error),
+	Invalid { key: &'static str, reason: String },
+}
+
+impl Error for ConfigError {}
+
+impl fmt::Display for ConfigError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ConfigError::Io(path, err) => write!(f, "could not read {}: {}", path.display(), err),
+			ConfigError::Parse(err) => write!(f, "invalid config: {}", err),
+			ConfigError::Invalid { key, reason } => {
+				write!(f, "invalid value for key `{}`: {}", key, reason)
+			}
+		}
+	}
+}
+
+fn deserialize_color<'de, D>(deserializer: D) -> Result<i32, D::Error>
+where
+	D: Deserializer<'de>,
+{
+	String::deserialize(deserializer)?
+		.parse::<Color>()
+		.map(|color| color.to_colorref() as i32)
+		.map_err(de::Error::custom)
+}
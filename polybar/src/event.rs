@@ -4,10 +4,11 @@ use winapi::um::winuser::{
 	EVENT_OBJECT_DESTROY, EVENT_OBJECT_HIDE, EVENT_OBJECT_SHOW, EVENT_SYSTEM_FOREGROUND,
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Event {
 	RedrawAppBar(RedrawReason),
 	WinEvent(WinEvent),
+	ConfigChanged,
 	__Nonexhaustive,
 }
 
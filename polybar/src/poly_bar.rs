@@ -2,8 +2,8 @@ use crate::{
 	components::Component, config::Config, display::Display, event::Event, util, INSTANCE,
 };
 use crossbeam_channel::select;
-use log::{debug, info};
-use std::{collections::HashMap, ptr, thread, time::Duration};
+use log::{debug, error, info};
+use std::{collections::HashMap, fs, mem, ptr, sync::RwLock, thread};
 use system::*;
 use winapi::{
 	shared::{
@@ -14,13 +14,16 @@ use winapi::{
 		libloaderapi::GetModuleHandleA,
 		wingdi::CreateSolidBrush,
 		winuser::{
-			CreateWindowExA, DispatchMessageW, GetMessageW, PeekMessageW, RegisterClassA,
-			SendMessageA, SetWinEventHook, ShowWindow, TranslateMessage, EVENT_MAX, EVENT_MIN, MSG,
-			PM_REMOVE, SW_HIDE, SW_SHOW, WM_PAINT, WNDCLASSA,
+			CreateWindowExA, DispatchMessageW, GetMessageW, RegisterClassA, SendMessageA,
+			SetWinEventHook, ShowWindow, TranslateMessage, EVENT_MAX, EVENT_MIN, MSG, SW_HIDE,
+			SW_SHOW, WM_PAINT, WNDCLASSA,
 		},
 	},
 };
-use winsapi::{CTypeExt, DeviceContext, EventChannel, Font, PtrExt, WinApiError, WinApiResult};
+use winsapi::{
+	run_message_loop, CTypeExt, Color, DeviceContext, EventChannel, Font, PtrExt, Rect,
+	WinApiError, WinApiResult, Window,
+};
 
 mod system;
 
@@ -29,13 +32,12 @@ pub type RedrawReason = String;
 #[derive(Debug, Default)]
 pub struct PolyBar {
 	display: Display,
-	config: Config,
+	config: RwLock<Config>,
 	window: Option<i32>,
-	font: Font,
+	font: RwLock<Font>,
 	redraw_reason: RedrawReason,
 	components: HashMap<RedrawReason, Box<dyn Component>>,
 	channel: EventChannel<Event>,
-	draw_data: Option<DrawData>,
 }
 
 impl PolyBar {
@@ -44,7 +46,17 @@ impl PolyBar {
 			match INSTANCE.get_mut() {
 				Some(instance) => instance,
 				None => {
-					INSTANCE.set(PolyBar::default()).unwrap();
+					let config = Config::load().unwrap_or_else(|err| {
+						error!("Failed to load config, using defaults: {}", err);
+						Config::default()
+					});
+
+					INSTANCE
+						.set(PolyBar {
+							config: RwLock::new(config),
+							..PolyBar::default()
+						})
+						.unwrap();
 					INSTANCE.get_mut().unwrap()
 				}
 			}
@@ -69,6 +81,7 @@ impl PolyBar {
 			self.init_components();
 			self.init_window();
 			self.init_winevent();
+			self.init_config_watcher();
 
 			loop {
 				select! {
@@ -90,16 +103,82 @@ impl PolyBar {
 					self.show();
 				}
 			}
+			Event::ConfigChanged => self.reload_config(),
 			_ => {}
 		}
 	}
 
-	fn init_draw_data(&'static mut self) {
-		self.draw_data = Some(DrawData {
+	fn reload_config(&'static self) {
+		let config = match Config::load() {
+			Ok(config) => config,
+			Err(err) => {
+				error!("Keeping the previous config: {}", err);
+				return;
+			}
+		};
+
+		if config == *self.config.read().unwrap() {
+			return;
+		}
+
+		info!("Applying new config");
+
+		let previous = mem::replace(&mut *self.config.write().unwrap(), config.clone());
+
+		if let Err(err) = self.apply_config(&previous, &config) {
+			error!("Keeping the previous config: {}", err);
+			*self.config.write().unwrap() = previous.clone();
+
+			// Puts back what was changed before the error, nothing was changed without a window
+			if self.window.is_some() {
+				if let Err(err) = self.apply_config(&config, &previous) {
+					error!("Failed to restore the previous config: {}", err);
+				}
+			}
+		}
+	}
+
+	/// Changes the bar from how `previous` says it looks to how `config` does
+	fn apply_config(&'static self, previous: &Config, config: &Config) -> WinApiResult<()> {
+		// Config changes can arrive before the window was created
+		let mut window = Window(self.window.ok_or(WinApiError::Null)? as HWND);
+
+		if previous.font_name != config.font_name || previous.font_size != config.font_size {
+			*self.font.write().unwrap() = Font::create(&config.font_name, config.font_size)?;
+		}
+
+		if previous.height != config.height {
+			window.set_pos(
+				Rect {
+					x: 0,
+					y: 0,
+					w: self.display.width,
+					h: config.height,
+				},
+				None,
+			);
+		}
+
+		if previous.bg_color != config.bg_color {
+			window.set_background(Color::from_colorref(config.bg_color as u32));
+		} else {
+			window.redraw();
+		}
+
+		for reason in self.components.keys() {
+			self.redraw(reason.clone())?;
+		}
+
+		Ok(())
+	}
+
+	/// What the components need to draw themselves, as the current config says
+	pub(crate) fn draw_data(&'static self) -> DrawData {
+		DrawData {
 			display: &self.display,
-			bg_color: &self.config.bg_color,
-			font: &self.font,
-		})
+			bg_color: self.config.read().unwrap().bg_color,
+			font: *self.font.read().unwrap(),
+		}
 	}
 
 	fn hide(&'static self) {
@@ -135,16 +214,16 @@ impl PolyBar {
 	fn init_window(&'static self) {
 		info!("Creating appbar");
 		let name = "app_bar";
-		let config = self.config;
 
-		let height = config.height;
+		let (height, bg_color) = {
+			let config = self.config.read().unwrap();
+			(config.height, config.bg_color)
+		};
 		let display_width = self.display.width;
 
 		thread::spawn(move || unsafe {
 			let instance = GetModuleHandleA(ptr::null_mut()).as_result().unwrap();
-			let background_brush = CreateSolidBrush(config.bg_color as u32)
-				.as_result()
-				.unwrap();
+			let background_brush = CreateSolidBrush(bg_color as u32).as_result().unwrap();
 
 			let class = WNDCLASSA {
 				hInstance: instance as HINSTANCE,
@@ -176,10 +255,9 @@ impl PolyBar {
 			{
 				let app = INSTANCE.get_mut().unwrap();
 				app.window = Some(window_handle as i32);
-				app.init_draw_data();
 			}
 
-			let draw_data = self.draw_data.as_ref().unwrap();
+			let draw_data = &self.draw_data();
 			let hwnd = self.show();
 
 			self.components.values().for_each(|component| {
@@ -213,10 +291,19 @@ impl PolyBar {
 		});
 	}
 
+	fn init_config_watcher(&'static self) {
+		if let Some(path) = Config::path() {
+			let result = fs::create_dir_all(path.parent().unwrap())
+				.and_then(|_| self.channel.watch_file(path, Event::ConfigChanged));
+
+			if let Err(err) = result {
+				error!("Failed to watch the config file: {}", err);
+			}
+		}
+	}
+
 	fn init_winevent(&'static self) {
 		thread::spawn(|| unsafe {
-			let mut msg: MSG = MSG::default();
-
 			debug!("Registering win event hook");
 
 			let _hook = SetWinEventHook(
@@ -231,14 +318,8 @@ impl PolyBar {
 			.as_result()
 			.unwrap();
 
-			loop {
-				while PeekMessageW(&mut msg, 0 as HWND, 0, 0, PM_REMOVE) > 0 {
-					TranslateMessage(&msg);
-					DispatchMessageW(&msg);
-				}
-
-				thread::sleep(Duration::from_millis(5));
-			}
+			// The hook's events arrive as messages, the thread sleeps until there is one
+			run_message_loop();
 		});
 	}
 }
@@ -246,6 +327,6 @@ impl PolyBar {
 #[derive(Debug)]
 pub struct DrawData {
 	pub display: &'static Display,
-	pub bg_color: &'static i32,
-	pub font: &'static Font,
+	pub bg_color: i32,
+	pub font: Font,
 }
@@ -35,8 +35,9 @@ pub unsafe extern "system" fn window_cb(
 		info!("Received click @ ({}, {})", x, y);
 	} else if msg == WM_CREATE {
 		info!("loading font");
-		let app = INSTANCE.get_mut().unwrap();
-		app.font = Font::create(app.config.font_name, app.config.font_size).unwrap();
+		let app = INSTANCE.get().unwrap();
+		let config = app.config.read().unwrap();
+		*app.font.write().unwrap() = Font::create(&config.font_name, config.font_size).unwrap();
 	} else if !hwnd.is_null() && msg == WM_PAINT {
 		let reason = &INSTANCE.get().unwrap().redraw_reason;
 		debug!("Reason for paint was {:?}", reason);
@@ -51,10 +52,7 @@ pub unsafe extern "system" fn window_cb(
 
 		if let Some(component) = components.get(reason) {
 			component
-				.draw(
-					app.draw_data.as_ref().unwrap(),
-					DeviceContext::new(hwnd).unwrap(),
-				)
+				.draw(&app.draw_data(), DeviceContext::new(hwnd).unwrap())
 				.unwrap_or_else(|_| panic!("Failed to draw component: {:?}", component));
 		}
 
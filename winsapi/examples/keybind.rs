@@ -11,8 +11,8 @@ fn main() {
 	let b =
 		GlobalHotkeySet::new().add_global_hotkey(Event::B, Modifier::Ctrl + Modifier::Alt + Key::S);
 
-	channel.listen_for_hotkeys(a);
-	channel.listen_for_hotkeys(b);
+	channel.listen_for_hotkeys(a).unwrap();
+	channel.listen_for_hotkeys(b).unwrap();
 
 	let receiver = channel.receiver.clone();
 
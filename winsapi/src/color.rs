@@ -0,0 +1,61 @@
+use std::{error::Error, fmt, str::FromStr};
+use winapi::{shared::windef::COLORREF, um::wingdi::{GetBValue, GetGValue, GetRValue, RGB}};
+
+/// A 24-bit RGB color
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct Color {
+	pub r: u8,
+	pub g: u8,
+	pub b: u8,
+}
+
+impl Color {
+	pub const fn new(r: u8, g: u8, b: u8) -> Self {
+		Self { r, g, b }
+	}
+
+	/// Converts the color to the format that the windows api uses
+	pub fn to_colorref(self) -> COLORREF {
+		RGB(self.r, self.g, self.b)
+	}
+
+	/// Creates a color from the format that the windows api uses
+	pub fn from_colorref(color: COLORREF) -> Self {
+		Color::new(GetRValue(color), GetGValue(color), GetBValue(color))
+	}
+}
+
+impl FromStr for Color {
+	type Err = ParseColorError;
+
+	/// Parses a color in the `#RRGGBB` format
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let hex = s.strip_prefix('#').unwrap_or(s);
+
+		if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+			return Err(ParseColorError(s.to_owned()));
+		}
+
+		let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap();
+
+		Ok(Color::new(channel(0), channel(2), channel(4)))
+	}
+}
+
+impl fmt::Display for Color {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "#{:02X}{:02X}{:02X}", self.r, self.g, self.b)
+	}
+}
+
+/// Error returned when a color is not in the `#RRGGBB` format
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseColorError(String);
+
+impl Error for ParseColorError {}
+
+impl fmt::Display for ParseColorError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "`{}` is not a color in the #RRGGBB format", self.0)
+	}
+}
@@ -3,8 +3,11 @@ use std::ffi::CString;
 use winapi::{
 	shared::windef::{HDC, HWND, RECT, SIZE},
 	um::{
-		wingdi::{GetTextExtentPoint32A, SelectObject, SetBkColor, SetTextColor},
-		winuser::{DrawTextA, GetClientRect, GetDC},
+		wingdi::{
+			CreateSolidBrush, DeleteObject, GetTextExtentPoint32A, SelectObject, SetBkColor,
+			SetTextColor,
+		},
+		winuser::{DrawTextA, FillRect, GetClientRect, GetDC},
 	},
 };
 
@@ -42,6 +45,15 @@ impl DeviceContext {
 		}
 	}
 
+	pub fn fill(&self, color: u32) -> WinApiResult<()> {
+		unsafe {
+			let brush = CreateSolidBrush(color).as_result()?;
+			FillRect(self.hdc, &self.rect, brush);
+			DeleteObject(brush as *mut _);
+		}
+		Ok(())
+	}
+
 	pub fn get_text_extent(&self, text: String) -> WinApiResult<SIZE> {
 		let mut size = SIZE::default();
 		let string = CString::new(text.clone()).unwrap();
@@ -1,6 +1,6 @@
-use crate::GlobalHotkeySet;
+use crate::{watcher::watch_file, GlobalHotkeySet, HotkeyHandle};
 use crossbeam_channel::{bounded, unbounded, Receiver, Sender};
-use std::fmt;
+use std::{fmt, io, path::PathBuf};
 
 pub type EventSender<T> = Sender<T>;
 pub type EventReceiver<T> = Receiver<T>;
@@ -36,12 +36,48 @@ impl<T> EventChannel<T>
 where
 	T: 'static + Copy + Send + Sync,
 {
-	pub fn listen_for_hotkeys(&'static self, hotkeys: GlobalHotkeySet<T>) {
+	/// Sends the hotkey's event whenever it is pressed
+	///
+	/// The returned handle can be used to unregister the hotkeys again. Fails without registering
+	/// any of the hotkeys if one of them can't be registered.
+	pub fn listen_for_hotkeys(&self, hotkeys: GlobalHotkeySet<T>) -> io::Result<HotkeyHandle> {
 		let sender = self.sender.clone();
+		let (receiver, handle) = hotkeys.listen_for_hotkeys()?.split();
+
 		std::thread::spawn(move || {
-			for event in hotkeys.listen_for_hotkeys().unwrap() {
-				sender.send(event.unwrap()).unwrap();
+			for event in receiver {
+				let event = match event {
+					Ok(event) => event,
+					Err(_) => break,
+				};
+
+				if sender.send(event).is_err() {
+					break;
+				}
 			}
 		});
+
+		Ok(handle)
+	}
+}
+
+impl<T> EventChannel<T>
+where
+	T: 'static + Clone + Send,
+{
+	/// Sends `event` whenever the file at `path` is modified
+	pub fn watch_file(&self, path: PathBuf, event: T) -> io::Result<()> {
+		let sender = self.sender.clone();
+		let changes = watch_file(path)?;
+
+		std::thread::spawn(move || {
+			for _ in changes {
+				if sender.send(event.clone()).is_err() {
+					break;
+				}
+			}
+		});
+
+		Ok(())
 	}
 }
@@ -1,17 +1,32 @@
 use crate::internal::ReturnValue;
-use std::{collections::HashMap, io, mem::MaybeUninit, ops::Add, ptr, sync::mpsc, thread};
+use std::{
+	collections::HashMap,
+	error::Error,
+	fmt, io,
+	mem::{self, MaybeUninit},
+	ops::Add,
+	ptr,
+	str::FromStr,
+	sync::mpsc,
+	thread::{self, JoinHandle},
+};
 use winapi::{
 	ctypes::c_int,
-	shared::minwindef::{INT, LPARAM, UINT},
-	um::winuser::{
-		self, GetMessageW, RegisterHotKey, UnregisterHotKey, MOD_ALT, MOD_CONTROL, MOD_NOREPEAT,
-		MOD_SHIFT, MOD_WIN, VK_ADD, VK_BACK, VK_DELETE, VK_DIVIDE, VK_DOWN, VK_END, VK_ESCAPE,
-		VK_F1, VK_F10, VK_F11, VK_F12, VK_F2, VK_F3, VK_F4, VK_F5, VK_F6, VK_F7, VK_F8, VK_F9,
-		VK_HOME, VK_INSERT, VK_LEFT, VK_MULTIPLY, VK_NEXT, VK_NUMPAD0, VK_NUMPAD1, VK_NUMPAD2,
-		VK_NUMPAD3, VK_NUMPAD4, VK_NUMPAD5, VK_NUMPAD6, VK_NUMPAD7, VK_NUMPAD8, VK_NUMPAD9,
-		VK_OEM_1, VK_OEM_102, VK_OEM_2, VK_OEM_3, VK_OEM_4, VK_OEM_5, VK_OEM_6, VK_OEM_7, VK_OEM_8,
-		VK_OEM_COMMA, VK_OEM_MINUS, VK_OEM_PERIOD, VK_OEM_PLUS, VK_PAUSE, VK_PRIOR, VK_RETURN,
-		VK_RIGHT, VK_SPACE, VK_SUBTRACT, VK_TAB, VK_UP, WM_HOTKEY,
+	shared::minwindef::{DWORD, INT, LPARAM, UINT},
+	um::{
+		processthreadsapi::GetCurrentThreadId,
+		winuser::{
+			self, GetAsyncKeyState, GetMessageW, PeekMessageW, PostThreadMessageW, RegisterHotKey,
+			UnregisterHotKey, MOD_ALT, MOD_CONTROL, MOD_NOREPEAT, MOD_SHIFT, MOD_WIN, PM_NOREMOVE,
+			VK_ADD, VK_BACK, VK_DELETE, VK_DIVIDE, VK_DOWN, VK_END, VK_ESCAPE, VK_F1, VK_F10,
+			VK_F11, VK_F12, VK_F2, VK_F3, VK_F4, VK_F5, VK_F6, VK_F7, VK_F8, VK_F9, VK_HOME,
+			VK_INSERT, VK_LCONTROL, VK_LEFT, VK_LMENU, VK_LSHIFT, VK_LWIN, VK_MULTIPLY, VK_NEXT,
+			VK_NUMPAD0, VK_NUMPAD1, VK_NUMPAD2, VK_NUMPAD3, VK_NUMPAD4, VK_NUMPAD5, VK_NUMPAD6,
+			VK_NUMPAD7, VK_NUMPAD8, VK_NUMPAD9, VK_OEM_1, VK_OEM_102, VK_OEM_2, VK_OEM_3, VK_OEM_4,
+			VK_OEM_5, VK_OEM_6, VK_OEM_7, VK_OEM_8, VK_OEM_COMMA, VK_OEM_MINUS, VK_OEM_PERIOD,
+			VK_OEM_PLUS, VK_PAUSE, VK_PRIOR, VK_RCONTROL, VK_RETURN, VK_RIGHT, VK_RMENU, VK_RSHIFT,
+			VK_RWIN, VK_SPACE, VK_SUBTRACT, VK_TAB, VK_UP, WM_HOTKEY, WM_QUIT, WM_USER,
+		},
 	},
 };
 
@@ -54,9 +69,17 @@ where
 		self
 	}
 
-	pub fn listen_for_hotkeys(mut self) -> io::Result<impl IntoIterator<Item = io::Result<ID>>> {
+	/// Registers the hotkeys on their own thread, fails if any of them can't be registered
+	pub fn listen_for_hotkeys(mut self) -> io::Result<HotkeyListener<ID>> {
 		let (tx_hotkey, rx_hotkey) = mpsc::channel();
-		thread::spawn(move || {
+		let (tx_registered, rx_registered) = mpsc::channel();
+		let thread = thread::spawn(move || {
+			unsafe {
+				// Forces the creation of the thread's message queue so it can receive WM_QUIT
+				let mut message = mem::zeroed();
+				PeekMessageW(&mut message, ptr::null_mut(), WM_USER, WM_USER, PM_NOREMOVE);
+			}
+
 			let ids = || Self::MIN_ID..;
 			let register_result =
 				ids()
@@ -74,9 +97,13 @@ where
 						}
 					});
 			if let Err(err) = register_result {
-				tx_hotkey.send(Err(err)).unwrap_or(());
+				tx_registered.send(Err(err)).unwrap_or(());
 			} else {
 				self.hotkeys_active = true;
+				tx_registered
+					.send(Ok(unsafe { GetCurrentThreadId() }))
+					.unwrap_or(());
+
 				let id_assocs: HashMap<INT, ID> = ids()
 					.zip(self.hotkey_defs.iter().map(|def| def.user_id))
 					.collect();
@@ -106,7 +133,55 @@ where
 				}
 			}
 		});
-		Ok(rx_hotkey)
+
+		// Registration fails before the listener is handed out, while the caller can still react
+		let thread_id = rx_registered.recv().expect("Hotkey thread panicked")?;
+
+		Ok(HotkeyListener {
+			receiver: rx_hotkey,
+			handle: HotkeyHandle { thread_id, thread },
+		})
+	}
+}
+
+/// The pressed hotkeys of a [`GlobalHotkeySet`](struct.GlobalHotkeySet.html)
+pub struct HotkeyListener<ID> {
+	receiver: mpsc::Receiver<io::Result<ID>>,
+	handle: HotkeyHandle,
+}
+
+impl<ID> HotkeyListener<ID> {
+	/// Splits the listener into the receiving end of the pressed hotkeys and a handle that can
+	/// unregister them
+	pub fn split(self) -> (mpsc::Receiver<io::Result<ID>>, HotkeyHandle) {
+		(self.receiver, self.handle)
+	}
+}
+
+impl<ID> IntoIterator for HotkeyListener<ID> {
+	type Item = io::Result<ID>;
+	type IntoIter = mpsc::IntoIter<io::Result<ID>>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.receiver.into_iter()
+	}
+}
+
+/// Handle to the thread that owns a set of registered hotkeys
+#[derive(Debug)]
+pub struct HotkeyHandle {
+	thread_id: DWORD,
+	thread: JoinHandle<()>,
+}
+
+impl HotkeyHandle {
+	/// Unregisters the hotkeys, blocking until they can be registered again
+	pub fn unregister(self) {
+		unsafe {
+			PostThreadMessageW(self.thread_id, WM_QUIT, 0, 0);
+		}
+
+		let _ = self.thread.join();
 	}
 }
 
@@ -255,8 +330,112 @@ pub enum Key {
 	Oem102 = VK_OEM_102,
 }
 
+impl FromStr for Key {
+	type Err = ParseKeyError;
+
+	/// Parses a key from its name, ignoring case (e.g. `"S"`, `"F1"`, `"LeftArrow"`, `"Left"`)
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let key = match s.to_ascii_lowercase().as_str() {
+			"backspace" => Key::Backspace,
+			"tab" => Key::Tab,
+			"return" | "enter" => Key::Return,
+			"pause" => Key::Pause,
+			"esc" | "escape" => Key::Esc,
+			"space" => Key::Space,
+			"pgup" | "pageup" => Key::PgUp,
+			"pgdown" | "pagedown" => Key::PgDown,
+			"end" => Key::End,
+			"home" => Key::Home,
+			"leftarrow" | "left" => Key::LeftArrow,
+			"uparrow" | "up" => Key::UpArrow,
+			"rightarrow" | "right" => Key::RightArrow,
+			"downarrow" | "down" => Key::DownArrow,
+			"insert" => Key::Insert,
+			"delete" => Key::Delete,
+			"0" | "number0" => Key::Number0,
+			"1" | "number1" => Key::Number1,
+			"2" | "number2" => Key::Number2,
+			"3" | "number3" => Key::Number3,
+			"4" | "number4" => Key::Number4,
+			"5" | "number5" => Key::Number5,
+			"6" | "number6" => Key::Number6,
+			"7" | "number7" => Key::Number7,
+			"8" | "number8" => Key::Number8,
+			"9" | "number9" => Key::Number9,
+			"a" => Key::A,
+			"b" => Key::B,
+			"c" => Key::C,
+			"d" => Key::D,
+			"e" => Key::E,
+			"f" => Key::F,
+			"g" => Key::G,
+			"h" => Key::H,
+			"i" => Key::I,
+			"j" => Key::J,
+			"k" => Key::K,
+			"l" => Key::L,
+			"m" => Key::M,
+			"n" => Key::N,
+			"o" => Key::O,
+			"p" => Key::P,
+			"q" => Key::Q,
+			"r" => Key::R,
+			"s" => Key::S,
+			"t" => Key::T,
+			"u" => Key::U,
+			"v" => Key::V,
+			"w" => Key::W,
+			"x" => Key::X,
+			"y" => Key::Y,
+			"z" => Key::Z,
+			"numpad0" => Key::Numpad0,
+			"numpad1" => Key::Numpad1,
+			"numpad2" => Key::Numpad2,
+			"numpad3" => Key::Numpad3,
+			"numpad4" => Key::Numpad4,
+			"numpad5" => Key::Numpad5,
+			"numpad6" => Key::Numpad6,
+			"numpad7" => Key::Numpad7,
+			"numpad8" => Key::Numpad8,
+			"numpad9" => Key::Numpad9,
+			"multiply" => Key::Multiply,
+			"add" => Key::Add,
+			"subtract" => Key::Subtract,
+			"divide" => Key::Divide,
+			"f1" => Key::F1,
+			"f2" => Key::F2,
+			"f3" => Key::F3,
+			"f4" => Key::F4,
+			"f5" => Key::F5,
+			"f6" => Key::F6,
+			"f7" => Key::F7,
+			"f8" => Key::F8,
+			"f9" => Key::F9,
+			"f10" => Key::F10,
+			"f11" => Key::F11,
+			"f12" => Key::F12,
+			"oem1" => Key::Oem1,
+			"oemplus" => Key::OemPlus,
+			"oemcomma" => Key::OemComma,
+			"oemminus" => Key::OemMinus,
+			"oemperiod" => Key::OemPeriod,
+			"oem2" => Key::Oem2,
+			"oem3" => Key::Oem3,
+			"oem4" => Key::Oem4,
+			"oem5" => Key::Oem5,
+			"oem6" => Key::Oem6,
+			"oem7" => Key::Oem7,
+			"oem8" => Key::Oem8,
+			"oem102" => Key::Oem102,
+			_ => return Err(ParseKeyError(format!("unknown key `{}`", s))),
+		};
+
+		Ok(key)
+	}
+}
+
 /// Modifier key than cannot be used by itself for hotkeys.
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
 #[repr(isize)]
 pub enum Modifier {
 	Alt = MOD_ALT,
@@ -265,12 +444,62 @@ pub enum Modifier {
 	Win = MOD_WIN,
 }
 
+impl Modifier {
+	/// The left and right keys of the modifier
+	fn keys(self) -> [c_int; 2] {
+		match self {
+			Modifier::Alt => [VK_LMENU, VK_RMENU],
+			Modifier::Ctrl => [VK_LCONTROL, VK_RCONTROL],
+			Modifier::Shift => [VK_LSHIFT, VK_RSHIFT],
+			Modifier::Win => [VK_LWIN, VK_RWIN],
+		}
+	}
+
+	/// Is the modifier currently held down
+	pub fn is_pressed(self) -> bool {
+		self.keys().iter().any(|&key| is_key_down(key))
+	}
+
+	/// Is the modifier held down once `key` went down or up
+	///
+	/// Low-level keyboard hooks run before the key state is updated, so they can't use
+	/// [`is_pressed`](Modifier::is_pressed) for the key they were called for.
+	pub fn is_pressed_after(self, key: c_int, down: bool) -> bool {
+		let keys = self.keys();
+
+		if keys.contains(&key) {
+			down || keys.iter().any(|&other| other != key && is_key_down(other))
+		} else {
+			self.is_pressed()
+		}
+	}
+}
+
+/// Is the virtual key currently held down
+fn is_key_down(key: c_int) -> bool {
+	unsafe { GetAsyncKeyState(key) as u16 & 0x8000 != 0 }
+}
+
+impl FromStr for Modifier {
+	type Err = ParseKeyError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.to_ascii_lowercase().as_str() {
+			"alt" => Ok(Modifier::Alt),
+			"ctrl" | "control" => Ok(Modifier::Ctrl),
+			"shift" => Ok(Modifier::Shift),
+			"win" | "super" => Ok(Modifier::Win),
+			_ => Err(ParseKeyError(format!("unknown modifier `{}`", s))),
+		}
+	}
+}
+
 /// A combination of modifier keys.
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub struct ModifierCombination(LPARAM);
 
 /// A combination of zero or more modifiers and exactly one normal key.
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub struct KeyCombination {
 	modifiers: ModifierCombination,
 	key: Key,
@@ -286,6 +515,42 @@ impl KeyCombination {
 	}
 }
 
+impl FromStr for KeyCombination {
+	type Err = ParseKeyError;
+
+	/// Parses a combination such as `"Ctrl+Alt+S"`. The last part is the key, everything before it
+	/// is a modifier.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut parts = s.split('+').map(str::trim).collect::<Vec<_>>();
+
+		let key = match parts.pop() {
+			Some(key) if !key.is_empty() => key.parse::<Key>()?,
+			_ => return Err(ParseKeyError(format!("`{}` does not contain a key", s))),
+		};
+
+		let modifiers = parts
+			.into_iter()
+			.try_fold(ModifierCombination(0), |modifiers, part| {
+				part.parse::<Modifier>()
+					.map(|modifier| modifiers + modifier)
+			})?;
+
+		Ok(KeyCombination::new_from(modifiers, key))
+	}
+}
+
+/// Error returned when a key, modifier or key combination can not be parsed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseKeyError(String);
+
+impl Error for ParseKeyError {}
+
+impl fmt::Display for ParseKeyError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&self.0)
+	}
+}
+
 impl From<Modifier> for ModifierCombination {
 	fn from(modifier: Modifier) -> Self {
 		ModifierCombination(modifier as isize)
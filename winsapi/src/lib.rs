@@ -1,3 +1,4 @@
+mod color;
 mod device_context;
 mod error_handler;
 mod event;
@@ -5,16 +6,21 @@ mod font;
 mod internal;
 mod keyboard;
 mod macros;
+mod message_loop;
 mod monitor;
 mod rect;
+mod watcher;
 mod window;
 
+pub use color::*;
 pub use device_context::DeviceContext;
 pub use error_handler::*;
 pub use event::EventChannel;
 pub use font::*;
 pub use keyboard::*;
 pub use macros::*;
+pub use message_loop::*;
 pub use monitor::*;
 pub use rect::*;
+pub use watcher::*;
 pub use window::*;
@@ -0,0 +1,152 @@
+use crate::{PtrExt, WinApiResult};
+use std::{fmt, mem, ptr, sync::Arc};
+use winapi::um::{
+	handleapi::CloseHandle,
+	synchapi::{CreateEventW, SetEvent, WaitForSingleObject},
+	winbase::{INFINITE, WAIT_OBJECT_0},
+	winnt::HANDLE,
+	winuser::{
+		DispatchMessageW, MsgWaitForMultipleObjectsEx, PeekMessageW, TranslateMessage,
+		MWMO_INPUTAVAILABLE, PM_REMOVE, QS_ALLINPUT, WM_QUIT,
+	},
+};
+
+/// Wakes up the message loops that wait for it, from any thread
+///
+/// Once raised, the signal stays raised. Use a new signal for the next group of message loops.
+#[derive(Clone)]
+pub struct Signal(Arc<EventHandle>);
+
+/// An event object that is closed when it is dropped
+struct EventHandle(HANDLE);
+
+// Event objects can be used from any thread
+unsafe impl Send for EventHandle {}
+unsafe impl Sync for EventHandle {}
+
+impl Signal {
+	/// Creates a signal that isn't raised yet
+	pub fn new() -> WinApiResult<Self> {
+		// A manual-reset event wakes up every thread waiting for it, not just one
+		let handle = unsafe { CreateEventW(ptr::null_mut(), 1, 0, ptr::null()) }.as_result()?;
+
+		Ok(Self(Arc::new(EventHandle(handle))))
+	}
+
+	/// Raises the signal, waking up everything that waits for it
+	pub fn raise(&self) {
+		unsafe {
+			SetEvent((self.0).0);
+		}
+	}
+
+	/// Was the signal raised
+	pub fn is_raised(&self) -> bool {
+		unsafe { WaitForSingleObject((self.0).0, 0) == WAIT_OBJECT_0 }
+	}
+}
+
+impl fmt::Debug for Signal {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_tuple("Signal").field(&self.is_raised()).finish()
+	}
+}
+
+impl Drop for EventHandle {
+	fn drop(&mut self) {
+		unsafe {
+			CloseHandle(self.0);
+		}
+	}
+}
+
+/// Dispatches the window messages of the current thread until `stop` is raised
+///
+/// The thread sleeps while there are no messages, instead of checking for them over and over.
+/// Also returns when the thread receives `WM_QUIT`. Returns how often the thread woke up, which
+/// is once per batch of messages and once for `stop`.
+pub fn run_message_loop_until(stop: &Signal) -> usize {
+	let handles = [(stop.0).0];
+	let mut wakeups = 0;
+
+	while dispatch_messages() && !stop.is_raised() {
+		unsafe {
+			MsgWaitForMultipleObjectsEx(
+				handles.len() as u32,
+				handles.as_ptr(),
+				INFINITE,
+				QS_ALLINPUT,
+				MWMO_INPUTAVAILABLE,
+			);
+		}
+
+		wakeups += 1;
+	}
+
+	wakeups
+}
+
+/// Dispatches the window messages of the current thread until it receives `WM_QUIT`
+pub fn run_message_loop() {
+	while dispatch_messages() {
+		unsafe {
+			MsgWaitForMultipleObjectsEx(0, ptr::null(), INFINITE, QS_ALLINPUT, MWMO_INPUTAVAILABLE);
+		}
+	}
+}
+
+/// Dispatches the window messages that are waiting, returns false if one of them is `WM_QUIT`
+fn dispatch_messages() -> bool {
+	unsafe {
+		let mut msg = mem::zeroed();
+
+		while PeekMessageW(&mut msg, ptr::null_mut(), 0, 0, PM_REMOVE) > 0 {
+			if msg.message == WM_QUIT {
+				return false;
+			}
+
+			TranslateMessage(&msg);
+			DispatchMessageW(&msg);
+		}
+	}
+
+	true
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::{thread, time::Duration};
+
+	#[test]
+	fn raised_signals_stay_raised() {
+		let signal = Signal::new().unwrap();
+		let clone = signal.clone();
+		assert!(!clone.is_raised());
+
+		signal.raise();
+		assert!(clone.is_raised());
+		assert!(signal.is_raised());
+	}
+
+	#[test]
+	fn loops_stopped_before_they_run_return_without_waiting() {
+		let stop = Signal::new().unwrap();
+		stop.raise();
+
+		assert_eq!(run_message_loop_until(&stop), 0);
+	}
+
+	#[test]
+	fn idle_loops_only_wake_up_to_stop() {
+		let stop = Signal::new().unwrap();
+		let loop_stop = stop.clone();
+		let thread = thread::spawn(move || run_message_loop_until(&loop_stop));
+
+		// Polling every 10 ms would have woken the thread about 20 times meanwhile
+		thread::sleep(Duration::from_millis(200));
+		stop.raise();
+
+		assert!(thread.join().unwrap() <= 1);
+	}
+}
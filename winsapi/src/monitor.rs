@@ -1,23 +1,60 @@
-use crate::rect::Rect;
-use std::mem;
+use crate::{rect::Rect, Window};
+use std::{mem, ptr};
 use winapi::{
-	shared::windef::{HMONITOR, POINT},
+	shared::{
+		minwindef::{BOOL, LPARAM, TRUE},
+		windef::{HDC, HMONITOR, LPRECT, POINT},
+	},
 	um::winuser::{
-		GetCursorPos, GetMonitorInfoW, MonitorFromPoint, MONITORINFOEXW, MONITOR_DEFAULTTONEAREST,
+		EnumDisplayMonitors, GetCursorPos, GetMonitorInfoW, MonitorFromPoint, MonitorFromWindow,
+		MONITORINFOEXW, MONITOR_DEFAULTTONEAREST,
 	},
 };
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Monitor(pub HMONITOR);
 
 impl Monitor {
 	pub fn get_active() -> Monitor {
-		let active_monitor = unsafe {
-			let mut cursor_pos: POINT = mem::zeroed();
-			GetCursorPos(&mut cursor_pos);
+		Self::from_point(cursor_position())
+	}
+
+	/// The monitor that contains `point`, or the one closest to it
+	pub fn from_point((x, y): (i32, i32)) -> Monitor {
+		Monitor(unsafe { MonitorFromPoint(POINT { x, y }, MONITOR_DEFAULTTONEAREST) })
+	}
+
+	/// Every monitor that is part of the desktop
+	pub fn all() -> Vec<Monitor> {
+		unsafe extern "system" fn callback(
+			monitor: HMONITOR,
+			_: HDC,
+			_: LPRECT,
+			monitors: LPARAM,
+		) -> BOOL {
+			let monitors = &mut *(monitors as *mut Vec<Monitor>);
+			monitors.push(Monitor(monitor));
+
+			TRUE
+		}
+
+		let mut monitors = Vec::new();
+
+		unsafe {
+			EnumDisplayMonitors(
+				ptr::null_mut(),
+				ptr::null(),
+				Some(callback),
+				&mut monitors as *mut Vec<Monitor> as LPARAM,
+			);
+		}
+
+		monitors
+	}
 
-			MonitorFromPoint(cursor_pos, MONITOR_DEFAULTTONEAREST)
-		};
-		Monitor(active_monitor)
+	/// The monitor that contains the largest part of `window`
+	pub fn from_window(window: Window) -> Monitor {
+		Monitor(unsafe { MonitorFromWindow(window.0, MONITOR_DEFAULTTONEAREST) })
 	}
 
 	pub fn name(&self) -> String {
@@ -31,6 +68,18 @@ impl Monitor {
 		}
 	}
 
+	/// The whole monitor, including the taskbar
+	pub fn rect(&self) -> Rect {
+		unsafe {
+			let mut info: MONITORINFOEXW = mem::zeroed();
+			info.cbSize = mem::size_of::<MONITORINFOEXW>() as u32;
+
+			GetMonitorInfoW(self.0, &mut info as *mut MONITORINFOEXW as *mut _);
+
+			info.rcMonitor.into()
+		}
+	}
+
 	pub fn area(&self) -> Rect {
 		unsafe {
 			let work_area: Rect = {
@@ -46,3 +95,13 @@ impl Monitor {
 		}
 	}
 }
+
+/// The position of the mouse cursor in screen coordinates
+pub fn cursor_position() -> (i32, i32) {
+	unsafe {
+		let mut cursor_pos: POINT = mem::zeroed();
+		GetCursorPos(&mut cursor_pos);
+
+		(cursor_pos.x, cursor_pos.y)
+	}
+}
@@ -28,14 +28,37 @@ impl Rect {
 		}
 	}
 
-	/// Adjusts the rectangle for a border
-	pub fn adjust_for_border(&mut self, border: (i32, i32)) {
-		self.x -= border.0;
-		self.w += border.0 * 2;
-		self.h += border.1;
+	/// The window rectangle that makes the visible part of a window with `insets` cover this
+	/// rectangle, which is what has to be passed to `SetWindowPos`
+	pub fn outset(self, insets: Insets) -> Self {
+		Rect {
+			x: self.x - insets.left,
+			y: self.y - insets.top,
+			w: self.w + insets.left + insets.right,
+			h: self.h + insets.top + insets.bottom,
+		}
+	}
+
+	/// The visible part of a window with this window rectangle and `insets`
+	pub fn inset(self, insets: Insets) -> Self {
+		Rect {
+			x: self.x + insets.left,
+			y: self.y + insets.top,
+			w: self.w - insets.left - insets.right,
+			h: self.h - insets.top - insets.bottom,
+		}
 	}
 }
 
+/// The invisible part of a window on each side, between its window rectangle and what can be seen
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Insets {
+	pub left: i32,
+	pub top: i32,
+	pub right: i32,
+	pub bottom: i32,
+}
+
 impl Display for Rect {
 	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
 		writeln!(f, "x: {}", self.x)?;
@@ -68,3 +91,70 @@ impl From<Rect> for RECT {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const RECT: Rect = Rect {
+		x: 100,
+		y: 50,
+		w: 800,
+		h: 600,
+	};
+
+	/// The invisible border Windows 10 draws around a sizable window, there is none on the top
+	const DWM_BORDER: Insets = Insets {
+		left: 7,
+		top: 0,
+		right: 7,
+		bottom: 7,
+	};
+
+	#[test]
+	fn outset_grows_the_rectangle_by_the_insets() {
+		assert_eq!(
+			RECT.outset(DWM_BORDER),
+			Rect {
+				x: 93,
+				y: 50,
+				w: 814,
+				h: 607,
+			}
+		);
+	}
+
+	#[test]
+	fn inset_shrinks_the_rectangle_by_the_insets() {
+		assert_eq!(
+			RECT.inset(DWM_BORDER),
+			Rect {
+				x: 107,
+				y: 50,
+				w: 786,
+				h: 593,
+			}
+		);
+	}
+
+	#[test]
+	fn inset_and_outset_undo_each_other() {
+		let lopsided = Insets {
+			left: 1,
+			top: 31,
+			right: 12,
+			bottom: 3,
+		};
+
+		for insets in [Insets::default(), DWM_BORDER, lopsided] {
+			assert_eq!(RECT.inset(insets).outset(insets), RECT);
+			assert_eq!(RECT.outset(insets).inset(insets), RECT);
+		}
+	}
+
+	#[test]
+	fn zero_insets_change_nothing() {
+		assert_eq!(RECT.outset(Insets::default()), RECT);
+		assert_eq!(RECT.inset(Insets::default()), RECT);
+	}
+}
@@ -0,0 +1,84 @@
+use std::{
+	ffi::OsStr,
+	fs, io,
+	iter::once,
+	os::windows::ffi::OsStrExt,
+	path::{Path, PathBuf},
+	sync::mpsc::{self, Receiver},
+	thread,
+	time::{Duration, SystemTime},
+};
+use winapi::um::{
+	fileapi::{
+		FindCloseChangeNotification, FindFirstChangeNotificationW, FindNextChangeNotification,
+	},
+	handleapi::INVALID_HANDLE_VALUE,
+	synchapi::WaitForSingleObject,
+	winbase::{INFINITE, WAIT_OBJECT_0},
+	winnt::{FILE_NOTIFY_CHANGE_FILE_NAME, FILE_NOTIFY_CHANGE_LAST_WRITE, HANDLE},
+};
+
+/// Watches the file at `path`, receiving a message every time it is modified
+///
+/// The directory containing the file has to exist, the file itself doesn't.
+pub fn watch_file(path: PathBuf) -> io::Result<Receiver<()>> {
+	let directory = match path.parent() {
+		Some(parent) if !parent.as_os_str().is_empty() => parent,
+		_ => Path::new("."),
+	};
+
+	let handle = unsafe {
+		FindFirstChangeNotificationW(
+			to_wide(directory.as_os_str()).as_ptr(),
+			0,
+			FILE_NOTIFY_CHANGE_LAST_WRITE | FILE_NOTIFY_CHANGE_FILE_NAME,
+		)
+	};
+
+	if handle == INVALID_HANDLE_VALUE {
+		return Err(io::Error::last_os_error());
+	}
+
+	let (sender, receiver) = mpsc::channel();
+
+	// Handles can't be sent between threads, but the value itself is valid in every thread
+	let handle = handle as usize;
+
+	thread::spawn(move || unsafe {
+		let handle = handle as HANDLE;
+		let mut last_modified = modified(&path);
+
+		while WaitForSingleObject(handle, INFINITE) == WAIT_OBJECT_0 {
+			// Editors tend to save a file in multiple steps
+			thread::sleep(Duration::from_millis(50));
+
+			let modified = modified(&path);
+
+			if modified != last_modified {
+				last_modified = modified;
+
+				if sender.send(()).is_err() {
+					break;
+				}
+			}
+
+			if FindNextChangeNotification(handle) == 0 {
+				break;
+			}
+		}
+
+		FindCloseChangeNotification(handle);
+	});
+
+	Ok(receiver)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+	fs::metadata(path)
+		.and_then(|metadata| metadata.modified())
+		.ok()
+}
+
+fn to_wide(string: &OsStr) -> Vec<u16> {
+	string.encode_wide().chain(once(0)).collect()
+}
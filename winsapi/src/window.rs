@@ -1,16 +1,47 @@
-use crate::Rect;
-use std::{mem, ptr};
+use crate::{Color, Insets, Rect};
+use std::{
+	hash::{Hash, Hasher},
+	mem, ptr,
+};
 use winapi::{
-	shared::windef::HWND,
-	um::winuser::{
-		GetForegroundWindow, GetWindowInfo, SetWindowPos, ShowWindow, SWP_NOACTIVATE, SW_MINIMIZE,
-		SW_RESTORE, WINDOWINFO,
+	shared::{
+		minwindef::{BOOL, DWORD, LPARAM, TRUE},
+		windef::{HWND, RECT},
+		winerror::S_OK,
+	},
+	um::{
+		dwmapi::{DwmGetWindowAttribute, DWMWA_CLOAKED, DWMWA_EXTENDED_FRAME_BOUNDS},
+		handleapi::CloseHandle,
+		processthreadsapi::OpenProcess,
+		winbase::QueryFullProcessImageNameW,
+		wingdi::{CreateSolidBrush, DeleteObject},
+		winnt::PROCESS_QUERY_LIMITED_INFORMATION,
+		winuser::{
+			EnumWindows, GetClassNameW, GetForegroundWindow, GetSystemMetrics, GetWindow,
+			GetWindowInfo, GetWindowLongPtrW, GetWindowTextLengthW, GetWindowTextW,
+			GetWindowThreadProcessId, InvalidateRect, IsIconic, IsWindow, IsWindowVisible,
+			IsZoomed, SendMessageTimeoutW, SetClassLongPtrW, SetForegroundWindow,
+			SetLayeredWindowAttributes, SetWindowLongPtrW, SetWindowPos, ShowWindow,
+			GCLP_HBRBACKGROUND, GWL_EXSTYLE, GW_OWNER, LWA_ALPHA, LWA_COLORKEY, MINMAXINFO,
+			SMTO_ABORTIFHUNG, SM_CXMAXTRACK, SM_CXMINTRACK, SM_CYMAXTRACK, SM_CYMINTRACK,
+			SWP_NOACTIVATE, SW_HIDE, SW_MAXIMIZE, SW_MINIMIZE, SW_RESTORE, SW_SHOWNA, WINDOWINFO,
+			WM_GETMINMAXINFO, WS_EX_LAYERED,
+		},
 	},
 };
 
 #[derive(Debug, Copy, Clone)]
 pub struct Window(pub HWND);
 
+/// The smallest and largest size a window can be resized to, including its invisible border
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SizeLimits {
+	pub min_width: i32,
+	pub min_height: i32,
+	pub max_width: i32,
+	pub max_height: i32,
+}
+
 unsafe impl Send for Window {}
 unsafe impl Sync for Window {}
 
@@ -20,6 +51,122 @@ impl Window {
 		Self(handle)
 	}
 
+	/// Every top-level window, from the top of the z-order to the bottom
+	pub fn all() -> Vec<Window> {
+		unsafe extern "system" fn callback(window: HWND, windows: LPARAM) -> BOOL {
+			let windows = &mut *(windows as *mut Vec<Window>);
+			windows.push(Window(window));
+
+			TRUE
+		}
+
+		let mut windows = Vec::new();
+
+		unsafe {
+			EnumWindows(Some(callback), &mut windows as *mut Vec<Window> as LPARAM);
+		}
+
+		windows
+	}
+
+	/// The title of the window
+	pub fn title(self) -> String {
+		unsafe {
+			let length = GetWindowTextLengthW(self.0);
+			let mut title = vec![0u16; length as usize + 1];
+
+			let length = GetWindowTextW(self.0, title.as_mut_ptr(), title.len() as i32);
+
+			String::from_utf16_lossy(&title[..length as usize])
+		}
+	}
+
+	/// The name of the window's class
+	pub fn class_name(self) -> String {
+		unsafe {
+			// Class names are at most 256 characters long
+			let mut name = [0u16; 257];
+			let length = GetClassNameW(self.0, name.as_mut_ptr(), name.len() as i32);
+
+			String::from_utf16_lossy(&name[..length.max(0) as usize])
+		}
+	}
+
+	/// The path of the executable of the process that created the window
+	///
+	/// Returns `None` if the process can't be queried, like processes of other users.
+	pub fn executable(self) -> Option<String> {
+		unsafe {
+			let mut process_id = 0;
+			GetWindowThreadProcessId(self.0, &mut process_id);
+
+			let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, process_id);
+
+			if process.is_null() {
+				return None;
+			}
+
+			let mut path = [0u16; 1024];
+			let mut length = path.len() as u32;
+			let result = QueryFullProcessImageNameW(process, 0, path.as_mut_ptr(), &mut length);
+
+			CloseHandle(process);
+
+			if result == 0 {
+				None
+			} else {
+				Some(String::from_utf16_lossy(&path[..length as usize]))
+			}
+		}
+	}
+
+	/// The window that owns this window, like the main window of a dialog
+	pub fn owner(self) -> Option<Window> {
+		let owner = unsafe { GetWindow(self.0, GW_OWNER) };
+
+		if owner.is_null() {
+			None
+		} else {
+			Some(Window(owner))
+		}
+	}
+
+	/// Does the window still exist
+	pub fn exists(self) -> bool {
+		unsafe { IsWindow(self.0) != 0 }
+	}
+
+	/// Is the window shown
+	pub fn is_visible(self) -> bool {
+		unsafe { IsWindowVisible(self.0) != 0 }
+	}
+
+	/// Is the window hidden by the desktop window manager, like apps on other virtual desktops
+	pub fn is_cloaked(self) -> bool {
+		let mut cloaked: DWORD = 0;
+
+		unsafe {
+			DwmGetWindowAttribute(
+				self.0,
+				DWMWA_CLOAKED,
+				&mut cloaked as *mut DWORD as *mut _,
+				mem::size_of::<DWORD>() as u32,
+			);
+		}
+
+		cloaked != 0
+	}
+
+	/// Is the window minimized
+	pub fn is_minimized(self) -> bool {
+		unsafe { IsIconic(self.0) != 0 }
+	}
+
+	/// Is the window maximized
+	pub fn is_maximized(self) -> bool {
+		unsafe { IsZoomed(self.0) != 0 }
+	}
+
 	pub fn set_pos(&mut self, rect: Rect, insert_after: Option<Window>) {
 		unsafe {
 			SetWindowPos(
@@ -46,19 +193,70 @@ impl Window {
 		}
 	}
 
-	/// Get's the dimensions of the window without the border
-	pub fn transparent_border(self) -> (i32, i32) {
-		let info = self.info();
+	/// The invisible border around the window, taken from the frame the desktop window manager
+	/// draws
+	///
+	/// Windows without such a frame, like ones that aren't shown yet, have no border.
+	pub fn frame_insets(self) -> Insets {
+		let mut frame: RECT = Default::default();
 
-		let x = {
-			(info.window_rect.x - info.client_rect.x) + (info.window_rect.w - info.client_rect.w)
+		let result = unsafe {
+			DwmGetWindowAttribute(
+				self.0,
+				DWMWA_EXTENDED_FRAME_BOUNDS,
+				&mut frame as *mut RECT as *mut _,
+				mem::size_of::<RECT>() as u32,
+			)
 		};
 
-		let y = {
-			(info.window_rect.y - info.client_rect.y) + (info.window_rect.h - info.client_rect.h)
-		};
+		if result != S_OK {
+			return Insets::default();
+		}
+
+		let window: RECT = self.info().window_rect.into();
 
-		(x, y)
+		Insets {
+			left: frame.left - window.left,
+			top: frame.top - window.top,
+			right: window.right - frame.right,
+			bottom: window.bottom - frame.bottom,
+		}
+	}
+
+	/// The part of the window that can be seen, without its invisible border
+	pub fn visible_rect(self) -> Rect {
+		self.info().window_rect.inset(self.frame_insets())
+	}
+
+	/// The smallest and largest size the window accepts
+	///
+	/// Windows that don't answer within 100 milliseconds get the limits of the system.
+	pub fn size_limits(self) -> SizeLimits {
+		unsafe {
+			let mut info: MINMAXINFO = mem::zeroed();
+			info.ptMinTrackSize.x = GetSystemMetrics(SM_CXMINTRACK);
+			info.ptMinTrackSize.y = GetSystemMetrics(SM_CYMINTRACK);
+			info.ptMaxTrackSize.x = GetSystemMetrics(SM_CXMAXTRACK);
+			info.ptMaxTrackSize.y = GetSystemMetrics(SM_CYMAXTRACK);
+
+			let mut result = 0;
+			SendMessageTimeoutW(
+				self.0,
+				WM_GETMINMAXINFO,
+				0,
+				&mut info as *mut MINMAXINFO as LPARAM,
+				SMTO_ABORTIFHUNG,
+				100,
+				&mut result,
+			);
+
+			SizeLimits {
+				min_width: info.ptMinTrackSize.x,
+				min_height: info.ptMinTrackSize.y,
+				max_width: info.ptMaxTrackSize.x,
+				max_height: info.ptMaxTrackSize.y,
+			}
+		}
 	}
 
 	/// Restores the window to it's previous location
@@ -74,6 +272,89 @@ impl Window {
 			ShowWindow(self.0, SW_MINIMIZE);
 		}
 	}
+
+	/// Maximizes the window
+	pub fn maximize(&mut self) {
+		unsafe {
+			ShowWindow(self.0, SW_MAXIMIZE);
+		}
+	}
+
+	/// Hides the window, including its taskbar button
+	pub fn hide(self) {
+		unsafe {
+			ShowWindow(self.0, SW_HIDE);
+		}
+	}
+
+	/// Shows a hidden window in its previous state without activating it
+	pub fn show(self) {
+		unsafe {
+			ShowWindow(self.0, SW_SHOWNA);
+		}
+	}
+
+	/// Brings the window to the front and activates it
+	pub fn focus(self) {
+		unsafe {
+			SetForegroundWindow(self.0);
+		}
+	}
+
+	/// Changes the background color of the window's class and redraws the window
+	pub fn set_background(self, color: Color) {
+		unsafe {
+			let brush = CreateSolidBrush(color.to_colorref());
+			let previous = SetClassLongPtrW(self.0, GCLP_HBRBACKGROUND, brush as isize);
+
+			if previous != 0 {
+				DeleteObject(previous as *mut _);
+			}
+		}
+
+		self.redraw();
+	}
+
+	/// Turns the window into a layered window, so its opacity can be changed
+	pub fn make_layered(self) {
+		unsafe {
+			let extended_styles = GetWindowLongPtrW(self.0, GWL_EXSTYLE);
+
+			if extended_styles & WS_EX_LAYERED as isize == 0 {
+				SetWindowLongPtrW(
+					self.0,
+					GWL_EXSTYLE,
+					extended_styles | WS_EX_LAYERED as isize,
+				);
+			}
+		}
+	}
+
+	/// Sets the opacity of a layered window
+	pub fn set_opacity(self, opacity: u8) {
+		unsafe {
+			SetLayeredWindowAttributes(self.0, 0, opacity, LWA_ALPHA);
+		}
+	}
+
+	/// Sets the opacity of a layered window and makes every pixel of the color `key` invisible
+	pub fn set_color_key(self, key: Color, opacity: u8) {
+		unsafe {
+			SetLayeredWindowAttributes(
+				self.0,
+				key.to_colorref(),
+				opacity,
+				LWA_ALPHA | LWA_COLORKEY,
+			);
+		}
+	}
+
+	/// Invalidates the whole window so it will be redrawn
+	pub fn redraw(self) {
+		unsafe {
+			InvalidateRect(self.0, ptr::null(), 1);
+		}
+	}
 }
 
 impl Default for Window {
@@ -88,6 +369,14 @@ impl PartialEq for Window {
 	}
 }
 
+impl Eq for Window {}
+
+impl Hash for Window {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.0.hash(state);
+	}
+}
+
 /// Info about the window
 #[derive(Debug)]
 pub struct WindowInfo {
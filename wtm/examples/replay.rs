@@ -0,0 +1,39 @@
+//! Replays a recording of wtm's events and prints where the windows would have been placed
+//!
+//! Uses the config in the user's config directory:
+//! `cargo run --example replay -- recording.jsonl`
+
+use std::{env, path::PathBuf, process};
+use wtm::{TilingManager, WtmConfig};
+
+fn main() {
+	let path = match env::args_os().nth(1) {
+		Some(path) => PathBuf::from(path),
+		None => {
+			eprintln!("usage: replay <recording.jsonl>");
+			process::exit(2);
+		}
+	};
+
+	let config = WtmConfig::load().unwrap_or_else(|err| {
+		eprintln!("Failed to load config, using defaults: {}", err);
+		WtmConfig::default()
+	});
+
+	match TilingManager::replay(config, &path) {
+		Ok(placements) => {
+			for placement in placements {
+				let rect = placement.rect;
+
+				println!(
+					"{:>8} ms  window {:#x} to x {} y {} width {} height {}",
+					placement.time, placement.window.0 as usize, rect.x, rect.y, rect.w, rect.h
+				);
+			}
+		}
+		Err(err) => {
+			eprintln!("{}", err);
+			process::exit(1);
+		}
+	}
+}
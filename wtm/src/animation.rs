@@ -0,0 +1,443 @@
+//! Animation module
+
+use serde::{de, Deserialize, Deserializer};
+use std::{
+	fmt,
+	str::FromStr,
+	time::{Duration, Instant},
+};
+use winsapi::{Rect, Window};
+
+/// How the progress of an animation speeds up and slows down
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+	/// The same speed from start to end
+	Linear,
+	/// Starts fast and slows down towards the end
+	EaseOutCubic,
+	/// Overshoots the end a little and settles on it, like a spring
+	Spring,
+}
+
+/// Names of the easing curves that can be used in the config
+const EASINGS: &[(&str, Easing)] = &[
+	("linear", Easing::Linear),
+	("ease-out-cubic", Easing::EaseOutCubic),
+	("spring", Easing::Spring),
+];
+
+/// Tells the time, so animations can be driven by a clock other than the system's
+pub trait Clock {
+	/// The current time
+	fn now(&self) -> Instant;
+}
+
+/// The clock of the system
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SystemClock;
+
+/// A window moving from one rectangle to another
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Animation {
+	/// The window that is moved
+	pub window: Window,
+	/// The window's rectangle when the animation started
+	pub from: Rect,
+	/// The window's rectangle when the animation ends
+	pub to: Rect,
+	/// When the animation started
+	pub start: Instant,
+	/// How long the animation takes
+	pub duration: Duration,
+	/// How the window speeds up and slows down
+	pub easing: Easing,
+}
+
+/// Runs the animations of the windows and decides when their next frame is due
+///
+/// Frames are due at a fixed rate. Each frame shows where the windows should be at the time it is
+/// drawn, so frames that are late don't slow the animations down. Frames that were missed
+/// completely are dropped instead of being drawn in a hurry.
+#[derive(Debug, Clone)]
+pub struct Animator<C: Clock = SystemClock> {
+	clock: C,
+	/// Time between two frames
+	frame_interval: Duration,
+	/// The animations that are running, at most one for each window
+	animations: Vec<Animation>,
+	/// When the next frame is due, if any animation is running
+	next_frame: Option<Instant>,
+}
+
+impl Easing {
+	/// The eased progress at `t`, which goes from 0 at the start to 1 at the end
+	pub fn apply(self, t: f32) -> f32 {
+		let t = t.clamp(0.0, 1.0);
+
+		match self {
+			Easing::Linear => t,
+			Easing::EaseOutCubic => 1.0 - (1.0 - t).powi(3),
+			Easing::Spring => {
+				if t >= 1.0 {
+					return 1.0;
+				}
+
+				// A damped spring with a damping ratio of 0.5 that has settled by the end
+				let (frequency, damping) = (12.0f32, 0.5f32);
+				let decay = damping * frequency;
+				let damped = frequency * (1.0 - damping * damping).sqrt();
+
+				1.0 - (-decay * t).exp()
+					* ((damped * t).cos() + decay / damped * (damped * t).sin())
+			}
+		}
+	}
+}
+
+impl Clock for SystemClock {
+	fn now(&self) -> Instant {
+		Instant::now()
+	}
+}
+
+impl Animation {
+	/// The window's rectangle at `now`, and whether the animation is over
+	pub fn rect_at(&self, now: Instant) -> (Rect, bool) {
+		let elapsed = now.saturating_duration_since(self.start);
+
+		if elapsed >= self.duration {
+			return (self.to, true);
+		}
+
+		let t = elapsed.as_secs_f32() / self.duration.as_secs_f32();
+
+		(interpolate(self.from, self.to, self.easing.apply(t)), false)
+	}
+}
+
+impl<C: Clock> Animator<C> {
+	/// Creates an animator that draws `frame_rate` frames each second
+	pub fn new(clock: C, frame_rate: u32) -> Self {
+		Self {
+			clock,
+			frame_interval: frame_interval(frame_rate),
+			animations: Vec::new(),
+			next_frame: None,
+		}
+	}
+
+	/// Changes how many frames are drawn each second
+	pub fn set_frame_rate(&mut self, frame_rate: u32) {
+		self.frame_interval = frame_interval(frame_rate);
+	}
+
+	/// Starts moving `window` from `from` to `to`
+	///
+	/// An animation of the window that is still running is replaced, so `from` should be where
+	/// that animation left the window.
+	pub fn start(
+		&mut self,
+		window: Window,
+		from: Rect,
+		to: Rect,
+		duration: Duration,
+		easing: Easing,
+	) {
+		self.animations
+			.retain(|animation| animation.window != window);
+
+		let now = self.clock.now();
+
+		self.animations.push(Animation {
+			window,
+			from,
+			to,
+			start: now,
+			duration,
+			easing,
+		});
+
+		if self.next_frame.is_none() {
+			self.next_frame = Some(now + self.frame_interval);
+		}
+	}
+
+	/// Stops the animation of `window`, leaving the window where the last frame put it
+	///
+	/// Returns the rectangle the window was moving to.
+	pub fn cancel(&mut self, window: Window) -> Option<Rect> {
+		let index = self
+			.animations
+			.iter()
+			.position(|animation| animation.window == window)?;
+
+		let animation = self.animations.remove(index);
+
+		if self.animations.is_empty() {
+			self.next_frame = None;
+		}
+
+		Some(animation.to)
+	}
+
+	/// The rectangle `window` is moving to, if it is animated
+	pub fn target(&self, window: Window) -> Option<Rect> {
+		self.animations
+			.iter()
+			.find(|animation| animation.window == window)
+			.map(|animation| animation.to)
+	}
+
+	/// When the next frame is due, if any animation is running
+	pub fn next_frame(&self) -> Option<Instant> {
+		self.next_frame
+	}
+
+	/// Where each animated window has to be moved to right now
+	///
+	/// Animations that are over return their last rectangle and are removed. The next frame is
+	/// scheduled after the current time, skipping the frames that were missed.
+	pub fn frame(&mut self) -> Vec<(Window, Rect)> {
+		let now = self.clock.now();
+		let mut rects = Vec::with_capacity(self.animations.len());
+
+		self.animations.retain(|animation| {
+			let (rect, done) = animation.rect_at(now);
+			rects.push((animation.window, rect));
+
+			!done
+		});
+
+		self.next_frame = if self.animations.is_empty() {
+			None
+		} else {
+			self.next_frame
+				.map(|next_frame| next_frame_after(next_frame, now, self.frame_interval))
+		};
+
+		rects
+	}
+}
+
+impl Default for Animator {
+	fn default() -> Self {
+		Self::new(SystemClock, 60)
+	}
+}
+
+/// The rectangle `progress` of the way from `from` to `to`
+///
+/// Progress above 1 moves past `to`, which lets springs overshoot.
+pub fn interpolate(from: Rect, to: Rect, progress: f32) -> Rect {
+	let lerp = |from: i32, to: i32| from + ((to - from) as f32 * progress).round() as i32;
+
+	Rect {
+		x: lerp(from.x, to.x),
+		y: lerp(from.y, to.y),
+		w: lerp(from.w, to.w),
+		h: lerp(from.h, to.h),
+	}
+}
+
+/// The first frame on the schedule of `scheduled` that comes after `now`
+fn next_frame_after(scheduled: Instant, now: Instant, interval: Duration) -> Instant {
+	if scheduled > now {
+		return scheduled;
+	}
+
+	let missed = (now - scheduled).as_nanos() / interval.as_nanos() + 1;
+
+	scheduled + interval * missed as u32
+}
+
+/// Time between two frames when drawing `frame_rate` frames each second
+fn frame_interval(frame_rate: u32) -> Duration {
+	Duration::from_secs(1) / frame_rate.max(1)
+}
+
+impl FromStr for Easing {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		EASINGS
+			.iter()
+			.find(|(name, _)| name.eq_ignore_ascii_case(s.trim()))
+			.map(|(_, easing)| *easing)
+			.ok_or_else(|| format!("unknown easing `{}`", s))
+	}
+}
+
+impl fmt::Display for Easing {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let (name, _) = EASINGS.iter().find(|(_, easing)| easing == self).unwrap();
+
+		f.write_str(name)
+	}
+}
+
+impl<'de> Deserialize<'de> for Easing {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		String::deserialize(deserializer)?
+			.parse()
+			.map_err(de::Error::custom)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::{cell::Cell, rc::Rc};
+
+	/// A clock that only moves when a test advances it
+	#[derive(Debug, Clone)]
+	struct TestClock(Rc<Cell<Instant>>);
+
+	impl Clock for TestClock {
+		fn now(&self) -> Instant {
+			self.0.get()
+		}
+	}
+
+	impl TestClock {
+		fn advance(&self, millis: u64) {
+			self.0.set(self.0.get() + Duration::from_millis(millis));
+		}
+	}
+
+	const DURATION: Duration = Duration::from_millis(200);
+
+	const FROM: Rect = Rect {
+		x: 0,
+		y: 0,
+		w: 100,
+		h: 100,
+	};
+
+	const TO: Rect = Rect {
+		x: 1000,
+		y: 500,
+		w: 300,
+		h: 200,
+	};
+
+	/// An animator at 50 frames each second, so frames are 20 ms apart
+	fn animator() -> (Animator<TestClock>, TestClock) {
+		let clock = TestClock(Rc::new(Cell::new(Instant::now())));
+
+		(Animator::new(clock.clone(), 50), clock)
+	}
+
+	fn window(id: usize) -> Window {
+		Window(id as _)
+	}
+
+	#[test]
+	fn frames_start_where_the_window_was() {
+		let (mut animator, clock) = animator();
+		animator.start(window(1), FROM, TO, DURATION, Easing::Linear);
+
+		assert_eq!(animator.frame(), vec![(window(1), FROM)]);
+		assert_eq!(
+			animator.next_frame(),
+			Some(clock.now() + Duration::from_millis(20))
+		);
+	}
+
+	#[test]
+	fn frames_halfway_through_are_halfway_there() {
+		let (mut animator, clock) = animator();
+		animator.start(window(1), FROM, TO, DURATION, Easing::Linear);
+
+		clock.advance(100);
+
+		assert_eq!(
+			animator.frame(),
+			vec![(
+				window(1),
+				Rect {
+					x: 500,
+					y: 250,
+					w: 200,
+					h: 150
+				}
+			)]
+		);
+		assert!(animator.next_frame().is_some());
+	}
+
+	#[test]
+	fn the_last_frame_ends_on_the_target_and_stops_the_animation() {
+		let (mut animator, clock) = animator();
+		animator.start(window(1), FROM, TO, DURATION, Easing::Spring);
+
+		clock.advance(250);
+
+		assert_eq!(animator.frame(), vec![(window(1), TO)]);
+		assert_eq!(animator.next_frame(), None);
+		assert_eq!(animator.target(window(1)), None);
+		assert!(animator.frame().is_empty());
+	}
+
+	#[test]
+	fn missed_frames_are_skipped() {
+		let (mut animator, clock) = animator();
+		animator.start(window(1), FROM, TO, DURATION, Easing::Linear);
+		let start = clock.now();
+
+		clock.advance(65);
+		animator.frame();
+
+		assert_eq!(
+			animator.next_frame(),
+			Some(start + Duration::from_millis(80))
+		);
+	}
+
+	#[test]
+	fn starting_again_retargets_the_running_animation() {
+		let (mut animator, clock) = animator();
+		animator.start(window(1), FROM, TO, DURATION, Easing::Linear);
+
+		clock.advance(100);
+		let (_, halfway) = animator.frame()[0];
+
+		animator.start(window(1), halfway, FROM, DURATION, Easing::Linear);
+
+		assert_eq!(animator.target(window(1)), Some(FROM));
+		assert_eq!(animator.frame(), vec![(window(1), halfway)]);
+
+		clock.advance(200);
+
+		assert_eq!(animator.frame(), vec![(window(1), FROM)]);
+		assert_eq!(animator.next_frame(), None);
+	}
+
+	#[test]
+	fn cancelling_returns_the_target_and_leaves_other_windows_moving() {
+		let (mut animator, clock) = animator();
+		animator.start(window(1), FROM, TO, DURATION, Easing::Linear);
+		animator.start(window(2), TO, FROM, DURATION, Easing::Linear);
+
+		assert_eq!(animator.cancel(window(1)), Some(TO));
+		assert_eq!(animator.cancel(window(1)), None);
+		assert!(animator.next_frame().is_some());
+
+		clock.advance(100);
+		assert_eq!(animator.frame().len(), 1);
+
+		assert_eq!(animator.cancel(window(2)), Some(FROM));
+		assert_eq!(animator.next_frame(), None);
+	}
+
+	#[test]
+	fn easings_start_at_zero_and_end_at_one() {
+		for easing in [Easing::Linear, Easing::EaseOutCubic, Easing::Spring] {
+			assert_eq!(easing.apply(0.0), 0.0);
+			assert_eq!(easing.apply(1.0), 1.0);
+			assert_eq!(easing.apply(2.0), 1.0);
+		}
+	}
+}
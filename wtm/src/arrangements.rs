@@ -0,0 +1,653 @@
+//! Arrangements module
+
+use crate::{
+	config::WtmConfig,
+	monitors::relocate,
+	rules::{Pattern, WindowProperties},
+};
+use serde::{de, de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+	collections::BTreeMap,
+	error::Error,
+	fmt, fs, io,
+	path::{Path, PathBuf},
+	str::FromStr,
+};
+use winsapi::Rect;
+
+/// Directory next to the config file that holds the saved arrangements
+const ARRANGEMENT_DIRECTORY: &str = "arrangements";
+
+/// File next to the config file that remembers which arrangement was used with which monitor setup
+const SETUPS_FILE: &str = "setups.toml";
+
+/// Names of the window states used in arrangement files
+const WINDOW_STATES: &[(&str, WindowState)] = &[
+	("normal", WindowState::Normal),
+	("maximized", WindowState::Maximized),
+	("minimized", WindowState::Minimized),
+];
+
+/// Where the windows were when the arrangement was saved
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Arrangement {
+	/// The saved windows, from the top of the z-order to the bottom
+	pub windows: Vec<Entry>,
+}
+
+/// A saved window
+///
+/// Windows are recognized by their executable and class, the title decides between windows of
+/// the same application.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Entry {
+	/// File name of the window's executable, like `slack.exe`
+	pub executable: String,
+	/// Name of the window's class
+	pub class: String,
+	/// The monitor the window was on, counted from the left
+	pub monitor: usize,
+	/// Work area of the monitor, as `[x, y, width, height]`
+	pub monitor_area: [i32; 4],
+	/// Rectangle of the window, as `[x, y, width, height]`, not saved for minimized windows
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub rect: Option<[i32; 4]>,
+	/// Was the window maximized or minimized
+	pub state: WindowState,
+	/// Title of the window, saved as exact text but any pattern can be used
+	pub title: Pattern,
+}
+
+/// Is a window maximized or minimized
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WindowState {
+	/// Neither maximized nor minimized
+	Normal,
+	/// Fills the monitor
+	Maximized,
+	/// Only shown in the taskbar
+	Minimized,
+}
+
+/// How well a window matches an entry
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Match {
+	/// The window has the entry's executable, class and title, holds the index of the window
+	Exact(usize),
+	/// The window only has the entry's executable and class, holds the index of the window
+	Loose(usize),
+	/// No window is left that matches the entry
+	Unmatched,
+}
+
+/// The arrangement that was saved or restored last with each monitor setup
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Setups {
+	/// Name of the arrangement for each fingerprint of a monitor setup
+	pub arrangements: BTreeMap<String, String>,
+}
+
+/// What restoring an arrangement does with each of its entries
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plan {
+	/// Each entry with what happens to it
+	pub steps: Vec<(Entry, Step)>,
+}
+
+/// What happens to a saved window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Step {
+	/// A window is moved to `rect` and gets `state`, `rect` is `None` for minimized windows
+	Move {
+		/// Index of the window
+		window: usize,
+		/// Did only the executable and class match
+		loose: bool,
+		/// Where the window goes
+		rect: Option<Rect>,
+		/// Is the window maximized or minimized afterwards
+		state: WindowState,
+	},
+	/// The window that matched stays where it is, because its monitor is gone
+	MissingMonitor {
+		/// Index of the window
+		window: usize,
+	},
+	/// No window matched
+	Unmatched,
+}
+
+/// Errors that can occur while loading or saving an arrangement
+#[derive(Debug)]
+pub enum ArrangementError {
+	/// The user has no config directory
+	NoDirectory,
+	/// The file couldn't be read or written
+	Io(PathBuf, io::Error),
+	/// The file isn't a valid arrangement
+	Parse(PathBuf, toml::de::Error),
+	/// The arrangement couldn't be written as toml
+	Serialize(toml::ser::Error),
+}
+
+impl Arrangement {
+	/// The file the arrangement called `name` is saved in
+	pub fn path(name: &str) -> Option<PathBuf> {
+		WtmConfig::path()
+			.and_then(|path| path.parent().map(|dir| dir.join(ARRANGEMENT_DIRECTORY)))
+			.map(|dir| dir.join(format!("{}.toml", name)))
+	}
+
+	/// Loads the arrangement called `name`
+	pub fn load(name: &str) -> Result<Self, ArrangementError> {
+		read(&Self::path(name).ok_or(ArrangementError::NoDirectory)?)
+	}
+
+	/// Saves the arrangement as `name`, replacing a previous one with the same name
+	pub fn save(&self, name: &str) -> Result<(), ArrangementError> {
+		let path = Self::path(name).ok_or(ArrangementError::NoDirectory)?;
+
+		write(&path, self)
+	}
+
+	/// What restoring the arrangement does with `windows`
+	///
+	/// `monitors` are the work areas of the monitors, counted from the left. Windows are moved
+	/// to the same place on the monitor with the same number, scaled to its current size.
+	pub fn plan(&self, windows: &[WindowProperties], monitors: &[Rect]) -> Plan {
+		let steps = self
+			.windows
+			.iter()
+			.zip(match_windows(&self.windows, windows))
+			.map(|(entry, matched)| {
+				let (window, loose) = match matched {
+					Match::Exact(window) => (window, false),
+					Match::Loose(window) => (window, true),
+					Match::Unmatched => return (entry.clone(), Step::Unmatched),
+				};
+
+				let step = match monitors.get(entry.monitor.wrapping_sub(1)) {
+					Some(area) => Step::Move {
+						window,
+						loose,
+						rect: entry.rect.map(|rect| {
+							relocate(to_rect(rect), to_rect(entry.monitor_area), *area)
+						}),
+						state: entry.state,
+					},
+					None => Step::MissingMonitor { window },
+				};
+
+				(entry.clone(), step)
+			})
+			.collect();
+
+		Plan { steps }
+	}
+}
+
+impl Setups {
+	/// The file the setups are saved in
+	pub fn path() -> Option<PathBuf> {
+		WtmConfig::path().and_then(|path| path.parent().map(|dir| dir.join(SETUPS_FILE)))
+	}
+
+	/// Loads the setups, there are none if the file doesn't exist yet
+	pub fn load() -> Result<Self, ArrangementError> {
+		match Self::path() {
+			Some(path) if path.exists() => read(&path),
+			Some(_) => Ok(Self::default()),
+			None => Err(ArrangementError::NoDirectory),
+		}
+	}
+
+	/// Remembers that the arrangement `name` was used with the monitor setup `fingerprint`
+	pub fn remember(fingerprint: &str, name: &str) -> Result<(), ArrangementError> {
+		let mut setups = Self::load()?;
+
+		if setups.arrangements.get(fingerprint).map(String::as_str) == Some(name) {
+			return Ok(());
+		}
+
+		setups
+			.arrangements
+			.insert(fingerprint.to_owned(), name.to_owned());
+
+		write(&Self::path().ok_or(ArrangementError::NoDirectory)?, &setups)
+	}
+}
+
+impl Entry {
+	/// Saves a window with `properties` at `rect` on the monitor numbered `monitor`
+	pub fn new(
+		properties: WindowProperties,
+		monitor: usize,
+		monitor_area: Rect,
+		rect: Rect,
+		state: WindowState,
+	) -> Self {
+		Self {
+			executable: properties.executable,
+			class: properties.class,
+			monitor,
+			monitor_area: from_rect(monitor_area),
+			rect: if state == WindowState::Minimized {
+				None
+			} else {
+				Some(from_rect(rect))
+			},
+			state,
+			title: Pattern::exact(&properties.title),
+		}
+	}
+
+	/// Does a window with `properties` match the entry, and does its title match too
+	///
+	/// Returns `None` if the executable or class is different.
+	pub fn matches(&self, properties: &WindowProperties) -> Option<bool> {
+		if self.executable.eq_ignore_ascii_case(&properties.executable)
+			&& self.class == properties.class
+		{
+			Some(self.title.matches(&properties.title))
+		} else {
+			None
+		}
+	}
+}
+
+/// Which of `windows` each of `entries` is restored to
+///
+/// Every window is used for at most one entry. Entries are first matched to windows with the same
+/// title, in the order of the entries, the remaining ones then take any window of the same
+/// application that is left.
+pub fn match_windows(entries: &[Entry], windows: &[WindowProperties]) -> Vec<Match> {
+	let mut matches = vec![Match::Unmatched; entries.len()];
+	let mut used = vec![false; windows.len()];
+
+	for exact in &[true, false] {
+		for (entry, matched) in entries.iter().zip(matches.iter_mut()) {
+			if *matched != Match::Unmatched {
+				continue;
+			}
+
+			let found = (0..windows.len()).find(|index| {
+				!used[*index]
+					&& match entry.matches(&windows[*index]) {
+						Some(title) => title || !exact,
+						None => false,
+					}
+			});
+
+			if let Some(index) = found {
+				used[index] = true;
+				*matched = if *exact {
+					Match::Exact(index)
+				} else {
+					Match::Loose(index)
+				};
+			}
+		}
+	}
+
+	matches
+}
+
+/// Reads a toml file
+fn read<T: DeserializeOwned>(path: &Path) -> Result<T, ArrangementError> {
+	let text =
+		fs::read_to_string(path).map_err(|err| ArrangementError::Io(path.to_owned(), err))?;
+
+	toml::from_str(&text).map_err(|err| ArrangementError::Parse(path.to_owned(), err))
+}
+
+/// Writes `value` to a toml file, creating its directory if needed
+fn write<T: Serialize>(path: &Path, value: &T) -> Result<(), ArrangementError> {
+	let text = toml::to_string(value).map_err(ArrangementError::Serialize)?;
+
+	if let Some(dir) = path.parent() {
+		fs::create_dir_all(dir).map_err(|err| ArrangementError::Io(dir.to_owned(), err))?;
+	}
+
+	fs::write(path, text).map_err(|err| ArrangementError::Io(path.to_owned(), err))
+}
+
+/// Converts `[x, y, width, height]` to a rectangle
+fn to_rect([x, y, w, h]: [i32; 4]) -> Rect {
+	Rect { x, y, w, h }
+}
+
+/// Converts a rectangle to `[x, y, width, height]`
+fn from_rect(rect: Rect) -> [i32; 4] {
+	[rect.x, rect.y, rect.w, rect.h]
+}
+
+impl fmt::Display for Plan {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let moved = self
+			.steps
+			.iter()
+			.filter(|(_, step)| matches!(step, Step::Move { .. }))
+			.count();
+
+		write!(f, "{} of {} windows are restored", moved, self.steps.len())?;
+
+		for (entry, step) in &self.steps {
+			write!(
+				f,
+				"\n  {} {} ({}): ",
+				entry.executable, entry.class, entry.title
+			)?;
+
+			match step {
+				Step::Move {
+					loose, rect, state, ..
+				} => {
+					match rect {
+						Some(rect) => write!(
+							f,
+							"{} at {}, {} with a size of {}x{} on monitor {}",
+							state, rect.x, rect.y, rect.w, rect.h, entry.monitor
+						)?,
+						None => write!(f, "{} on monitor {}", state, entry.monitor)?,
+					}
+
+					if *loose {
+						write!(f, ", the title doesn't match")?;
+					}
+				}
+				Step::MissingMonitor { .. } => {
+					write!(f, "left alone, there is no monitor {}", entry.monitor)?
+				}
+				Step::Unmatched => write!(f, "no window matches")?,
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl FromStr for WindowState {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		WINDOW_STATES
+			.iter()
+			.find(|(name, _)| name.eq_ignore_ascii_case(s.trim()))
+			.map(|(_, state)| *state)
+			.ok_or_else(|| format!("unknown window state `{}`", s))
+	}
+}
+
+impl fmt::Display for WindowState {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let (name, _) = WINDOW_STATES
+			.iter()
+			.find(|(_, state)| state == self)
+			.unwrap();
+
+		f.write_str(name)
+	}
+}
+
+impl Serialize for WindowState {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		serializer.collect_str(self)
+	}
+}
+
+impl<'de> Deserialize<'de> for WindowState {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		String::deserialize(deserializer)?
+			.parse()
+			.map_err(de::Error::custom)
+	}
+}
+
+impl Error for ArrangementError {}
+
+impl fmt::Display for ArrangementError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ArrangementError::NoDirectory => write!(f, "there is no config directory"),
+			ArrangementError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+			ArrangementError::Parse(path, err) => {
+				write!(f, "invalid file {}: {}", path.display(), err)
+			}
+			ArrangementError::Serialize(err) => write!(f, "could not convert to toml: {}", err),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const FULL_HD: Rect = Rect {
+		x: 0,
+		y: 0,
+		w: 1920,
+		h: 1080,
+	};
+
+	fn properties(executable: &str, class: &str, title: &str) -> WindowProperties {
+		WindowProperties {
+			title: title.to_owned(),
+			class: class.to_owned(),
+			executable: executable.to_owned(),
+			..WindowProperties::default()
+		}
+	}
+
+	fn editor(title: &str) -> WindowProperties {
+		properties("code.exe", "Chrome_WidgetWin_1", title)
+	}
+
+	fn entry(properties: WindowProperties, monitor: usize, rect: Rect) -> Entry {
+		Entry::new(properties, monitor, FULL_HD, rect, WindowState::Normal)
+	}
+
+	fn left_half() -> Rect {
+		Rect {
+			x: 0,
+			y: 0,
+			w: 960,
+			h: 1080,
+		}
+	}
+
+	#[test]
+	fn windows_with_the_saved_title_are_matched_first() {
+		let entries = vec![
+			entry(editor("a.rs"), 1, left_half()),
+			entry(editor("b.rs"), 1, left_half()),
+		];
+		let windows = vec![editor("b.rs"), editor("a.rs")];
+
+		assert_eq!(
+			match_windows(&entries, &windows),
+			vec![Match::Exact(1), Match::Exact(0)]
+		);
+	}
+
+	#[test]
+	fn windows_with_another_title_match_loosely() {
+		let entries = vec![
+			entry(editor("a.rs"), 1, left_half()),
+			entry(editor("b.rs"), 1, left_half()),
+		];
+		// The exact match of the second entry wins over the loose match of the first one
+		let windows = vec![editor("b.rs"), editor("c.rs")];
+
+		assert_eq!(
+			match_windows(&entries, &windows),
+			vec![Match::Loose(1), Match::Exact(0)]
+		);
+	}
+
+	#[test]
+	fn other_applications_never_match() {
+		let entries = vec![entry(editor("a.rs"), 1, left_half())];
+
+		let other_executable = properties("notepad.exe", "Chrome_WidgetWin_1", "a.rs");
+		let other_class = properties("code.exe", "Notepad", "a.rs");
+		let other_case = properties("CODE.EXE", "Chrome_WidgetWin_1", "A.RS");
+
+		assert_eq!(
+			match_windows(&entries, &[other_executable, other_class]),
+			vec![Match::Unmatched]
+		);
+		assert_eq!(
+			match_windows(&entries, &[other_case]),
+			vec![Match::Exact(0)]
+		);
+	}
+
+	#[test]
+	fn missing_windows_leave_their_entries_unmatched() {
+		let arrangement = Arrangement {
+			windows: vec![
+				entry(editor("a.rs"), 1, left_half()),
+				entry(properties("slack.exe", "Slack", "Slack"), 1, left_half()),
+			],
+		};
+
+		let plan = arrangement.plan(&[editor("a.rs")], &[FULL_HD]);
+
+		assert!(matches!(plan.steps[0].1, Step::Move { window: 0, .. }));
+		assert_eq!(plan.steps[1].1, Step::Unmatched);
+		assert!(plan.to_string().starts_with("1 of 2 windows are restored"));
+	}
+
+	#[test]
+	fn no_windows_match_nothing() {
+		let arrangement = Arrangement {
+			windows: vec![entry(editor("a.rs"), 1, left_half())],
+		};
+
+		let plan = arrangement.plan(&[], &[FULL_HD]);
+
+		assert_eq!(plan.steps[0].1, Step::Unmatched);
+	}
+
+	#[test]
+	fn duplicate_titles_use_every_window_once() {
+		let entries = vec![
+			entry(editor("Untitled"), 1, left_half()),
+			entry(editor("Untitled"), 1, left_half()),
+			entry(editor("Untitled"), 1, left_half()),
+		];
+
+		assert_eq!(
+			match_windows(&entries, &[editor("Untitled"), editor("Untitled")]),
+			vec![Match::Exact(0), Match::Exact(1), Match::Unmatched]
+		);
+		assert_eq!(
+			match_windows(&entries[..1], &[editor("Untitled"), editor("Untitled")]),
+			vec![Match::Exact(0)]
+		);
+	}
+
+	#[test]
+	fn windows_on_an_unknown_monitor_are_left_alone() {
+		let arrangement = Arrangement {
+			windows: vec![
+				entry(editor("a.rs"), 3, left_half()),
+				entry(editor("b.rs"), 0, left_half()),
+			],
+		};
+
+		let plan = arrangement.plan(&[editor("a.rs"), editor("b.rs")], &[FULL_HD, FULL_HD]);
+
+		assert_eq!(plan.steps[0].1, Step::MissingMonitor { window: 0 });
+		assert_eq!(plan.steps[1].1, Step::MissingMonitor { window: 1 });
+		assert!(plan.to_string().contains("there is no monitor 3"));
+	}
+
+	#[test]
+	fn windows_are_scaled_to_the_monitor_with_their_number() {
+		let arrangement = Arrangement {
+			windows: vec![entry(editor("a.rs"), 2, left_half())],
+		};
+		let monitors = [
+			FULL_HD,
+			Rect {
+				x: 1920,
+				y: -360,
+				w: 2560,
+				h: 1440,
+			},
+		];
+
+		let plan = arrangement.plan(&[editor("a.rs")], &monitors);
+
+		assert_eq!(
+			plan.steps[0].1,
+			Step::Move {
+				window: 0,
+				loose: false,
+				rect: Some(Rect {
+					x: 1920,
+					y: -360,
+					w: 1280,
+					h: 1440,
+				}),
+				state: WindowState::Normal,
+			}
+		);
+	}
+
+	#[test]
+	fn minimized_windows_keep_no_rectangle() {
+		let minimized = Entry::new(
+			editor("a.rs"),
+			1,
+			FULL_HD,
+			left_half(),
+			WindowState::Minimized,
+		);
+		let arrangement = Arrangement {
+			windows: vec![minimized],
+		};
+
+		let plan = arrangement.plan(&[editor("a.rs")], &[FULL_HD]);
+
+		assert_eq!(
+			plan.steps[0].1,
+			Step::Move {
+				window: 0,
+				loose: false,
+				rect: None,
+				state: WindowState::Minimized,
+			}
+		);
+	}
+
+	#[test]
+	fn arrangements_round_trip_through_toml() {
+		let arrangement = Arrangement {
+			windows: vec![
+				entry(editor("a.rs"), 1, left_half()),
+				Entry::new(
+					editor("b.rs"),
+					2,
+					FULL_HD,
+					left_half(),
+					WindowState::Minimized,
+				),
+			],
+		};
+
+		let text = toml::to_string(&arrangement).unwrap();
+
+		assert_eq!(toml::from_str::<Arrangement>(&text).unwrap(), arrangement);
+	}
+}
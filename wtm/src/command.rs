@@ -0,0 +1,67 @@
+//! Command module
+
+use crate::event::Notification;
+use winsapi::{Rect, Window};
+
+/// A side effect the tiling manager asks for while it handles an event
+///
+/// Handling an event only decides what has to happen, the commands are carried out afterwards.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+	/// Creates the grid window at `rect`
+	OpenGridWindow(Rect),
+	/// Creates the preview window of the grid window
+	OpenPreviewWindow,
+	/// Starts watching the active monitor and the foreground window while the grid window is open
+	WatchGridWindow,
+	/// Shows the grid window and brings it to the foreground
+	ShowGridWindow(Window),
+	/// Destroys the grid and preview windows
+	CloseWindows,
+	/// Starts following the cursor while a window is dragged
+	StartDrag,
+	/// Stops following the cursor and destroys the preview window of the dragged window
+	EndDrag,
+	/// Moves a window to `rect`, right behind `insert_after` if set
+	SetPos {
+		/// The window that is moved
+		window: Window,
+		/// Where the window is moved to
+		rect: Rect,
+		/// The window that ends up in front of it
+		insert_after: Option<Window>,
+	},
+	/// Makes a window see-through, holds the opacity
+	SetOpacity(Window, u8),
+	/// Makes the parts of the preview window in the overlay's color key invisible, holds the
+	/// opacity of the rest
+	SetColorKey(Window, u8),
+	/// Asks a window to be painted again
+	Redraw(Window),
+	/// Paints one of the windows drawn by wtm
+	Paint(Window),
+	/// Tells the grid window when the mouse leaves it
+	TrackMouse(Window),
+	/// Moves an application window to `rect`, which includes its invisible border
+	MoveWindow(Window, Rect),
+	/// Asks for the border of a window to be checked again, once the commands before it moved it
+	CheckBorder(Window),
+	/// Restores a minimized or maximized window
+	Restore(Window),
+	/// Maximizes a window
+	Maximize(Window),
+	/// Minimizes a window
+	Minimize(Window),
+	/// Shows a window that was hidden
+	Show(Window),
+	/// Hides a window
+	Hide(Window),
+	/// Creates the window that shows the labels of hint mode at `rect`
+	OpenHintWindow(Rect),
+	/// Destroys the hint window
+	CloseHintWindow,
+	/// Brings a window to the foreground and activates it
+	Focus(Window),
+	/// Sends a notification to the subscribers
+	Notify(Notification),
+}
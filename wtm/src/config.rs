@@ -0,0 +1,1597 @@
+//! Config module
+
+use crate::{
+	animation::Easing,
+	fit::TooSmall,
+	layout::Layout,
+	nudge::NudgeStep,
+	rules::Rule,
+	snap::{Region, SnapTarget},
+	workspaces::MAX_WORKSPACES,
+	zones::{Zone, ZoneSet, MAX_ZONES},
+};
+use serde::{de, Deserialize, Deserializer};
+use std::{
+	error::Error,
+	fmt, fs, io,
+	path::{Path, PathBuf},
+	str::FromStr,
+};
+use winsapi::{Color, Key, KeyCombination, Modifier};
+
+/// Name of the config file inside of the `archer` config directory
+const CONFIG_FILE: &str = "wtm.toml";
+
+/// Name of the profile described by the `[grid]` section
+pub const DEFAULT_PROFILE: &str = "Default";
+
+/// The largest amount of rows or columns the grid can have
+const MAX_TILES: usize = 16;
+
+/// The largest weight a row or column can have
+pub const MAX_WEIGHT: u32 = 16;
+
+/// Settings for wtm
+///
+/// Every section is optional, missing values fall back to their defaults.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WtmConfig {
+	/// Layout of the grid
+	pub grid: GridConfig,
+	/// Size of the tiles drawn in the grid window
+	pub tile: TileConfig,
+	/// Colors used by the grid and preview windows
+	pub colors: ColorConfig,
+	/// Settings for the preview window
+	pub preview: PreviewConfig,
+	/// Global hotkeys
+	pub hotkeys: HotkeyConfig,
+	/// Snapping windows while they are dragged
+	pub drag: DragConfig,
+	/// Hotkeys that move the foreground window without opening the grid window
+	pub snap: Vec<SnapConfig>,
+	/// Sizes a region cycles through when its snap hotkey is pressed repeatedly
+	pub cycle: CycleConfig,
+	/// Automatic tiling of the windows of a monitor
+	pub tiling: TilingConfig,
+	/// Numbered workspaces on each monitor
+	pub workspaces: WorkspaceConfig,
+	/// Moving and resizing windows with the keyboard
+	pub nudge: NudgeConfig,
+	/// Placing windows that can't be resized to fit their area
+	pub placement: PlacementConfig,
+	/// Animating windows while wtm moves them
+	pub animation: AnimationConfig,
+	/// Recording the events wtm handles, to replay them later
+	pub recording: RecordingConfig,
+	/// Picking windows by typing the labels shown on them
+	pub hints: HintConfig,
+	/// Per-application behavior, the first rule that matches a window is used
+	pub rules: Vec<Rule>,
+	/// Named window arrangements that can be saved and restored
+	pub arrangements: Vec<ArrangementConfig>,
+	/// Additional grid layouts, selectable with F2 and up in the grid window
+	pub profiles: Vec<ProfileConfig>,
+	/// Freely placed zones, selectable with Tab in the grid window
+	pub zone_sets: Vec<ZoneSet>,
+}
+
+/// Layout of the grid
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GridConfig {
+	/// Amount of rows in the grid
+	pub rows: usize,
+	/// Amount of columns in the grid
+	pub columns: usize,
+	/// Space between the tiles of the grid window
+	pub grid_margins: u8,
+	/// Space between zones
+	pub zone_margins: u8,
+	/// Space between the zones and the edge of the monitor
+	pub border_margins: u8,
+	/// Relative height of each row, all rows are equally high if empty
+	pub row_weights: Vec<u32>,
+	/// Relative width of each column, all columns are equally wide if empty
+	pub column_weights: Vec<u32>,
+	/// Tiles that form a single zone
+	pub merged: Vec<MergedCell>,
+}
+
+/// Tiles that are combined into a single zone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MergedCell {
+	/// Row of the top left tile, counted from 0
+	pub row: usize,
+	/// Column of the top left tile, counted from 0
+	pub column: usize,
+	/// Amount of rows covered by the cell
+	#[serde(default = "default_span")]
+	pub rows: usize,
+	/// Amount of columns covered by the cell
+	#[serde(default = "default_span")]
+	pub columns: usize,
+}
+
+/// A named grid layout
+///
+/// Values that are left out are taken from the `[grid]` section.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileConfig {
+	/// Name of the profile
+	pub name: String,
+	/// Amount of rows in the grid
+	pub rows: Option<usize>,
+	/// Amount of columns in the grid
+	pub columns: Option<usize>,
+	/// Space between zones
+	pub zone_margins: Option<u8>,
+	/// Space between the zones and the edge of the monitor
+	pub border_margins: Option<u8>,
+	/// Relative height of each row
+	pub row_weights: Option<Vec<u32>>,
+	/// Relative width of each column
+	pub column_weights: Option<Vec<u32>>,
+	/// Tiles that form a single zone
+	pub merged: Option<Vec<MergedCell>>,
+}
+
+/// A grid layout with all of its values resolved
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+	/// Name of the profile
+	pub name: String,
+	/// Amount of rows in the grid
+	pub rows: usize,
+	/// Amount of columns in the grid
+	pub columns: usize,
+	/// Space between zones
+	pub zone_margins: u8,
+	/// Space between the zones and the edge of the monitor
+	pub border_margins: u8,
+	/// Relative height of each row, all rows are equally high if empty
+	pub row_weights: Vec<u32>,
+	/// Relative width of each column, all columns are equally wide if empty
+	pub column_weights: Vec<u32>,
+	/// Tiles that form a single zone
+	pub merged: Vec<MergedCell>,
+}
+
+/// Size of the tiles drawn in the grid window
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TileConfig {
+	/// Width of a tile
+	pub width: u32,
+	/// Height of a tile
+	pub height: u32,
+}
+
+/// Colors used by the grid and preview windows
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColorConfig {
+	/// Background of the grid window
+	#[serde(deserialize_with = "deserialize_color")]
+	pub background: Color,
+	/// A tile that is neither hovered nor selected
+	#[serde(deserialize_with = "deserialize_color")]
+	pub tile: Color,
+	/// A hovered tile
+	#[serde(deserialize_with = "deserialize_color")]
+	pub hovered: Color,
+	/// A selected tile
+	#[serde(deserialize_with = "deserialize_color")]
+	pub selected: Color,
+	/// The frame around each tile
+	#[serde(deserialize_with = "deserialize_color")]
+	pub border: Color,
+	/// The preview window
+	#[serde(deserialize_with = "deserialize_color")]
+	pub preview: Color,
+}
+
+/// Settings for the preview window
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PreviewConfig {
+	/// Opacity of the preview window, from 1 to 255
+	pub opacity: u8,
+}
+
+/// Global hotkeys
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HotkeyConfig {
+	/// Opens the grid window
+	#[serde(deserialize_with = "deserialize_hotkey")]
+	pub main: KeyCombination,
+	/// Opens the grid window and closes it after one resize
+	#[serde(deserialize_with = "deserialize_hotkey")]
+	pub quick_resize: KeyCombination,
+	/// Switches the active monitor to the next profile
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub next_profile: Option<KeyCombination>,
+	/// Tiles the windows of the active monitor
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub tile: Option<KeyCombination>,
+	/// Tiles the windows of the active monitor with the next layout
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub next_layout: Option<KeyCombination>,
+	/// Widens the master area
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub grow_master: Option<KeyCombination>,
+	/// Narrows the master area
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub shrink_master: Option<KeyCombination>,
+	/// Moves one more window into the master area
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub add_master: Option<KeyCombination>,
+	/// Moves one window out of the master area
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub remove_master: Option<KeyCombination>,
+	/// Applies the rules to every window again
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub apply_rules: Option<KeyCombination>,
+	/// Logs which rule matches the foreground window
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub explain_rules: Option<KeyCombination>,
+	/// Undoes the last move
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub undo: Option<KeyCombination>,
+	/// Redoes the last undone move
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub redo: Option<KeyCombination>,
+	/// Moves the foreground window back to where it was before wtm moved it
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub restore_window: Option<KeyCombination>,
+	/// Moves every window back to where it was before wtm moved it
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub restore_all: Option<KeyCombination>,
+	/// Sends the foreground window to the next monitor, counted from the left
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub next_monitor: Option<KeyCombination>,
+	/// Sends the foreground window to the previous monitor, counted from the left
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub previous_monitor: Option<KeyCombination>,
+	/// Sends the foreground window to the monitor on the left
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub monitor_left: Option<KeyCombination>,
+	/// Sends the foreground window to the monitor on the right
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub monitor_right: Option<KeyCombination>,
+	/// Sends the foreground window to the monitor above
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub monitor_up: Option<KeyCombination>,
+	/// Sends the foreground window to the monitor below
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub monitor_down: Option<KeyCombination>,
+	/// Focuses the window on the left of the foreground window
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub focus_left: Option<KeyCombination>,
+	/// Focuses the window on the right of the foreground window
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub focus_right: Option<KeyCombination>,
+	/// Focuses the window above of the foreground window
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub focus_up: Option<KeyCombination>,
+	/// Focuses the window below of the foreground window
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub focus_down: Option<KeyCombination>,
+	/// Swaps the foreground window with the window on the left of it
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub swap_left: Option<KeyCombination>,
+	/// Swaps the foreground window with the window on the right of it
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub swap_right: Option<KeyCombination>,
+	/// Swaps the foreground window with the window above of it
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub swap_up: Option<KeyCombination>,
+	/// Swaps the foreground window with the window below of it
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub swap_down: Option<KeyCombination>,
+	/// Moves the foreground window left by one step
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub move_left: Option<KeyCombination>,
+	/// Moves the foreground window right by one step
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub move_right: Option<KeyCombination>,
+	/// Moves the foreground window up by one step
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub move_up: Option<KeyCombination>,
+	/// Moves the foreground window down by one step
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub move_down: Option<KeyCombination>,
+	/// Moves the left edge of the foreground window outwards by one step
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub grow_left: Option<KeyCombination>,
+	/// Moves the right edge of the foreground window outwards by one step
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub grow_right: Option<KeyCombination>,
+	/// Moves the top edge of the foreground window outwards by one step
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub grow_up: Option<KeyCombination>,
+	/// Moves the bottom edge of the foreground window outwards by one step
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub grow_down: Option<KeyCombination>,
+	/// Moves the left edge of the foreground window inwards by one step
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub shrink_left: Option<KeyCombination>,
+	/// Moves the right edge of the foreground window inwards by one step
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub shrink_right: Option<KeyCombination>,
+	/// Moves the top edge of the foreground window inwards by one step
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub shrink_up: Option<KeyCombination>,
+	/// Moves the bottom edge of the foreground window inwards by one step
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub shrink_down: Option<KeyCombination>,
+	/// Moves the foreground window to the center of its monitor
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub center: Option<KeyCombination>,
+	/// Labels the windows, typing a label focuses that window
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub hint_focus: Option<KeyCombination>,
+	/// Labels the windows, typing a label swaps that window with the foreground window
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub hint_swap: Option<KeyCombination>,
+	/// Labels the windows, typing a label and then the number of a zone moves that window there
+	#[serde(deserialize_with = "deserialize_optional_hotkey")]
+	pub hint_zone: Option<KeyCombination>,
+	/// Hotkeys that send the foreground window to the monitor with the same number
+	#[serde(deserialize_with = "deserialize_hotkeys")]
+	pub send_to_monitor: Vec<KeyCombination>,
+}
+
+/// Automatic tiling of the windows of a monitor
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TilingConfig {
+	/// The layouts `hotkeys.next_layout` switches between, the first one is used at first
+	pub layouts: Vec<Layout>,
+	/// Width of the master area, as a fraction of the work area
+	pub master_ratio: f32,
+	/// How much `hotkeys.grow_master` and `hotkeys.shrink_master` change the master ratio
+	pub master_ratio_step: f32,
+	/// Amount of windows in the master area
+	pub master_count: usize,
+	/// Space between the windows and to the edges of the work area
+	pub gaps: u8,
+}
+
+/// Moving and resizing windows with the keyboard
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NudgeConfig {
+	/// How far a window moves or grows, in pixels like `20` or as a fraction of the work area like
+	/// `"1/24"`
+	#[serde(deserialize_with = "deserialize_step")]
+	pub step: NudgeStep,
+	/// How close an edge has to get to the edge of the monitor or another window to snap to it
+	pub snap_distance: u16,
+}
+
+/// Placing windows that can't be resized to fit their area
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PlacementConfig {
+	/// What happens to windows that can't be made as small as their area, `"center"`, `"expand"`
+	/// or `"float"`
+	pub too_small: TooSmall,
+}
+
+/// Animating windows while wtm moves them
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AnimationConfig {
+	/// Are moves animated
+	pub enabled: bool,
+	/// How long a move takes, in milliseconds
+	pub duration: u32,
+	/// How the window speeds up and slows down, `"linear"`, `"ease-out-cubic"` or `"spring"`
+	pub easing: Easing,
+	/// How many frames are drawn each second
+	pub frame_rate: u32,
+}
+
+/// Recording the events wtm handles, to replay them later
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RecordingConfig {
+	/// Are the events written to `recording.jsonl` next to the config file
+	pub enabled: bool,
+}
+
+/// Picking windows by typing the labels shown on them
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HintConfig {
+	/// The letters the labels are made of, the first ones end up on the windows closest to the
+	/// cursor
+	pub alphabet: String,
+}
+
+/// Snapping windows while they are dragged
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DragConfig {
+	/// Can windows be snapped by dragging them
+	pub enabled: bool,
+	/// Shows the zones while a window is dragged
+	#[serde(deserialize_with = "deserialize_modifier")]
+	pub modifier: Modifier,
+	/// Spans the zones between the first and the current zone while held with `modifier`
+	#[serde(deserialize_with = "deserialize_modifier")]
+	pub span_modifier: Modifier,
+}
+
+/// Numbered workspaces on each monitor
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WorkspaceConfig {
+	/// Amount of workspaces on each monitor
+	pub count: usize,
+	/// Hotkeys that switch the active monitor to the workspace with the same number
+	#[serde(deserialize_with = "deserialize_hotkeys")]
+	pub switch: Vec<KeyCombination>,
+	/// Hotkeys that move the foreground window to the workspace with the same number
+	#[serde(rename = "move", deserialize_with = "deserialize_hotkeys")]
+	pub move_window: Vec<KeyCombination>,
+}
+
+/// Hotkeys that save and restore a named arrangement of the windows
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ArrangementConfig {
+	/// Name of the arrangement, also the name of the file it is saved in
+	pub name: String,
+	/// Saves where the windows are
+	#[serde(default, deserialize_with = "deserialize_optional_hotkey")]
+	pub save: Option<KeyCombination>,
+	/// Moves the windows back to where they were saved
+	#[serde(default, deserialize_with = "deserialize_optional_hotkey")]
+	pub restore: Option<KeyCombination>,
+	/// Logs what restoring the arrangement would do, without moving any window
+	#[serde(default, deserialize_with = "deserialize_optional_hotkey")]
+	pub dry_run: Option<KeyCombination>,
+}
+
+/// A hotkey that moves the foreground window without opening the grid window
+///
+/// Exactly one of `region`, `cell` and `zone` has to be set.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SnapConfig {
+	/// The hotkey
+	#[serde(deserialize_with = "deserialize_hotkey")]
+	pub hotkey: KeyCombination,
+	/// A named part of the monitor, like `left-half`
+	pub region: Option<Region>,
+	/// Tiles of the monitor's grid, in the same format as merged cells
+	pub cell: Option<MergedCell>,
+	/// A freely placed zone, in the same format as the zones of a zone set
+	pub zone: Option<Zone>,
+}
+
+/// Sizes a region cycles through when its snap hotkey is pressed repeatedly
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CycleConfig {
+	/// Fractions of the work area, written as `"1/3"` or `0.5`
+	#[serde(deserialize_with = "deserialize_ratios")]
+	pub ratios: Vec<f32>,
+}
+
+impl ArrangementConfig {
+	/// The hotkeys of the arrangement with the names of their fields
+	pub fn hotkeys(&self) -> [(&'static str, Option<KeyCombination>); 3] {
+		[
+			("save", self.save),
+			("restore", self.restore),
+			("dry_run", self.dry_run),
+		]
+	}
+}
+
+impl SnapConfig {
+	/// Where the window is moved to
+	pub fn target(&self) -> Option<SnapTarget> {
+		match (self.region, self.cell, self.zone) {
+			(Some(region), None, None) => Some(SnapTarget::Region(region)),
+			(None, Some(cell), None) => Some(SnapTarget::Cell(cell)),
+			(None, None, Some(zone)) => Some(SnapTarget::Zone(zone)),
+			_ => None,
+		}
+	}
+}
+
+impl WtmConfig {
+	/// The location of the config file in the user's config directory
+	pub fn path() -> Option<PathBuf> {
+		dirs::config_dir().map(|dir| dir.join("archer").join(CONFIG_FILE))
+	}
+
+	/// Loads the config from the user's config directory
+	///
+	/// The default config is returned if the file doesn't exist.
+	pub fn load() -> Result<Self, ConfigError> {
+		match Self::path() {
+			Some(path) if path.exists() => Self::load_from(&path),
+			_ => Ok(Self::default()),
+		}
+	}
+
+	/// Loads the config from `path`
+	pub fn load_from(path: &Path) -> Result<Self, ConfigError> {
+		fs::read_to_string(path)
+			.map_err(|err| ConfigError::Io(path.to_owned(), err))?
+			.parse()
+	}
+
+	/// All profiles, starting with the `Default` profile described by the `[grid]` section
+	///
+	/// Weights and merged cells are only inherited by profiles that keep the amount of rows and
+	/// columns they refer to.
+	pub fn profiles(&self) -> Vec<Profile> {
+		let default = Profile {
+			name: DEFAULT_PROFILE.to_owned(),
+			rows: self.grid.rows,
+			columns: self.grid.columns,
+			zone_margins: self.grid.zone_margins,
+			border_margins: self.grid.border_margins,
+			row_weights: self.grid.row_weights.clone(),
+			column_weights: self.grid.column_weights.clone(),
+			merged: self.grid.merged.clone(),
+		};
+
+		let others = self.profiles.iter().map(|profile| {
+			let same_rows = profile.rows.is_none();
+			let same_columns = profile.columns.is_none();
+
+			Profile {
+				name: profile.name.clone(),
+				rows: profile.rows.unwrap_or(default.rows),
+				columns: profile.columns.unwrap_or(default.columns),
+				zone_margins: profile.zone_margins.unwrap_or(default.zone_margins),
+				border_margins: profile.border_margins.unwrap_or(default.border_margins),
+				row_weights: inherit(&profile.row_weights, &default.row_weights, same_rows),
+				column_weights: inherit(
+					&profile.column_weights,
+					&default.column_weights,
+					same_columns,
+				),
+				merged: inherit(&profile.merged, &default.merged, same_rows && same_columns),
+			}
+		});
+
+		let mut profiles = vec![default.clone()];
+		profiles.extend(others);
+		profiles
+	}
+
+	/// Checks the values that can't be enforced by their types
+	pub fn validate(&self) -> Result<(), ConfigError> {
+		check_range("grid.rows", self.grid.rows, 1, MAX_TILES)?;
+		check_range("grid.columns", self.grid.columns, 1, MAX_TILES)?;
+		check_range("tile.width", self.tile.width, 8, 512)?;
+		check_range("tile.height", self.tile.height, 8, 512)?;
+		check_range("preview.opacity", self.preview.opacity, 1, 255)?;
+
+		if self.drag.modifier == self.drag.span_modifier {
+			return Err(ConfigError::invalid(
+				"drag.span_modifier",
+				"has to differ from `drag.modifier`",
+			));
+		}
+
+		check_range("workspaces.count", self.workspaces.count, 1, MAX_WORKSPACES)?;
+
+		let workspace_hotkeys = [
+			("switch", &self.workspaces.switch),
+			("move", &self.workspaces.move_window),
+		];
+
+		for (field, hotkeys) in workspace_hotkeys.iter() {
+			if hotkeys.len() > self.workspaces.count {
+				return Err(ConfigError::invalid(
+					format!("workspaces.{}", field),
+					format!(
+						"has more hotkeys than the {} workspaces",
+						self.workspaces.count
+					),
+				));
+			}
+		}
+
+		let mut hotkeys = self.hotkeys.bindings();
+		hotkeys.extend(
+			self.snap
+				.iter()
+				.enumerate()
+				.map(|(i, snap)| (format!("snap[{}].hotkey", i), snap.hotkey)),
+		);
+
+		for (field, workspace_hotkeys) in workspace_hotkeys.iter() {
+			hotkeys.extend(
+				workspace_hotkeys
+					.iter()
+					.enumerate()
+					.map(|(i, hotkey)| (format!("workspaces.{}[{}]", field, i), *hotkey)),
+			);
+		}
+
+		for (i, arrangement) in self.arrangements.iter().enumerate() {
+			hotkeys.extend(arrangement.hotkeys().iter().filter_map(|(field, hotkey)| {
+				hotkey.map(|hotkey| (format!("arrangements[{}].{}", i, field), hotkey))
+			}));
+		}
+
+		for (i, (key, hotkey)) in hotkeys.iter().enumerate() {
+			if let Some((other, _)) = hotkeys[..i].iter().find(|(_, other)| other == hotkey) {
+				return Err(ConfigError::invalid(
+					key.as_str(),
+					format!("is already bound to `{}`", other),
+				));
+			}
+		}
+
+		for (i, profile) in self.profiles.iter().enumerate() {
+			let key = |field: &str| format!("profiles[{}].{}", i, field);
+
+			if profile.name.is_empty() {
+				return Err(ConfigError::invalid(key("name"), "can not be empty"));
+			}
+
+			if profile.name == DEFAULT_PROFILE
+				|| self.profiles[..i].iter().any(|p| p.name == profile.name)
+			{
+				return Err(ConfigError::invalid(
+					key("name"),
+					format!("a profile named `{}` already exists", profile.name),
+				));
+			}
+
+			if let Some(rows) = profile.rows {
+				check_range(&key("rows"), rows, 1, MAX_TILES)?;
+			}
+
+			if let Some(columns) = profile.columns {
+				check_range(&key("columns"), columns, 1, MAX_TILES)?;
+			}
+		}
+
+		for (i, profile) in self.profiles().iter().enumerate() {
+			let section = match i {
+				0 => "grid".to_owned(),
+				i => format!("profiles[{}]", i - 1),
+			};
+
+			profile.validate_layout(&section)?;
+		}
+
+		for (i, zone_set) in self.zone_sets.iter().enumerate() {
+			let key = |field: &str| format!("zone_sets[{}].{}", i, field);
+
+			if zone_set.name.is_empty() {
+				return Err(ConfigError::invalid(key("name"), "can not be empty"));
+			}
+
+			if self.zone_sets[..i].iter().any(|z| z.name == zone_set.name) {
+				return Err(ConfigError::invalid(
+					key("name"),
+					format!("a zone set named `{}` already exists", zone_set.name),
+				));
+			}
+
+			check_range(&key("zones"), zone_set.zones.len(), 1, MAX_ZONES)?;
+
+			for (j, zone) in zone_set.zones.iter().enumerate() {
+				validate_zone(&key(&format!("zones[{}]", j)), zone)?;
+			}
+		}
+
+		for (i, snap) in self.snap.iter().enumerate() {
+			let section = format!("snap[{}]", i);
+
+			match snap.target() {
+				Some(target) => validate_target(&section, &target)?,
+				None => {
+					return Err(ConfigError::invalid(
+						section,
+						"needs exactly one of `region`, `cell` and `zone`",
+					))
+				}
+			}
+		}
+
+		if self.tiling.layouts.is_empty() {
+			return Err(ConfigError::invalid(
+				"tiling.layouts",
+				"needs at least one layout",
+			));
+		}
+
+		check_range("tiling.master_ratio", self.tiling.master_ratio, 0.1, 0.9)?;
+		check_range(
+			"tiling.master_ratio_step",
+			self.tiling.master_ratio_step,
+			0.01,
+			0.5,
+		)?;
+		check_range(
+			"tiling.master_count",
+			self.tiling.master_count,
+			0,
+			MAX_TILES,
+		)?;
+
+		for (i, rule) in self.rules.iter().enumerate() {
+			let key = |field: &str| format!("rules[{}].{}", i, field);
+
+			if !rule.has_matchers() {
+				return Err(ConfigError::invalid(
+					format!("rules[{}]", i),
+					"needs at least one of `title`, `class`, `executable` and `style`",
+				));
+			}
+
+			let targets = [
+				rule.region.is_some(),
+				rule.cell.is_some(),
+				rule.zone.is_some(),
+			];
+			if targets.iter().filter(|target| **target).count() > 1 {
+				return Err(ConfigError::invalid(
+					format!("rules[{}]", i),
+					"can only have one of `region`, `cell` and `zone`",
+				));
+			}
+
+			let has_actions = rule.float
+				|| rule.target().is_some()
+				|| rule.monitor.is_some()
+				|| rule.workspace.is_some()
+				|| rule.opacity.is_some()
+				|| rule.frame.is_some();
+
+			if rule.ignore && has_actions {
+				return Err(ConfigError::invalid(
+					key("ignore"),
+					"can not be combined with other actions",
+				));
+			}
+
+			if let Some(target) = rule.target() {
+				validate_target(&format!("rules[{}]", i), &target)?;
+			}
+
+			if let Some(monitor) = rule.monitor {
+				if monitor == 0 {
+					return Err(ConfigError::invalid(key("monitor"), "starts at 1"));
+				}
+			}
+
+			if let Some(workspace) = rule.workspace {
+				check_range(&key("workspace"), workspace, 1, self.workspaces.count)?;
+			}
+
+			if let Some(opacity) = rule.opacity {
+				check_range(&key("opacity"), opacity, 1, 255)?;
+			}
+
+			if let Some(frame) = rule.frame {
+				for (side, inset) in frame.iter().enumerate() {
+					check_range(&format!("{}[{}]", key("frame"), side), *inset, 0, 64)?;
+				}
+			}
+		}
+
+		for (i, arrangement) in self.arrangements.iter().enumerate() {
+			let key = format!("arrangements[{}].name", i);
+			let name = &arrangement.name;
+
+			if name.trim().is_empty() {
+				return Err(ConfigError::invalid(key, "can not be empty"));
+			}
+
+			if name.contains(|c: char| "\\/:*?\"<>|".contains(c) || c.is_control()) {
+				return Err(ConfigError::invalid(
+					key,
+					"can not contain characters that aren't allowed in file names",
+				));
+			}
+
+			if self.arrangements[..i].iter().any(|a| a.name == *name) {
+				return Err(ConfigError::invalid(
+					key,
+					format!("an arrangement named `{}` already exists", name),
+				));
+			}
+		}
+
+		check_range("animation.duration", self.animation.duration, 1, 2000)?;
+		check_range("animation.frame_rate", self.animation.frame_rate, 10, 240)?;
+
+		match self.nudge.step {
+			NudgeStep::Pixels(pixels) => check_range("nudge.step", pixels, 1, 10000)?,
+			// Written this way so `NaN` is rejected as well
+			NudgeStep::Fraction(fraction) if !(fraction > 0.0 && fraction <= 1.0) => {
+				return Err(ConfigError::invalid(
+					"nudge.step",
+					format!("{} is not between 0 and 1", fraction),
+				))
+			}
+			NudgeStep::Fraction(_) => {}
+		}
+
+		let letters = self.hints.letters();
+
+		if letters.len() < 2 || !letters.iter().all(char::is_ascii_lowercase) {
+			return Err(ConfigError::invalid(
+				"hints.alphabet",
+				"has to consist of at least two letters from a to z",
+			));
+		}
+
+		for (i, letter) in letters.iter().enumerate() {
+			if letters[..i].contains(letter) {
+				return Err(ConfigError::invalid(
+					"hints.alphabet",
+					format!("contains `{}` more than once", letter),
+				));
+			}
+		}
+
+		if self.hotkeys.hint_zone.is_some() && self.zone_sets.is_empty() {
+			return Err(ConfigError::invalid(
+				"hotkeys.hint_zone",
+				"needs at least one zone set",
+			));
+		}
+
+		for (i, ratio) in self.cycle.ratios.iter().enumerate() {
+			// Written this way so `NaN` is rejected as well
+			if !(*ratio > 0.0 && *ratio <= 1.0) {
+				return Err(ConfigError::invalid(
+					format!("cycle.ratios[{}]", i),
+					format!("{} is not between 0 and 1", ratio),
+				));
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl Profile {
+	/// Checks that the weights and merged cells fit the amount of rows and columns
+	fn validate_layout(&self, section: &str) -> Result<(), ConfigError> {
+		let weights = [
+			("row_weights", &self.row_weights, self.rows),
+			("column_weights", &self.column_weights, self.columns),
+		];
+
+		for (name, weights, count) in weights.iter() {
+			let key = format!("{}.{}", section, name);
+
+			if !weights.is_empty() && weights.len() != *count {
+				return Err(ConfigError::invalid(
+					key,
+					format!("has {} weights for {} tiles", weights.len(), count),
+				));
+			}
+
+			for weight in weights.iter() {
+				check_range(&key, *weight, 1, MAX_WEIGHT)?;
+			}
+		}
+
+		for (i, cell) in self.merged.iter().enumerate() {
+			let key = format!("{}.merged[{}]", section, i);
+
+			if cell.rows == 0 || cell.columns == 0 {
+				return Err(ConfigError::invalid(key, "has to cover at least one tile"));
+			}
+
+			if cell.row + cell.rows > self.rows || cell.column + cell.columns > self.columns {
+				return Err(ConfigError::invalid(
+					key,
+					format!("doesn't fit into a {}x{} grid", self.rows, self.columns),
+				));
+			}
+
+			let (from, to) = cell.span();
+
+			if self.merged[..i]
+				.iter()
+				.any(|other| other.intersects(from, to))
+			{
+				return Err(ConfigError::invalid(key, "overlaps another merged cell"));
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl MergedCell {
+	/// Creates a merged cell from its first and last tile
+	pub fn from_span(from: (usize, usize), to: (usize, usize)) -> Self {
+		Self {
+			row: from.0,
+			column: from.1,
+			rows: to.0 - from.0 + 1,
+			columns: to.1 - from.1 + 1,
+		}
+	}
+
+	/// The first and last tile of the cell
+	pub fn span(&self) -> ((usize, usize), (usize, usize)) {
+		(
+			(self.row, self.column),
+			(self.row + self.rows - 1, self.column + self.columns - 1),
+		)
+	}
+
+	/// Does the cell share a tile with the span from `from` to `to`
+	pub fn intersects(&self, from: (usize, usize), to: (usize, usize)) -> bool {
+		let (cell_from, cell_to) = self.span();
+
+		cell_from.0 <= to.0 && cell_to.0 >= from.0 && cell_from.1 <= to.1 && cell_to.1 >= from.1
+	}
+
+	/// Shrinks the cell to fit into a grid of `rows` by `columns`
+	///
+	/// Returns `None` if less than two tiles are left.
+	pub fn clamp(&self, rows: usize, columns: usize) -> Option<Self> {
+		if self.row >= rows || self.column >= columns {
+			return None;
+		}
+
+		let cell = Self {
+			rows: self.rows.min(rows - self.row),
+			columns: self.columns.min(columns - self.column),
+			..*self
+		};
+
+		if cell.rows * cell.columns > 1 {
+			Some(cell)
+		} else {
+			None
+		}
+	}
+}
+
+impl HotkeyConfig {
+	/// Every bound hotkey together with its key in the config
+	pub fn bindings(&self) -> Vec<(String, KeyCombination)> {
+		let mut bindings = vec![
+			("hotkeys.main".to_owned(), self.main),
+			("hotkeys.quick_resize".to_owned(), self.quick_resize),
+		];
+
+		let optional = [
+			("next_profile", self.next_profile),
+			("tile", self.tile),
+			("next_layout", self.next_layout),
+			("grow_master", self.grow_master),
+			("shrink_master", self.shrink_master),
+			("add_master", self.add_master),
+			("remove_master", self.remove_master),
+			("apply_rules", self.apply_rules),
+			("explain_rules", self.explain_rules),
+			("undo", self.undo),
+			("redo", self.redo),
+			("restore_window", self.restore_window),
+			("restore_all", self.restore_all),
+			("next_monitor", self.next_monitor),
+			("previous_monitor", self.previous_monitor),
+			("monitor_left", self.monitor_left),
+			("monitor_right", self.monitor_right),
+			("monitor_up", self.monitor_up),
+			("monitor_down", self.monitor_down),
+			("focus_left", self.focus_left),
+			("focus_right", self.focus_right),
+			("focus_up", self.focus_up),
+			("focus_down", self.focus_down),
+			("swap_left", self.swap_left),
+			("swap_right", self.swap_right),
+			("swap_up", self.swap_up),
+			("swap_down", self.swap_down),
+			("move_left", self.move_left),
+			("move_right", self.move_right),
+			("move_up", self.move_up),
+			("move_down", self.move_down),
+			("grow_left", self.grow_left),
+			("grow_right", self.grow_right),
+			("grow_up", self.grow_up),
+			("grow_down", self.grow_down),
+			("shrink_left", self.shrink_left),
+			("shrink_right", self.shrink_right),
+			("shrink_up", self.shrink_up),
+			("shrink_down", self.shrink_down),
+			("center", self.center),
+			("hint_focus", self.hint_focus),
+			("hint_swap", self.hint_swap),
+			("hint_zone", self.hint_zone),
+		];
+
+		for (name, hotkey) in optional.iter() {
+			if let Some(hotkey) = hotkey {
+				bindings.push((format!("hotkeys.{}", name), *hotkey));
+			}
+		}
+
+		for (i, hotkey) in self.send_to_monitor.iter().enumerate() {
+			bindings.push((format!("hotkeys.send_to_monitor[{}]", i), *hotkey));
+		}
+
+		bindings
+	}
+}
+
+impl HintConfig {
+	/// The letters of the alphabet, in lower case
+	pub fn letters(&self) -> Vec<char> {
+		self.alphabet
+			.chars()
+			.map(|c| c.to_ascii_lowercase())
+			.collect()
+	}
+}
+
+impl FromStr for WtmConfig {
+	type Err = ConfigError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let config: WtmConfig = toml::from_str(s).map_err(ConfigError::Parse)?;
+		config.validate()?;
+
+		Ok(config)
+	}
+}
+
+impl Default for GridConfig {
+	fn default() -> Self {
+		Self {
+			rows: 2,
+			columns: 2,
+			grid_margins: 3,
+			zone_margins: 10,
+			border_margins: 10,
+			row_weights: Vec::new(),
+			column_weights: Vec::new(),
+			merged: Vec::new(),
+		}
+	}
+}
+
+impl Default for TileConfig {
+	fn default() -> Self {
+		Self {
+			width: 48,
+			height: 48,
+		}
+	}
+}
+
+impl Default for ColorConfig {
+	fn default() -> Self {
+		Self {
+			background: Color::new(44, 44, 44),
+			tile: Color::new(178, 178, 178),
+			hovered: Color::new(0, 100, 148),
+			selected: Color::new(0, 77, 128),
+			border: Color::new(0, 0, 0),
+			preview: Color::new(0, 77, 128),
+		}
+	}
+}
+
+impl Default for PreviewConfig {
+	fn default() -> Self {
+		Self { opacity: 107 }
+	}
+}
+
+impl Default for HotkeyConfig {
+	fn default() -> Self {
+		Self {
+			main: Modifier::Ctrl + Modifier::Alt + Key::S,
+			quick_resize: Modifier::Ctrl + Modifier::Alt + Key::Q,
+			next_profile: None,
+			tile: None,
+			next_layout: None,
+			grow_master: None,
+			shrink_master: None,
+			add_master: None,
+			remove_master: None,
+			apply_rules: None,
+			explain_rules: None,
+			undo: None,
+			redo: None,
+			restore_window: None,
+			restore_all: None,
+			next_monitor: None,
+			previous_monitor: None,
+			monitor_left: None,
+			monitor_right: None,
+			monitor_up: None,
+			monitor_down: None,
+			focus_left: None,
+			focus_right: None,
+			focus_up: None,
+			focus_down: None,
+			swap_left: None,
+			swap_right: None,
+			swap_up: None,
+			swap_down: None,
+			move_left: None,
+			move_right: None,
+			move_up: None,
+			move_down: None,
+			grow_left: None,
+			grow_right: None,
+			grow_up: None,
+			grow_down: None,
+			shrink_left: None,
+			shrink_right: None,
+			shrink_up: None,
+			shrink_down: None,
+			center: None,
+			hint_focus: None,
+			hint_swap: None,
+			hint_zone: None,
+			send_to_monitor: Vec::new(),
+		}
+	}
+}
+
+impl Default for WorkspaceConfig {
+	fn default() -> Self {
+		Self {
+			count: 4,
+			switch: Vec::new(),
+			move_window: Vec::new(),
+		}
+	}
+}
+
+impl Default for TilingConfig {
+	fn default() -> Self {
+		Self {
+			layouts: vec![Layout::MasterStack, Layout::Bsp, Layout::Monocle],
+			master_ratio: 0.55,
+			master_ratio_step: 0.05,
+			master_count: 1,
+			gaps: 10,
+		}
+	}
+}
+
+impl Default for NudgeConfig {
+	fn default() -> Self {
+		Self {
+			step: NudgeStep::Pixels(20),
+			snap_distance: 16,
+		}
+	}
+}
+
+impl Default for PlacementConfig {
+	fn default() -> Self {
+		Self {
+			too_small: TooSmall::Center,
+		}
+	}
+}
+
+impl Default for AnimationConfig {
+	fn default() -> Self {
+		Self {
+			enabled: false,
+			duration: 150,
+			easing: Easing::EaseOutCubic,
+			frame_rate: 60,
+		}
+	}
+}
+
+impl Default for HintConfig {
+	fn default() -> Self {
+		Self {
+			alphabet: "asdfghjkl".to_owned(),
+		}
+	}
+}
+
+impl Default for CycleConfig {
+	fn default() -> Self {
+		Self {
+			ratios: vec![1.0 / 2.0, 1.0 / 3.0, 2.0 / 3.0],
+		}
+	}
+}
+
+impl Default for DragConfig {
+	fn default() -> Self {
+		Self {
+			enabled: true,
+			modifier: Modifier::Shift,
+			span_modifier: Modifier::Ctrl,
+		}
+	}
+}
+
+/// Errors that can occur while loading the config
+#[derive(Debug)]
+pub enum ConfigError {
+	/// The config file couldn't be read
+	Io(PathBuf, io::Error),
+	/// The config file isn't valid toml or doesn't match the expected schema
+	Parse(toml::de::Error),
+	/// A value is outside of its allowed range
+	Invalid {
+		/// Path to the offending value, e.g. `grid.rows`
+		key: String,
+		/// Why the value was rejected
+		reason: String,
+	},
+}
+
+impl ConfigError {
+	fn invalid(key: impl Into<String>, reason: impl Into<String>) -> Self {
+		ConfigError::Invalid {
+			key: key.into(),
+			reason: reason.into(),
+		}
+	}
+}
+
+impl Error for ConfigError {}
+
+impl fmt::Display for ConfigError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ConfigError::Io(path, err) => write!(f, "could not read {}: {}", path.display(), err),
+			ConfigError::Parse(err) => write!(f, "invalid config: {}", err),
+			ConfigError::Invalid { key, reason } => {
+				write!(f, "invalid value for key `{}`: {}", key, reason)
+			}
+		}
+	}
+}
+
+fn check_range<T>(key: &str, value: T, min: T, max: T) -> Result<(), ConfigError>
+where
+	T: PartialOrd + fmt::Display,
+{
+	if value < min || value > max {
+		Err(ConfigError::invalid(
+			key,
+			format!("{} is not between {} and {}", value, min, max),
+		))
+	} else {
+		Ok(())
+	}
+}
+
+/// Takes the value of a profile, or the default if the profile keeps the tiles it refers to
+fn inherit<T: Clone>(value: &Option<Vec<T>>, default: &[T], same_tiles: bool) -> Vec<T> {
+	match value {
+		Some(value) => value.clone(),
+		None if same_tiles => default.to_vec(),
+		None => Vec::new(),
+	}
+}
+
+/// Checks the target of a snap hotkey or rule in `section`
+fn validate_target(section: &str, target: &SnapTarget) -> Result<(), ConfigError> {
+	match target {
+		SnapTarget::Region(region) if !region.is_valid() => Err(ConfigError::invalid(
+			format!("{}.region", section),
+			"is out of range",
+		)),
+		SnapTarget::Cell(cell) if cell.rows == 0 || cell.columns == 0 => Err(ConfigError::invalid(
+			format!("{}.cell", section),
+			"has to cover at least one tile",
+		)),
+		SnapTarget::Zone(zone) => validate_zone(&format!("{}.zone", section), zone),
+		_ => Ok(()),
+	}
+}
+
+fn validate_zone(key: &str, zone: &Zone) -> Result<(), ConfigError> {
+	let lengths = [
+		("x", zone.x, true),
+		("y", zone.y, true),
+		("width", zone.width, false),
+		("height", zone.height, false),
+	];
+
+	for (field, length, allow_zero) in lengths.iter() {
+		if !length.is_valid(*allow_zero) {
+			return Err(ConfigError::invalid(
+				format!("{}.{}", key, field),
+				format!("{} is out of range", length),
+			));
+		}
+	}
+
+	Ok(())
+}
+
+fn default_span() -> usize {
+	1
+}
+
+fn deserialize_color<'de, D>(deserializer: D) -> Result<Color, D::Error>
+where
+	D: Deserializer<'de>,
+{
+	String::deserialize(deserializer)?
+		.parse()
+		.map_err(de::Error::custom)
+}
+
+fn deserialize_hotkey<'de, D>(deserializer: D) -> Result<KeyCombination, D::Error>
+where
+	D: Deserializer<'de>,
+{
+	String::deserialize(deserializer)?
+		.parse()
+		.map_err(de::Error::custom)
+}
+
+fn deserialize_hotkeys<'de, D>(deserializer: D) -> Result<Vec<KeyCombination>, D::Error>
+where
+	D: Deserializer<'de>,
+{
+	Vec::<String>::deserialize(deserializer)?
+		.iter()
+		.map(|hotkey| hotkey.parse().map_err(de::Error::custom))
+		.collect()
+}
+
+fn deserialize_modifier<'de, D>(deserializer: D) -> Result<Modifier, D::Error>
+where
+	D: Deserializer<'de>,
+{
+	String::deserialize(deserializer)?
+		.parse()
+		.map_err(de::Error::custom)
+}
+
+fn deserialize_ratios<'de, D>(deserializer: D) -> Result<Vec<f32>, D::Error>
+where
+	D: Deserializer<'de>,
+{
+	#[derive(Deserialize)]
+	#[serde(untagged)]
+	enum Raw {
+		Number(f32),
+		Text(String),
+	}
+
+	Vec::<Raw>::deserialize(deserializer)?
+		.into_iter()
+		.map(|ratio| match ratio {
+			Raw::Number(ratio) => Ok(ratio),
+			Raw::Text(text) => parse_ratio(&text).map_err(de::Error::custom),
+		})
+		.collect()
+}
+
+/// Parses a fraction like `1/3`, or a plain number
+fn parse_ratio(s: &str) -> Result<f32, String> {
+	let error = || format!("`{}` is not a ratio", s);
+
+	match s.split_once('/') {
+		Some((numerator, denominator)) => {
+			let numerator: f32 = numerator.trim().parse().map_err(|_| error())?;
+			let denominator: f32 = denominator.trim().parse().map_err(|_| error())?;
+
+			Ok(numerator / denominator)
+		}
+		None => s.trim().parse().map_err(|_| error()),
+	}
+}
+
+fn deserialize_step<'de, D>(deserializer: D) -> Result<NudgeStep, D::Error>
+where
+	D: Deserializer<'de>,
+{
+	#[derive(Deserialize)]
+	#[serde(untagged)]
+	enum Raw {
+		Pixels(u32),
+		Fraction(f32),
+		Text(String),
+	}
+
+	match Raw::deserialize(deserializer)? {
+		Raw::Pixels(pixels) => Ok(NudgeStep::Pixels(pixels)),
+		Raw::Fraction(fraction) => Ok(NudgeStep::Fraction(fraction)),
+		Raw::Text(text) => parse_ratio(&text)
+			.map(NudgeStep::Fraction)
+			.map_err(de::Error::custom),
+	}
+}
+
+fn deserialize_optional_hotkey<'de, D>(deserializer: D) -> Result<Option<KeyCombination>, D::Error>
+where
+	D: Deserializer<'de>,
+{
+	deserialize_hotkey(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// The key and reason `config` is rejected with
+	fn invalid(config: &str) -> (String, String) {
+		match config.parse::<WtmConfig>() {
+			Err(ConfigError::Invalid { key, reason }) => (key, reason),
+			other => panic!("expected an invalid value, got {:?}", other),
+		}
+	}
+
+	fn rejects(config: &str, key: &str, reason: &str) {
+		assert_eq!(invalid(config), (key.to_owned(), reason.to_owned()));
+	}
+
+	#[test]
+	fn empty_config_is_default() {
+		assert_eq!("".parse::<WtmConfig>().unwrap(), WtmConfig::default());
+		assert!(WtmConfig::default().validate().is_ok());
+	}
+
+	#[test]
+	fn values_outside_of_their_range() {
+		rejects("[grid]\nrows = 0", "grid.rows", "0 is not between 1 and 16");
+		rejects(
+			"[grid]\ncolumns = 17",
+			"grid.columns",
+			"17 is not between 1 and 16",
+		);
+		rejects(
+			"[tile]\nwidth = 4",
+			"tile.width",
+			"4 is not between 8 and 512",
+		);
+		rejects(
+			"[preview]\nopacity = 0",
+			"preview.opacity",
+			"0 is not between 1 and 255",
+		);
+		rejects(
+			"[animation]\nframe_rate = 500",
+			"animation.frame_rate",
+			"500 is not between 10 and 240",
+		);
+		rejects(
+			"[tiling]\nmaster_ratio = 0.95",
+			"tiling.master_ratio",
+			"0.95 is not between 0.1 and 0.9",
+		);
+	}
+
+	#[test]
+	fn paths_point_into_arrays() {
+		rejects(
+			"[cycle]\nratios = [\"1/2\", 1.5]",
+			"cycle.ratios[1]",
+			"1.5 is not between 0 and 1",
+		);
+		rejects(
+			"[[snap]]\nhotkey = \"Alt+1\"",
+			"snap[0]",
+			"needs exactly one of `region`, `cell` and `zone`",
+		);
+		rejects(
+			"[[rules]]\ntitle = \"*\"\n\n[[rules]]\ntitle = \"x\"\nignore = true\nfloat = true",
+			"rules[1].ignore",
+			"can not be combined with other actions",
+		);
+		rejects(
+			"[[rules]]\nclass = \"x\"\nworkspace = 5",
+			"rules[0].workspace",
+			"5 is not between 1 and 4",
+		);
+		rejects(
+			"[[zone_sets]]\nname = \"Empty\"\nzones = []",
+			"zone_sets[0].zones",
+			"0 is not between 1 and 10",
+		);
+	}
+
+	#[test]
+	fn names_have_to_be_unique() {
+		rejects(
+			"[[profiles]]\nname = \"Default\"",
+			"profiles[0].name",
+			"a profile named `Default` already exists",
+		);
+		rejects(
+			"[[arrangements]]\nname = \"Work\"\n\n[[arrangements]]\nname = \"Work\"",
+			"arrangements[1].name",
+			"an arrangement named `Work` already exists",
+		);
+		rejects(
+			"[[arrangements]]\nname = \"a/b\"",
+			"arrangements[0].name",
+			"can not contain characters that aren't allowed in file names",
+		);
+	}
+
+	#[test]
+	fn hotkeys_are_bound_once() {
+		rejects(
+			"[hotkeys]\nmain = \"Ctrl+Alt+Q\"",
+			"hotkeys.quick_resize",
+			"is already bound to `hotkeys.main`",
+		);
+		rejects(
+			"[hotkeys]\nundo = \"Alt+U\"\n\n[[snap]]\nhotkey = \"Alt+U\"\nregion = \"left-half\"",
+			"snap[0].hotkey",
+			"is already bound to `hotkeys.undo`",
+		);
+		rejects(
+			"[workspaces]\ncount = 2\nswitch = [\"Alt+1\", \"Alt+2\", \"Alt+3\"]",
+			"workspaces.switch",
+			"has more hotkeys than the 2 workspaces",
+		);
+	}
+
+	#[test]
+	fn settings_that_depend_on_each_other() {
+		rejects(
+			"[drag]\nmodifier = \"Ctrl\"\nspan_modifier = \"Control\"",
+			"drag.span_modifier",
+			"has to differ from `drag.modifier`",
+		);
+		rejects(
+			"[hotkeys]\nhint_zone = \"Alt+Z\"",
+			"hotkeys.hint_zone",
+			"needs at least one zone set",
+		);
+		rejects(
+			"[hints]\nalphabet = \"asa\"",
+			"hints.alphabet",
+			"contains `a` more than once",
+		);
+		rejects(
+			"[hints]\nalphabet = \"a1\"",
+			"hints.alphabet",
+			"has to consist of at least two letters from a to z",
+		);
+	}
+
+	#[test]
+	fn errors_name_the_key() {
+		let err = "[grid]\nrows = 0".parse::<WtmConfig>().unwrap_err();
+
+		assert_eq!(
+			err.to_string(),
+			"invalid value for key `grid.rows`: 0 is not between 1 and 16"
+		);
+	}
+
+	#[test]
+	fn unknown_keys_and_bad_values_are_parse_errors() {
+		for config in &[
+			"[grid]\nrow = 3",
+			"[hotkeys]\nmain = \"Ctrl+Hyper+S\"",
+			"[colors]\npreview = \"blue-ish\"",
+		] {
+			match config.parse::<WtmConfig>() {
+				Err(ConfigError::Parse(_)) => {}
+				other => panic!("{} should not parse, got {:?}", config, other),
+			}
+		}
+	}
+}
@@ -0,0 +1,114 @@
+//! Desktop module
+
+use crate::{
+	arrangements::WindowState,
+	displays::Display,
+	monitors::numbered_monitors,
+	rules::WindowProperties,
+	tiling::{is_managed, is_tileable},
+};
+use std::fmt;
+use winsapi::{cursor_position, Insets, Monitor, Rect, SizeLimits, Window};
+
+/// What the tiling manager reads from the desktop while it handles an event
+///
+/// Reading through this trait instead of asking Windows directly lets the events be handled
+/// against a desktop that is only simulated.
+pub trait Desktop: fmt::Debug + Send {
+	/// The monitor the cursor is on
+	fn active_monitor(&self) -> Display;
+	/// Every monitor, numbered from left to right
+	fn monitors(&self) -> Vec<Display>;
+	/// The monitor most of `window` is on
+	fn monitor_of(&self, window: Window) -> Display;
+	/// The window in the foreground
+	fn foreground_window(&self) -> Window;
+	/// Where the cursor is, in screen coordinates
+	fn cursor_position(&self) -> (i32, i32);
+	/// Every top-level window, from the top of the z-order to the bottom
+	fn windows(&self) -> Vec<Window>;
+	/// Is `window` still open
+	fn exists(&self, window: Window) -> bool;
+	/// Is `window` a shown application window
+	fn is_managed(&self, window: Window) -> bool;
+	/// Is `window` a normal application window that can be tiled
+	fn is_tileable(&self, window: Window) -> bool;
+	/// What the rules are matched against for `window`
+	fn window_properties(&self, window: Window) -> WindowProperties;
+	/// The rectangle of `window`, including its invisible border
+	fn window_rect(&self, window: Window) -> Rect;
+	/// The invisible border around `window`, as drawn by the desktop window manager
+	fn frame_insets(&self, window: Window) -> Insets;
+	/// Is `window` maximized or minimized
+	fn window_state(&self, window: Window) -> WindowState;
+	/// How small and large `window` can be made, including its invisible border
+	fn size_limits(&self, window: Window) -> SizeLimits;
+}
+
+/// The desktop of the user
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SystemDesktop;
+
+impl Desktop for SystemDesktop {
+	fn active_monitor(&self) -> Display {
+		Monitor::get_active().into()
+	}
+
+	fn monitors(&self) -> Vec<Display> {
+		numbered_monitors().into_iter().map(Display::from).collect()
+	}
+
+	fn monitor_of(&self, window: Window) -> Display {
+		Monitor::from_window(window).into()
+	}
+
+	fn foreground_window(&self) -> Window {
+		Window::get_foreground_window()
+	}
+
+	fn cursor_position(&self) -> (i32, i32) {
+		cursor_position()
+	}
+
+	fn windows(&self) -> Vec<Window> {
+		Window::all()
+	}
+
+	fn exists(&self, window: Window) -> bool {
+		window.exists()
+	}
+
+	fn is_managed(&self, window: Window) -> bool {
+		is_managed(window)
+	}
+
+	fn is_tileable(&self, window: Window) -> bool {
+		is_tileable(window)
+	}
+
+	fn window_properties(&self, window: Window) -> WindowProperties {
+		WindowProperties::of(window)
+	}
+
+	fn window_rect(&self, window: Window) -> Rect {
+		window.info().window_rect
+	}
+
+	fn frame_insets(&self, window: Window) -> Insets {
+		window.frame_insets()
+	}
+
+	fn window_state(&self, window: Window) -> WindowState {
+		if window.is_minimized() {
+			WindowState::Minimized
+		} else if window.is_maximized() {
+			WindowState::Maximized
+		} else {
+			WindowState::Normal
+		}
+	}
+
+	fn size_limits(&self, window: Window) -> SizeLimits {
+		window.size_limits()
+	}
+}
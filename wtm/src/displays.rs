@@ -0,0 +1,316 @@
+//! Displays module
+
+use crate::{monitors::relocate, record::rect};
+use serde::{Deserialize, Serialize};
+use std::mem;
+use winsapi::{Monitor, Rect, Window};
+
+/// A monitor of the monitor setup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Display {
+	/// Device name of the monitor, like `\\.\DISPLAY1`
+	pub name: String,
+	/// The whole monitor, its size is the resolution
+	#[serde(with = "rect")]
+	pub rect: Rect,
+	/// The part of the monitor that isn't covered by the taskbar
+	#[serde(with = "rect")]
+	pub work_area: Rect,
+}
+
+/// Notices when monitors are added, removed or change their resolution
+///
+/// A new setup only counts once it was seen twice in a row, so the short-lived setups Windows
+/// goes through while a laptop is docked are skipped.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DisplayTracker {
+	/// The current setup
+	setup: Vec<Display>,
+	/// A different setup that was seen once
+	pending: Option<Vec<Display>>,
+	/// Where the windows were while the current setup was in use
+	windows: Vec<(Window, Rect)>,
+}
+
+/// The monitor setup changed
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetupChange {
+	/// The setup before the change
+	pub previous: Vec<Display>,
+	/// The setup after the change
+	pub current: Vec<Display>,
+	/// Where the windows were before the change
+	pub windows: Vec<(Window, Rect)>,
+}
+
+impl DisplayTracker {
+	/// Starts tracking with the current `setup` and where the windows are
+	pub fn new(setup: Vec<Display>, windows: Vec<(Window, Rect)>) -> Self {
+		Self {
+			setup,
+			pending: None,
+			windows,
+		}
+	}
+
+	/// Compares `setup` with the current one
+	///
+	/// `windows` is only called while the setup stays the same, so the windows are remembered
+	/// where they were before Windows moves them off a monitor that was removed.
+	pub fn check(
+		&mut self,
+		setup: Vec<Display>,
+		windows: impl FnOnce() -> Vec<(Window, Rect)>,
+	) -> Option<SetupChange> {
+		if setup == self.setup {
+			self.pending = None;
+			self.windows = windows();
+
+			None
+		} else if self.pending.as_ref() == Some(&setup) {
+			self.pending = None;
+
+			Some(SetupChange {
+				previous: mem::replace(&mut self.setup, setup.clone()),
+				current: setup,
+				windows: mem::take(&mut self.windows),
+			})
+		} else {
+			self.pending = Some(setup);
+
+			None
+		}
+	}
+}
+
+/// Identifies a monitor setup by the resolution and position of its monitors
+///
+/// Looks like `1920x1080@0,0 2560x1440@1920,0`, the monitors are sorted from left to right.
+pub fn fingerprint(setup: &[Display]) -> String {
+	let mut rects: Vec<_> = setup.iter().map(|display| display.rect).collect();
+	rects.sort_by_key(|rect| (rect.x, rect.y));
+
+	rects
+		.iter()
+		.map(|rect| format!("{}x{}@{},{}", rect.w, rect.h, rect.x, rect.y))
+		.collect::<Vec<_>>()
+		.join(" ")
+}
+
+/// Where the windows of `change` have to go so they keep their place on their monitor
+///
+/// Windows on a monitor whose work area changed are scaled to the new one. Windows on a monitor
+/// that was removed are moved to the same place on the closest remaining monitor. Windows that
+/// weren't on any monitor, like minimized ones, stay where they are.
+pub fn rescue(change: &SetupChange) -> Vec<(Window, Rect)> {
+	let center = |rect: Rect| (rect.x + rect.w / 2, rect.y + rect.h / 2);
+	let distance = |from: Rect, to: Rect| {
+		let ((from_x, from_y), (to_x, to_y)) = (center(from), center(to));
+		let (x, y) = ((from_x - to_x) as i64, (from_y - to_y) as i64);
+
+		x * x + y * y
+	};
+
+	change
+		.windows
+		.iter()
+		.filter_map(|(window, rect)| {
+			let from = change
+				.previous
+				.iter()
+				.find(|display| display.rect.contains_point(center(*rect)))?;
+
+			let to = match change
+				.current
+				.iter()
+				.find(|display| display.name == from.name)
+			{
+				Some(to) => to,
+				None => change
+					.current
+					.iter()
+					.min_by_key(|display| distance(from.rect, display.rect))?,
+			};
+
+			if to.work_area == from.work_area {
+				None
+			} else {
+				Some((*window, relocate(*rect, from.work_area, to.work_area)))
+			}
+		})
+		.collect()
+}
+
+impl From<Monitor> for Display {
+	fn from(monitor: Monitor) -> Self {
+		Self {
+			name: monitor.name(),
+			rect: monitor.rect(),
+			work_area: monitor.area(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn window(id: usize) -> Window {
+		Window(id as _)
+	}
+
+	fn rect(x: i32, y: i32, w: i32, h: i32) -> Rect {
+		Rect { x, y, w, h }
+	}
+
+	/// A monitor with a 40 pixel high taskbar at the bottom
+	fn display(name: &str, rect: Rect) -> Display {
+		Display {
+			name: format!(r"\\.\{}", name),
+			rect,
+			work_area: Rect {
+				h: rect.h - 40,
+				..rect
+			},
+		}
+	}
+
+	fn laptop() -> Display {
+		display("DISPLAY1", rect(0, 0, 1920, 1080))
+	}
+
+	fn external() -> Display {
+		display("DISPLAY2", rect(1920, 0, 2560, 1440))
+	}
+
+	/// The right half of the external monitor
+	fn on_external() -> (Window, Rect) {
+		(window(2), rect(3200, 0, 1280, 700))
+	}
+
+	/// The left half of the laptop monitor
+	fn on_laptop() -> (Window, Rect) {
+		(window(1), rect(0, 0, 960, 520))
+	}
+
+	#[test]
+	fn fingerprints_list_the_monitors_from_left_to_right() {
+		assert_eq!(
+			fingerprint(&[external(), laptop()]),
+			"1920x1080@0,0 2560x1440@1920,0"
+		);
+		assert_eq!(fingerprint(&[]), "");
+	}
+
+	#[test]
+	fn fingerprints_ignore_the_names_and_work_areas() {
+		let mut renamed = laptop();
+		renamed.name = String::from(r"\\.\DISPLAY7");
+		renamed.work_area = renamed.rect;
+
+		assert_eq!(fingerprint(&[renamed]), fingerprint(&[laptop()]));
+	}
+
+	#[test]
+	fn the_same_setup_is_no_change() {
+		let mut tracker = DisplayTracker::new(vec![laptop()], Vec::new());
+
+		assert_eq!(tracker.check(vec![laptop()], || vec![on_laptop()]), None);
+		assert_eq!(tracker.windows, vec![on_laptop()]);
+	}
+
+	#[test]
+	fn a_new_setup_counts_once_it_was_seen_twice() {
+		let mut tracker = DisplayTracker::new(vec![laptop(), external()], vec![on_external()]);
+
+		assert_eq!(tracker.check(vec![laptop()], Vec::new), None);
+		assert_eq!(
+			tracker.check(vec![laptop()], Vec::new),
+			Some(SetupChange {
+				previous: vec![laptop(), external()],
+				current: vec![laptop()],
+				windows: vec![on_external()],
+			})
+		);
+		assert_eq!(tracker.check(vec![laptop()], Vec::new), None);
+	}
+
+	#[test]
+	fn setups_that_only_show_up_once_are_skipped() {
+		let mut tracker = DisplayTracker::new(vec![laptop(), external()], vec![on_external()]);
+
+		assert_eq!(tracker.check(vec![laptop()], Vec::new), None);
+		assert_eq!(tracker.check(vec![external()], Vec::new), None);
+		assert_eq!(
+			tracker.check(vec![laptop(), external()], || vec![on_laptop()]),
+			None
+		);
+		assert_eq!(tracker.check(vec![laptop()], Vec::new), None);
+	}
+
+	#[test]
+	fn windows_are_remembered_from_before_the_change() {
+		let mut tracker = DisplayTracker::new(vec![laptop(), external()], Vec::new());
+		tracker.check(vec![laptop(), external()], || vec![on_external()]);
+
+		// Windows moves the windows off the removed monitor before the setup is confirmed
+		tracker.check(vec![laptop()], || panic!("the setup changed"));
+		let change = tracker.check(vec![laptop()], || panic!("the setup changed"));
+
+		assert_eq!(change.unwrap().windows, vec![on_external()]);
+	}
+
+	#[test]
+	fn windows_of_a_removed_monitor_move_to_the_same_place_on_the_closest_one() {
+		let change = SetupChange {
+			previous: vec![laptop(), external()],
+			current: vec![laptop()],
+			windows: vec![on_laptop(), on_external()],
+		};
+
+		assert_eq!(rescue(&change), vec![(window(2), rect(960, 0, 960, 520))]);
+	}
+
+	#[test]
+	fn windows_stay_in_place_when_a_monitor_is_added_again() {
+		let change = SetupChange {
+			previous: vec![laptop()],
+			current: vec![laptop(), external()],
+			windows: vec![on_laptop(), (window(2), rect(960, 0, 960, 520))],
+		};
+
+		assert_eq!(rescue(&change), Vec::new());
+	}
+
+	#[test]
+	fn windows_are_scaled_when_the_resolution_changes() {
+		let change = SetupChange {
+			previous: vec![laptop(), external()],
+			current: vec![
+				display("DISPLAY1", rect(0, 0, 1280, 720)),
+				display("DISPLAY2", rect(1280, 0, 2560, 1440)),
+			],
+			windows: vec![on_laptop(), on_external()],
+		};
+
+		assert_eq!(
+			rescue(&change),
+			vec![
+				(window(1), rect(0, 0, 640, 340)),
+				(window(2), rect(2560, 0, 1280, 700)),
+			]
+		);
+	}
+
+	#[test]
+	fn windows_outside_of_every_monitor_stay_where_they_are() {
+		let minimized = (window(3), rect(-32000, -32000, 160, 28));
+		let change = SetupChange {
+			previous: vec![laptop(), external()],
+			current: vec![laptop()],
+			windows: vec![minimized],
+		};
+
+		assert_eq!(rescue(&change), Vec::new());
+	}
+}
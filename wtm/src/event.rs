@@ -1,64 +1,201 @@
 //! Event module
 
-use crate::INSTANCE;
-use crossbeam_channel::{select, Receiver};
-use std::{mem, ptr, thread, time::Duration};
+use crate::{
+	grid::Direction, hints::HintAction, monitors::MonitorTarget, nudge::Adjustment, record::handle,
+};
+use crossbeam_channel::Sender;
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::{cell::Cell, ptr, thread};
 use winapi::{
+	ctypes::c_int,
 	shared::{
-		minwindef::DWORD,
+		minwindef::{DWORD, LPARAM, LRESULT, WPARAM},
 		windef::{HWINEVENTHOOK, HWND},
 	},
 	um::{
 		winnt::LONG,
 		winuser::{
-			DispatchMessageW, PeekMessageW, SetWinEventHook, TranslateMessage,
-			EVENT_SYSTEM_FOREGROUND, WINEVENT_OUTOFCONTEXT,
+			CallNextHookEx, SetWinEventHook, SetWindowsHookExW, UnhookWinEvent,
+			UnhookWindowsHookEx, CHILDID_SELF, EVENT_OBJECT_DESTROY, EVENT_OBJECT_NAMECHANGE,
+			EVENT_OBJECT_SHOW, EVENT_SYSTEM_FOREGROUND, EVENT_SYSTEM_MOVESIZEEND,
+			EVENT_SYSTEM_MOVESIZESTART, HC_ACTION, KBDLLHOOKSTRUCT, MSLLHOOKSTRUCT, OBJID_WINDOW,
+			WH_KEYBOARD_LL, WH_MOUSE_LL, WINEVENT_OUTOFCONTEXT, WM_KEYDOWN, WM_MOUSEMOVE,
+			WM_SYSKEYDOWN,
 		},
 	},
 };
-use winsapi::{Monitor, Rect, Window};
+use winsapi::{cursor_position, run_message_loop_until, Modifier, Monitor, Signal, Window};
+
+/// Where hooks, window procedures and other threads send their events to
+static SENDER: OnceCell<Sender<Event>> = OnceCell::new();
+
+thread_local! {
+	/// The monitor the cursor was on when the mouse hook of the monitor tracking thread last ran
+	static ACTIVE_MONITOR: Cell<Option<Monitor>> = const { Cell::new(None) };
+
+	/// The drag modifiers and what was last sent by the hooks of the drag tracking thread
+	static DRAG: Cell<Option<DragState>> = const { Cell::new(None) };
+}
+
+/// What the drag tracking thread knows about the drag
+#[derive(Copy, Clone)]
+struct DragState {
+	modifier: Modifier,
+	span_modifier: Modifier,
+	previous: Option<((i32, i32), bool, bool)>,
+}
 
-/// Messages that are sent over [CHANNEL](../struct.CHANNEL.html)
-#[derive(Debug, Copy, Clone)]
+/// Messages that are sent to the tiling manager
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Event {
 	///
-	PreviewWindow(Window),
+	PreviewWindow(#[serde(with = "handle")] Window),
 	///
-	GridWindow(Window),
-	/// Highlight the hovered area over the grid window
-	HighlightZone(Rect),
+	GridWindow(#[serde(with = "handle")] Window),
 	/// A registered hotkey was pressed
 	HotkeyPressed(HotkeyType),
 	/// Tracks the mouse over the grid window
-	TrackMouse(Window),
+	TrackMouse(#[serde(with = "handle")] Window),
 	/// The active window changed
-	ActiveWindowChange(Window),
-	/// A different profile was activated
-	ProfileChange(&'static str),
+	ActiveWindowChange(#[serde(with = "handle")] Window),
 	/// The active monitor changed
 	MonitorChange,
 	/// Mouse left the Grid window
 	MouseLeft,
-	/// Draw the grid window
-	InitializeWindows,
-	/// Close the windows drawn by wtm
-	CloseWindows,
+	/// A key was pressed while the grid window has the focus, holds the virtual key code
+	GridKeyDown(i32),
+	/// A key was released while the grid window has the focus, holds the virtual key code
+	GridKeyUp(i32),
+	/// The mouse moved over the grid window, holds its position inside of the window
+	GridMouseMove((i32, i32)),
+	/// The left mouse button was pressed over the grid window, holds the position of the mouse
+	GridMouseDown((i32, i32)),
+	/// The left mouse button was released over the grid window
+	GridMouseUp,
+	/// The window that shows the labels of hint mode was created
+	HintWindow(#[serde(with = "handle")] Window),
+	/// A key was pressed while the hint window has the focus, holds the virtual key code
+	HintKeyDown(i32),
+	/// The hint window lost the focus
+	HintWindowDeactivated(#[serde(with = "handle")] Window),
+	/// One of the windows drawn by wtm has to be painted
+	Paint(#[serde(with = "handle")] Window),
+	/// The config file was modified
+	ConfigChanged,
+	/// The user started moving or resizing a window
+	MoveSizeStart(#[serde(with = "handle")] Window),
+	/// The user released the moved or resized window
+	MoveSizeEnd(#[serde(with = "handle")] Window),
+	/// The cursor moved, or a modifier changed, while a window is dragged
+	Drag {
+		/// Position of the cursor
+		point: (i32, i32),
+		/// is the drag modifier held
+		snap: bool,
+		/// is the span modifier held
+		extend: bool,
+	},
+	/// A window was brought to the foreground
+	WindowFocused(#[serde(with = "handle")] Window),
+	/// A window was shown, for example because it was just created
+	WindowShown(#[serde(with = "handle")] Window),
+	/// A window changed its title
+	WindowRenamed(#[serde(with = "handle")] Window),
+	/// A window was destroyed
+	WindowDestroyed(#[serde(with = "handle")] Window),
+	/// Time to check if monitors were added, removed or changed their resolution
+	CheckDisplays,
+	/// Time to draw the next frame of the running animations
+	AnimationFrame,
+	/// A window was moved to another monitor, its border may have changed with the monitor's scale
+	CheckBorder(#[serde(with = "handle")] Window),
 }
 
 /// The Commands that a keybind can execute
-#[derive(PartialEq, Clone, Copy, Debug)]
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum HotkeyType {
 	/// Open the grid window and resize as many windows until executed again
 	Main,
 	/// Quick Resize the current window
 	QuickResize,
+	/// Switch the active monitor to the next profile
+	NextProfile,
+	/// Move the foreground window to the target of a snap command, holds the index of the command
+	Snap(usize),
+	/// Tile the windows of the active monitor
+	Tile,
+	/// Tile the windows of the active monitor with the next layout
+	NextLayout,
+	/// Widen the master area
+	GrowMaster,
+	/// Narrow the master area
+	ShrinkMaster,
+	/// Move one more window into the master area
+	AddMaster,
+	/// Move one window out of the master area
+	RemoveMaster,
+	/// Switch the active monitor to a workspace, holds the index of the workspace
+	Workspace(usize),
+	/// Move the foreground window to a workspace, holds the index of the workspace
+	MoveToWorkspace(usize),
+	/// Apply the rules to every window
+	ApplyRules,
+	/// Log which rule matches the foreground window
+	ExplainRules,
+	/// Move the window that was moved last back to where it was before
+	Undo,
+	/// Move the window whose move was undone last again
+	Redo,
+	/// Move the foreground window back to where it was before wtm moved it
+	RestoreWindow,
+	/// Move every window back to where it was before wtm moved it
+	RestoreAll,
+	/// Send the foreground window to another monitor
+	SendToMonitor(MonitorTarget),
+	/// Focus the window next to the foreground window in a direction
+	Focus(Direction),
+	/// Swap the foreground window with the window next to it in a direction
+	Swap(Direction),
+	/// Move or resize the foreground window by one step
+	Nudge(Adjustment),
+	/// Move the foreground window to the center of its monitor
+	Center,
+	/// Save where the windows are, holds the index of the arrangement
+	SaveArrangement(usize),
+	/// Move the windows to where they were saved, holds the index of the arrangement
+	RestoreArrangement(usize),
+	/// Log what restoring an arrangement would do, holds the index of the arrangement
+	DryRunArrangement(usize),
+	/// Label the visible windows and act on the one whose label is typed
+	Hint(HintAction),
 }
 
-// TODO figure out what this does
+/// Messages that wtm sends to its subscribers
+#[derive(Debug, Clone, PartialEq)]
+pub enum Notification {
+	/// A monitor switched to a different profile
+	ProfileChanged {
+		/// Name of the monitor
+		monitor: String,
+		/// Name of the profile
+		profile: String,
+	},
+	/// A monitor switched to a different workspace
+	WorkspaceChanged {
+		/// Name of the monitor
+		monitor: String,
+		/// Number of the workspace, starting at 1
+		workspace: usize,
+	},
+}
+
+/// Sends the windows that are brought to the foreground until `stop` is raised
 ///
-pub fn spawn_foreground_hook(close_msg: Receiver<()>) {
+/// Runs while the grid window is open, so the grid places the window the user switches to.
+pub fn spawn_foreground_hook(stop: Signal) {
 	thread::spawn(move || unsafe {
-		SetWinEventHook(
+		let hook = SetWinEventHook(
 			EVENT_SYSTEM_FOREGROUND,
 			EVENT_SYSTEM_FOREGROUND,
 			ptr::null_mut(),
@@ -68,47 +205,207 @@ pub fn spawn_foreground_hook(close_msg: Receiver<()>) {
 			WINEVENT_OUTOFCONTEXT,
 		);
 
-		let mut msg = mem::zeroed();
-		loop {
-			if PeekMessageW(&mut msg, ptr::null_mut(), 0, 0, 1) > 0 {
-				TranslateMessage(&msg);
-				DispatchMessageW(&msg);
-			};
+		run_message_loop_until(&stop);
 
-			select! {
-				recv(close_msg) -> _ => break,
-				default(Duration::from_millis(10)) => {}
-			}
+		UnhookWinEvent(hook);
+	});
+}
+
+/// Sends an event whenever the user starts or stops moving a window, until `stop` is raised
+pub fn spawn_move_size_hook(stop: Signal) {
+	thread::spawn(move || unsafe {
+		let hook = SetWinEventHook(
+			EVENT_SYSTEM_MOVESIZESTART,
+			EVENT_SYSTEM_MOVESIZEEND,
+			ptr::null_mut(),
+			Some(move_size_callback),
+			0,
+			0,
+			WINEVENT_OUTOFCONTEXT,
+		);
+
+		run_message_loop_until(&stop);
+
+		UnhookWinEvent(hook);
+	});
+}
+
+/// Sends an event whenever a window is brought to the foreground, shown, renamed or destroyed,
+/// until `stop` is raised
+pub fn spawn_window_hooks(stop: Signal) {
+	thread::spawn(move || unsafe {
+		let focus_hook = SetWinEventHook(
+			EVENT_SYSTEM_FOREGROUND,
+			EVENT_SYSTEM_FOREGROUND,
+			ptr::null_mut(),
+			Some(focus_callback),
+			0,
+			0,
+			WINEVENT_OUTOFCONTEXT,
+		);
+
+		let object_hooks = [
+			EVENT_OBJECT_SHOW,
+			EVENT_OBJECT_NAMECHANGE,
+			EVENT_OBJECT_DESTROY,
+		]
+		.iter()
+		.map(|&event| {
+			SetWinEventHook(
+				event,
+				event,
+				ptr::null_mut(),
+				Some(object_callback),
+				0,
+				0,
+				WINEVENT_OUTOFCONTEXT,
+			)
+		})
+		.collect::<Vec<_>>();
+
+		run_message_loop_until(&stop);
+
+		UnhookWinEvent(focus_hook);
+		for hook in object_hooks {
+			UnhookWinEvent(hook);
 		}
 	});
 }
 
-/// Keeps track of which monitor is active
-pub fn spawn_track_monitor_thread(close_msg: Receiver<()>) {
+/// Makes [`send`] deliver events to `sender`
+///
+/// Only the first sender is used, it belongs to the tiling manager that was started first.
+pub fn set_sender(sender: Sender<Event>) {
+	let _ = SENDER.set(sender);
+}
+
+/// Sends `event` to the tiling manager
+///
+/// Events are dropped if the tiling manager wasn't started yet or doesn't run anymore.
+pub fn send(event: Event) -> bool {
+	match SENDER.get() {
+		Some(sender) => sender.send(event).is_ok(),
+		None => false,
+	}
+}
+
+/// Tracks the cursor and the drag modifiers while a window is dragged
+///
+/// The hooks only run when the mouse moves or a key is pressed or released, so the thread sleeps
+/// while neither happens.
+pub fn spawn_drag_tracker(stop: Signal, modifier: Modifier, span_modifier: Modifier) {
 	thread::spawn(move || unsafe {
-		let sender = INSTANCE.get().unwrap().channel.sender.clone();
+		DRAG.with(|drag| {
+			drag.set(Some(DragState {
+				modifier,
+				span_modifier,
+				previous: None,
+			}))
+		});
 
-		let mut previous_monitor = Monitor::get_active().name();
+		track_drag(cursor_position(), Modifier::is_pressed);
 
-		loop {
-			let current_monitor = Monitor::get_active().name();
+		let mouse_hook =
+			SetWindowsHookExW(WH_MOUSE_LL, Some(drag_mouse_callback), ptr::null_mut(), 0);
+		let keyboard_hook = SetWindowsHookExW(
+			WH_KEYBOARD_LL,
+			Some(drag_keyboard_callback),
+			ptr::null_mut(),
+			0,
+		);
 
-			if current_monitor != previous_monitor {
-				previous_monitor = current_monitor.clone();
+		run_message_loop_until(&stop);
 
-				let _ = sender.send(Event::MonitorChange);
-			}
+		UnhookWindowsHookEx(mouse_hook);
+		UnhookWindowsHookEx(keyboard_hook);
+	});
+}
+
+/// Sends a drag event if the cursor moved or the drag modifiers changed since the last one
+///
+/// `pressed` tells whether a modifier is held down.
+fn track_drag(point: (i32, i32), pressed: impl Fn(Modifier) -> bool) {
+	DRAG.with(|drag| {
+		if let Some(mut state) = drag.get() {
+			let snap = pressed(state.modifier);
+			let extend = snap && pressed(state.span_modifier);
+
+			if state.previous != Some((point, snap, extend)) {
+				state.previous = Some((point, snap, extend));
+				drag.set(Some(state));
 
-			select! {
-				recv(close_msg) -> _ => {
-					break;
-				}
-				default(Duration::from_millis(10)) => {}
+				send(Event::Drag {
+					point,
+					snap,
+					extend,
+				});
 			}
 		}
 	});
 }
 
+/// Keeps track of which monitor is active
+///
+/// The cursor is only looked at when the mouse moves, so the thread sleeps while it doesn't.
+pub fn spawn_track_monitor_thread(stop: Signal) {
+	thread::spawn(move || unsafe {
+		ACTIVE_MONITOR.with(|active| active.set(Some(Monitor::get_active())));
+
+		let hook = SetWindowsHookExW(WH_MOUSE_LL, Some(mouse_callback), ptr::null_mut(), 0);
+
+		run_message_loop_until(&stop);
+
+		UnhookWindowsHookEx(hook);
+	});
+}
+
+unsafe extern "system" fn mouse_callback(code: c_int, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+	if code == HC_ACTION && wparam == WM_MOUSEMOVE as WPARAM {
+		let info = &*(lparam as *const MSLLHOOKSTRUCT);
+		let monitor = Monitor::from_point((info.pt.x, info.pt.y));
+
+		let changed = ACTIVE_MONITOR.with(|active| active.replace(Some(monitor)) != Some(monitor));
+
+		if changed {
+			send(Event::MonitorChange);
+		}
+	}
+
+	CallNextHookEx(ptr::null_mut(), code, wparam, lparam)
+}
+
+unsafe extern "system" fn drag_mouse_callback(
+	code: c_int,
+	wparam: WPARAM,
+	lparam: LPARAM,
+) -> LRESULT {
+	if code == HC_ACTION && wparam == WM_MOUSEMOVE as WPARAM {
+		let info = &*(lparam as *const MSLLHOOKSTRUCT);
+
+		track_drag((info.pt.x, info.pt.y), Modifier::is_pressed);
+	}
+
+	CallNextHookEx(ptr::null_mut(), code, wparam, lparam)
+}
+
+unsafe extern "system" fn drag_keyboard_callback(
+	code: c_int,
+	wparam: WPARAM,
+	lparam: LPARAM,
+) -> LRESULT {
+	if code == HC_ACTION {
+		let info = &*(lparam as *const KBDLLHOOKSTRUCT);
+		let key = info.vkCode as c_int;
+		let down = wparam == WM_KEYDOWN as WPARAM || wparam == WM_SYSKEYDOWN as WPARAM;
+
+		track_drag(cursor_position(), |modifier| {
+			modifier.is_pressed_after(key, down)
+		});
+	}
+
+	CallNextHookEx(ptr::null_mut(), code, wparam, lparam)
+}
+
 unsafe extern "system" fn callback(
 	_: HWINEVENTHOOK,
 	_event: DWORD,
@@ -118,6 +415,59 @@ unsafe extern "system" fn callback(
 	_: DWORD,
 	_: DWORD,
 ) {
-	let sender = INSTANCE.get().unwrap().channel.sender.clone();
-	let _ = sender.send(Event::ActiveWindowChange(Window(hwnd)));
+	send(Event::ActiveWindowChange(Window(hwnd)));
+}
+
+unsafe extern "system" fn focus_callback(
+	_: HWINEVENTHOOK,
+	_: DWORD,
+	hwnd: HWND,
+	_: LONG,
+	_: LONG,
+	_: DWORD,
+	_: DWORD,
+) {
+	send(Event::WindowFocused(Window(hwnd)));
+}
+
+unsafe extern "system" fn object_callback(
+	_: HWINEVENTHOOK,
+	event: DWORD,
+	hwnd: HWND,
+	object: LONG,
+	child: LONG,
+	_: DWORD,
+	_: DWORD,
+) {
+	// The event is also sent for the parts of a window, like its cursor or scroll bars
+	if object != OBJID_WINDOW || child != CHILDID_SELF {
+		return;
+	}
+
+	match event {
+		EVENT_OBJECT_SHOW => send(Event::WindowShown(Window(hwnd))),
+		EVENT_OBJECT_NAMECHANGE => send(Event::WindowRenamed(Window(hwnd))),
+		EVENT_OBJECT_DESTROY => send(Event::WindowDestroyed(Window(hwnd))),
+		_ => false,
+	};
+}
+
+unsafe extern "system" fn move_size_callback(
+	_: HWINEVENTHOOK,
+	event: DWORD,
+	hwnd: HWND,
+	_: LONG,
+	_: LONG,
+	_: DWORD,
+	_: DWORD,
+) {
+	match event {
+		EVENT_SYSTEM_MOVESIZESTART => send(Event::MoveSizeStart(Window(hwnd))),
+		EVENT_SYSTEM_MOVESIZEEND => {
+			send(Event::MoveSizeEnd(Window(hwnd)));
+			// Remembers where the window was moved to, in case its monitor is removed later
+			send(Event::CheckDisplays)
+		}
+		_ => false,
+	};
 }
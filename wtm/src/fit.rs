@@ -0,0 +1,286 @@
+//! Fit module
+
+use serde::{de, Deserialize, Deserializer};
+use std::{fmt, str::FromStr};
+use winsapi::{Rect, SizeLimits};
+
+/// What happens to a window that can't be made as small as the area it is placed in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TooSmall {
+	/// The window keeps its minimum size and is centered on the area
+	Center,
+	/// The window is placed in the smallest zone that contains the area and is large enough
+	Expand,
+	/// The window isn't moved and is left floating
+	Float,
+}
+
+/// Names of the policies that can be used in the config
+const POLICIES: &[(&str, TooSmall)] = &[
+	("center", TooSmall::Center),
+	("expand", TooSmall::Expand),
+	("float", TooSmall::Float),
+];
+
+/// Where a window ends up when it is placed in an area
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fit {
+	/// The window goes to this rectangle
+	Place(Rect),
+	/// The window stays where it is
+	Float,
+}
+
+/// Where a window with `limits` goes when it is placed in `area` of `work_area`
+///
+/// Windows that can't be made as large as the area are centered on it at their maximum size.
+/// Windows that can't be made as small as the area are handled according to `policy`, `zones`
+/// are the areas they can expand into. A window that is centered is kept inside the work area
+/// where possible.
+pub fn fit(
+	area: Rect,
+	work_area: Rect,
+	limits: SizeLimits,
+	policy: TooSmall,
+	zones: &[Rect],
+) -> Fit {
+	let fits = |rect: &Rect| rect.w >= limits.min_width && rect.h >= limits.min_height;
+
+	if fits(&area) {
+		if area.w <= limits.max_width && area.h <= limits.max_height {
+			return Fit::Place(area);
+		}
+
+		return Fit::Place(center_on(area, work_area, limits));
+	}
+
+	match policy {
+		TooSmall::Center => Fit::Place(center_on(area, work_area, limits)),
+		TooSmall::Expand => {
+			let zone = zones
+				.iter()
+				.filter(|zone| contains(**zone, area) && fits(zone))
+				.min_by_key(|zone| zone.w as i64 * zone.h as i64);
+
+			match zone {
+				Some(zone) => Fit::Place(center_on(*zone, work_area, limits)),
+				None => Fit::Place(center_on(area, work_area, limits)),
+			}
+		}
+		TooSmall::Float => Fit::Float,
+	}
+}
+
+/// A rectangle the size of `area` within `limits`, centered on `area` and kept inside `work_area`
+fn center_on(area: Rect, work_area: Rect, limits: SizeLimits) -> Rect {
+	let w = area.w.min(limits.max_width).max(limits.min_width);
+	let h = area.h.min(limits.max_height).max(limits.min_height);
+
+	// Only shift the rectangle if it can fit into the work area at all
+	let inside = |start: i32, length: i32, area_start: i32, area_length: i32| {
+		if length > area_length {
+			start
+		} else {
+			start.clamp(area_start, area_start + area_length - length)
+		}
+	};
+
+	Rect {
+		x: inside(area.x + (area.w - w) / 2, w, work_area.x, work_area.w),
+		y: inside(area.y + (area.h - h) / 2, h, work_area.y, work_area.h),
+		w,
+		h,
+	}
+}
+
+/// Does `outer` cover all of `inner`
+fn contains(outer: Rect, inner: Rect) -> bool {
+	outer.x <= inner.x
+		&& outer.y <= inner.y
+		&& outer.x + outer.w >= inner.x + inner.w
+		&& outer.y + outer.h >= inner.y + inner.h
+}
+
+impl FromStr for TooSmall {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		POLICIES
+			.iter()
+			.find(|(name, _)| name.eq_ignore_ascii_case(s.trim()))
+			.map(|(_, policy)| *policy)
+			.ok_or_else(|| format!("unknown policy `{}`", s))
+	}
+}
+
+impl fmt::Display for TooSmall {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let (name, _) = POLICIES.iter().find(|(_, policy)| policy == self).unwrap();
+
+		f.write_str(name)
+	}
+}
+
+impl<'de> Deserialize<'de> for TooSmall {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		String::deserialize(deserializer)?
+			.parse()
+			.map_err(de::Error::custom)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const WORK_AREA: Rect = Rect {
+		x: 0,
+		y: 0,
+		w: 1920,
+		h: 1080,
+	};
+
+	fn rect(x: i32, y: i32, w: i32, h: i32) -> Rect {
+		Rect { x, y, w, h }
+	}
+
+	fn limits(min_width: i32, min_height: i32, max_width: i32, max_height: i32) -> SizeLimits {
+		SizeLimits {
+			min_width,
+			min_height,
+			max_width,
+			max_height,
+		}
+	}
+
+	fn at_least(min_width: i32, min_height: i32) -> SizeLimits {
+		limits(min_width, min_height, i32::MAX, i32::MAX)
+	}
+
+	#[test]
+	fn windows_within_their_limits_fill_the_area() {
+		let area = rect(960, 0, 960, 540);
+
+		for policy in [TooSmall::Center, TooSmall::Expand, TooSmall::Float] {
+			assert_eq!(
+				fit(area, WORK_AREA, limits(100, 100, 1000, 600), policy, &[]),
+				Fit::Place(area)
+			);
+		}
+	}
+
+	#[test]
+	fn windows_are_clamped_to_their_maximum_size_and_centered() {
+		let area = rect(0, 0, 960, 1080);
+
+		assert_eq!(
+			fit(
+				area,
+				WORK_AREA,
+				limits(0, 0, 800, 600),
+				TooSmall::Float,
+				&[]
+			),
+			Fit::Place(rect(80, 240, 800, 600))
+		);
+	}
+
+	#[test]
+	fn windows_are_clamped_to_their_minimum_size_and_centered() {
+		let area = rect(960, 0, 480, 540);
+
+		assert_eq!(
+			fit(area, WORK_AREA, at_least(600, 400), TooSmall::Center, &[]),
+			Fit::Place(rect(900, 0, 600, 540))
+		);
+	}
+
+	#[test]
+	fn centered_windows_stay_inside_the_work_area() {
+		let area = rect(1440, 0, 480, 540);
+
+		assert_eq!(
+			fit(area, WORK_AREA, at_least(600, 400), TooSmall::Center, &[]),
+			Fit::Place(rect(1320, 0, 600, 540))
+		);
+	}
+
+	#[test]
+	fn windows_larger_than_the_work_area_are_only_centered() {
+		let area = rect(0, 0, 960, 1080);
+
+		assert_eq!(
+			fit(area, WORK_AREA, at_least(2000, 0), TooSmall::Center, &[]),
+			Fit::Place(rect(-520, 0, 2000, 1080))
+		);
+	}
+
+	#[test]
+	fn windows_that_are_too_large_can_float() {
+		let area = rect(960, 0, 480, 540);
+
+		assert_eq!(
+			fit(area, WORK_AREA, at_least(600, 400), TooSmall::Float, &[]),
+			Fit::Float
+		);
+	}
+
+	#[test]
+	fn windows_expand_into_the_smallest_zone_they_fit() {
+		let area = rect(960, 0, 480, 540);
+		let zones = [
+			rect(960, 0, 960, 1080),
+			rect(960, 0, 960, 540),
+			rect(1440, 0, 480, 540),
+			rect(960, 0, 540, 540),
+		];
+
+		assert_eq!(
+			fit(
+				area,
+				WORK_AREA,
+				at_least(600, 400),
+				TooSmall::Expand,
+				&zones
+			),
+			Fit::Place(rect(960, 0, 960, 540))
+		);
+	}
+
+	#[test]
+	fn expanded_windows_keep_their_maximum_size() {
+		let area = rect(960, 0, 480, 540);
+		let zones = [rect(960, 0, 960, 1080)];
+
+		assert_eq!(
+			fit(
+				area,
+				WORK_AREA,
+				limits(600, 400, 800, 600),
+				TooSmall::Expand,
+				&zones
+			),
+			Fit::Place(rect(1040, 240, 800, 600))
+		);
+	}
+
+	#[test]
+	fn windows_without_a_zone_to_expand_into_are_centered() {
+		let area = rect(960, 0, 480, 540);
+		let zones = [rect(1440, 0, 480, 540)];
+
+		assert_eq!(
+			fit(
+				area,
+				WORK_AREA,
+				at_least(600, 400),
+				TooSmall::Expand,
+				&zones
+			),
+			Fit::Place(rect(900, 0, 600, 540))
+		);
+	}
+}
@@ -1,11 +1,37 @@
 //! Grid module
 
+mod drag;
+mod overlay;
 mod tile;
-
-use std::mem;
+mod zone_set;
+
+use crate::{
+	animation::Animator,
+	config::{ColorConfig, MergedCell, Profile, WtmConfig, MAX_WEIGHT},
+	history::History,
+	snap::SnapTarget,
+	zones::ZoneSet,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use tile::*;
-use winapi::um::winuser::{BeginPaint, EndPaint, PAINTSTRUCT};
-use winsapi::{Monitor, Rect, Window};
+
+pub use overlay::OVERLAY_COLOR_KEY;
+pub(crate) use overlay::{draw_number, draw_order};
+use winapi::um::{
+	wingdi::{CreateSolidBrush, DeleteObject},
+	winuser::{FillRect, GetDC, ReleaseDC},
+};
+use winsapi::{Insets, Rect, Window};
+
+/// A direction to move in
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Direction {
+	Left,
+	Right,
+	Up,
+	Down,
+}
 
 //TODO document this better
 /// The grid!
@@ -25,17 +51,110 @@ pub struct Grid {
 	pub active_window: Option<Window>,
 	/// The grid's window that the grid will be drawn on
 	pub grid_window: Option<Window>,
-	/// The last resize operation
-	pub previous_resize: Option<(Window, Rect)>,
+	/// The windows that were moved, so the moves can be undone
+	pub history: History,
 	/// is quick resize being used
 	pub quick_resize: bool,
+	/// is the span between the selected and hovered tile being extended with the keyboard
+	pub keyboard_span: bool,
+	/// Index of the zone set that is shown instead of the grid
+	pub zone_set: Option<usize>,
+	/// The zone of the zone set the mouse is hovering over
+	pub hovered_zone: Option<usize>,
+	/// The selected zone of the zone set
+	pub selected_zone: Option<usize>,
+	/// The window that is being dragged
+	pub drag_window: Option<Window>,
+	/// The area the dragged window snaps to when it is released
+	pub drag_area: Option<Rect>,
+	/// The work area of the monitor the zones are shown on
+	pub work_area: Rect,
+	/// Windows that were left floating because they can't be made small enough
+	pub floating: HashSet<Window>,
+	/// Moves the windows smoothly to where they were placed
+	pub animator: Animator,
 	grid_margins: u8,
 	zone_margins: u8,
 	border_margins: u8,
+	tile_width: u32,
+	tile_height: u32,
+	colors: ColorConfig,
 	tiles: Vec<Vec<Tile>>, // tiles[row][column]
+	row_weights: Vec<u32>,
+	column_weights: Vec<u32>,
+	merged: Vec<MergedCell>,
+	zone_sets: Vec<ZoneSet>,
 }
 
 impl Grid {
+	/// Creates a grid from the given config
+	pub fn new(config: &WtmConfig) -> Self {
+		let mut grid = Grid {
+			shift_down: false,
+			control_down: false,
+			cursor_down: false,
+			selected_tile: None,
+			hovered_tile: None,
+			active_window: None,
+			grid_window: None,
+			history: History::default(),
+			quick_resize: false,
+			keyboard_span: false,
+			zone_set: None,
+			hovered_zone: None,
+			selected_zone: None,
+			drag_window: None,
+			drag_area: None,
+			work_area: Rect::zero(),
+			floating: HashSet::new(),
+			animator: Animator::default(),
+			grid_margins: 0,
+			zone_margins: 0,
+			border_margins: 0,
+			tile_width: 0,
+			tile_height: 0,
+			colors: config.colors.clone(),
+			tiles: Vec::new(),
+			row_weights: Vec::new(),
+			column_weights: Vec::new(),
+			merged: Vec::new(),
+			zone_sets: Vec::new(),
+		};
+
+		grid.apply_config(config);
+		grid.apply_profile(&config.profiles()[0]);
+
+		grid
+	}
+
+	/// Applies the style of `config`
+	pub fn apply_config(&mut self, config: &WtmConfig) {
+		self.grid_margins = config.grid.grid_margins;
+		self.tile_width = config.tile.width;
+		self.tile_height = config.tile.height;
+		self.animator.set_frame_rate(config.animation.frame_rate);
+		self.colors = config.colors.clone();
+
+		if config.zone_sets != self.zone_sets {
+			self.zone_sets = config.zone_sets.clone();
+			self.zone_set = None;
+			self.hovered_zone = None;
+			self.selected_zone = None;
+		}
+	}
+
+	/// Applies the layout of `profile`, clearing the current selection
+	pub fn apply_profile(&mut self, profile: &Profile) {
+		self.zone_margins = profile.zone_margins;
+		self.border_margins = profile.border_margins;
+		self.tiles = vec![vec![Tile::default(); profile.columns]; profile.rows];
+		self.row_weights = weights_or_uniform(&profile.row_weights, profile.rows);
+		self.column_weights = weights_or_uniform(&profile.column_weights, profile.columns);
+		self.merged = profile.merged.clone();
+		self.selected_tile = None;
+		self.hovered_tile = None;
+	}
+
 	/// Resets the grid
 	pub fn reset(&mut self) {
 		self.shift_down = false;
@@ -45,6 +164,10 @@ impl Grid {
 		self.hovered_tile = None;
 		self.grid_window = None;
 		self.quick_resize = false;
+		self.keyboard_span = false;
+		self.zone_set = None;
+		self.hovered_zone = None;
+		self.selected_zone = None;
 
 		self.tiles.iter_mut().for_each(|row| {
 			row.iter_mut().for_each(|tile| {
@@ -56,42 +179,19 @@ impl Grid {
 
 	/// Get the dimensions of the grid window
 	pub fn dimensions(&self) -> (u32, u32) {
-		let width = self.columns() as u32 * TILE_WIDTH
+		let width = self.columns() as u32 * self.tile_width
 			+ (self.columns() as u32 + 1) * self.grid_margins as u32;
 
-		let height =
-			self.rows() as u32 * TILE_HEIGHT + (self.rows() as u32 + 1) * self.grid_margins as u32;
+		let height = self.rows() as u32 * self.tile_height
+			+ (self.rows() as u32 + 1) * self.grid_margins as u32;
 
 		(width, height)
 	}
 
 	fn zone_area(&self, row: usize, column: usize) -> Rect {
-		let work_area = Monitor::get_active().area();
-
-		let zone_width = (work_area.w
-			- self.border_margins as i32 * 2
-			- (self.columns() - 1) as i32 * self.zone_margins as i32)
-			/ self.columns() as i32;
-		let zone_height = (work_area.h
-			- self.border_margins as i32 * 2
-			- (self.rows() - 1) as i32 * self.zone_margins as i32)
-			/ self.rows() as i32;
-
-		let x = column as i32 * zone_width
-			+ self.border_margins as i32
-			+ column as i32 * self.zone_margins as i32
-			+ work_area.x;
-		let y = row as i32 * zone_height
-			+ self.border_margins as i32
-			+ row as i32 * self.zone_margins as i32
-			+ work_area.y;
+		let (from_tile, to_tile) = self.tile_span((row, column));
 
-		Rect {
-			x,
-			y,
-			w: zone_width,
-			h: zone_height,
-		}
+		self.span_area(from_tile, to_tile)
 	}
 
 	fn rows(&self) -> usize {
@@ -105,6 +205,7 @@ impl Grid {
 	/// Adds a row to the grid
 	pub fn add_row(&mut self) {
 		self.tiles.push(vec![Tile::default(); self.columns()]);
+		self.row_weights.push(1);
 	}
 
 	/// Adds a column to the grid
@@ -112,12 +213,15 @@ impl Grid {
 		for row in self.tiles.iter_mut() {
 			row.push(Tile::default());
 		}
+		self.column_weights.push(1);
 	}
 
 	/// Removes a row from the grid
 	pub fn remove_row(&mut self) {
 		if self.rows() > 1 {
 			self.tiles.pop();
+			self.row_weights.pop();
+			self.clamp_cursor();
 		}
 	}
 
@@ -127,179 +231,451 @@ impl Grid {
 			for row in self.tiles.iter_mut() {
 				row.pop();
 			}
+			self.column_weights.pop();
+			self.clamp_cursor();
+		}
+	}
+
+	/// Changes the weight of the row under the cursor by `delta`
+	pub fn change_row_weight(&mut self, delta: i32) -> bool {
+		match self.hovered_tile.or(self.selected_tile) {
+			Some((row, _)) => change_weight(&mut self.row_weights[row], delta),
+			None => false,
+		}
+	}
+
+	/// Changes the weight of the column under the cursor by `delta`
+	pub fn change_column_weight(&mut self, delta: i32) -> bool {
+		match self.hovered_tile.or(self.selected_tile) {
+			Some((_, column)) => change_weight(&mut self.column_weights[column], delta),
+			None => false,
+		}
+	}
+
+	/// Merges the tiles between the selected and the hovered tile
+	///
+	/// If the tiles already form a merged cell, it is split up again instead.
+	pub fn toggle_merge(&mut self) -> bool {
+		let (anchor, cursor) = match (self.selected_tile, self.hovered_tile) {
+			(Some(anchor), Some(cursor)) => (anchor, cursor),
+			(Some(tile), None) | (None, Some(tile)) => (tile, tile),
+			(None, None) => return false,
+		};
+
+		let (from_tile, to_tile) = self.expand_span(anchor, cursor);
+		let cell = MergedCell::from_span(from_tile, to_tile);
+
+		if self.merged.contains(&cell) {
+			self.merged.retain(|merged| *merged != cell);
+		} else if from_tile != to_tile {
+			self.merged
+				.retain(|merged| !merged.intersects(from_tile, to_tile));
+			self.merged.push(cell);
+		} else {
+			return false;
+		}
+
+		self.update_tiles();
+
+		true
+	}
+
+	/// Moves the selected and hovered tile back inside of the grid
+	fn clamp_cursor(&mut self) {
+		let (rows, columns) = (self.rows(), self.columns());
+		let clamp = |tile: Option<(usize, usize)>| {
+			tile.map(|(row, column)| (row.min(rows - 1), column.min(columns - 1)))
+		};
+
+		self.selected_tile = clamp(self.selected_tile);
+		self.hovered_tile = clamp(self.hovered_tile);
+		self.merged = self
+			.merged
+			.iter()
+			.filter_map(|cell| cell.clamp(rows, columns))
+			.collect();
+		self.update_tiles();
+	}
+
+	/// The area of the tiles from `from_tile` to `to_tile` inside of the grid window
+	///
+	/// The tiles are sized by their weights, so the grid window previews the layout of the zones.
+	fn tile_area(&self, from_tile: (usize, usize), to_tile: (usize, usize)) -> Rect {
+		let margins = self.grid_margins as i32;
+
+		self.weighted_area(self.window_area(), margins, margins, from_tile, to_tile)
+	}
+
+	/// The client area of the grid window
+	fn window_area(&self) -> Rect {
+		let (width, height) = self.dimensions();
+
+		Rect {
+			x: 0,
+			y: 0,
+			w: width as i32,
+			h: height as i32,
+		}
+	}
+
+	/// The area covered by the zones from `from_tile` to `to_tile` on the active monitor
+	fn span_area(&self, from_tile: (usize, usize), to_tile: (usize, usize)) -> Rect {
+		self.span_area_in(self.work_area, from_tile, to_tile)
+	}
+
+	/// The area covered by the zones from `from_tile` to `to_tile` inside of `work_area`
+	fn span_area_in(
+		&self,
+		work_area: Rect,
+		from_tile: (usize, usize),
+		to_tile: (usize, usize),
+	) -> Rect {
+		self.weighted_area(
+			work_area,
+			self.border_margins as i32,
+			self.zone_margins as i32,
+			from_tile,
+			to_tile,
+		)
+	}
+
+	/// The area of `target` inside of `work_area`
+	///
+	/// Cells that reach past the grid are cut off at its edge.
+	pub fn snap_area(&self, work_area: Rect, target: &SnapTarget) -> Rect {
+		match target {
+			SnapTarget::Region(region) => region.resolve(
+				work_area,
+				self.border_margins as i32,
+				self.zone_margins as i32,
+			),
+			SnapTarget::Cell(cell) => {
+				let (from_tile, to_tile) = cell.span();
+				let (from_tile, to_tile) = self.expand_span(from_tile, to_tile);
+
+				self.span_area_in(work_area, from_tile, to_tile)
+			}
+			SnapTarget::Zone(zone) => zone.resolve(work_area),
 		}
 	}
 
-	fn tile_area(&self, row: usize, column: usize) -> Rect {
-		let x = column as i32 * TILE_WIDTH as i32 + (column as i32 + 1) * self.grid_margins as i32;
+	/// Every area that can be selected in the grid, inside of `work_area`
+	pub fn span_areas(&self, work_area: Rect) -> Vec<Rect> {
+		let mut spans = HashSet::new();
+		let mut areas = Vec::new();
+
+		for from_row in 0..self.rows() {
+			for from_column in 0..self.columns() {
+				for to_row in from_row..self.rows() {
+					for to_column in from_column..self.columns() {
+						let (from_tile, to_tile) =
+							self.expand_span((from_row, from_column), (to_row, to_column));
+
+						// Merged cells make different selections cover the same tiles
+						if spans.insert((from_tile, to_tile)) {
+							areas.push(self.span_area_in(work_area, from_tile, to_tile));
+						}
+					}
+				}
+			}
+		}
 
-		let y = row as i32 * TILE_HEIGHT as i32 + (row as i32 + 1) * self.grid_margins as i32;
+		areas
+	}
+
+	/// Splits `area` by the row and column weights and returns the part from `from_tile` to
+	/// `to_tile`
+	fn weighted_area(
+		&self,
+		area: Rect,
+		border_margins: i32,
+		margins: i32,
+		from_tile: (usize, usize),
+		to_tile: (usize, usize),
+	) -> Rect {
+		let available_width = area.w - border_margins * 2 - (self.columns() - 1) as i32 * margins;
+		let available_height = area.h - border_margins * 2 - (self.rows() - 1) as i32 * margins;
+
+		let (x, w) = weighted_span(
+			available_width,
+			&self.column_weights,
+			from_tile.1,
+			to_tile.1,
+		);
+		let (y, h) = weighted_span(available_height, &self.row_weights, from_tile.0, to_tile.0);
 
 		Rect {
-			x,
-			y,
-			w: TILE_WIDTH as i32,
-			h: TILE_HEIGHT as i32,
+			x: area.x + border_margins + x + from_tile.1 as i32 * margins,
+			y: area.y + border_margins + y + from_tile.0 as i32 * margins,
+			w: w + (to_tile.1 - from_tile.1) as i32 * margins,
+			h: h + (to_tile.0 - from_tile.0) as i32 * margins,
 		}
 	}
 
-	/// Recenters the grid window after the a new row or column is added
-	pub fn reposition(&mut self) {
-		let work_area = Monitor::get_active().area();
+	/// Where the grid window goes, centered on the work area
+	pub fn window_rect(&self) -> Rect {
+		let work_area = self.work_area;
 		let dimensions = self.dimensions();
 
-		let rect = Rect {
+		Rect {
 			x: work_area.w / 2 - dimensions.0 as i32 / 2 + work_area.x,
 			y: work_area.h / 2 - dimensions.1 as i32 / 2 + work_area.y,
 			w: dimensions.0 as i32,
 			h: dimensions.1 as i32,
-		};
+		}
+	}
+
+	/// The tile at `point` inside of the grid window
+	fn tile_at(&self, point: (i32, i32)) -> Option<(usize, usize)> {
+		let margins = self.grid_margins as i32;
 
-		self.grid_window.as_mut().unwrap().set_pos(rect, None);
+		self.cell_at(self.window_area(), margins, margins, point)
 	}
 
-	/// Returns true if a change in highlighting occured
-	pub unsafe fn highlight_tiles(&mut self, point: (i32, i32)) -> Option<Rect> {
-		let original_tiles = self.tiles.clone();
-		let mut hovered_rect = None;
+	/// The tile whose zone contains `point` on the active monitor
+	fn zone_tile_at(&self, point: (i32, i32)) -> Option<(usize, usize)> {
+		self.cell_at(
+			self.work_area,
+			self.border_margins as i32,
+			self.zone_margins as i32,
+			point,
+		)
+	}
 
+	/// The tile whose cell contains `point`, with the cells laid out inside of `area`
+	fn cell_at(
+		&self,
+		area: Rect,
+		border_margins: i32,
+		margins: i32,
+		point: (i32, i32),
+	) -> Option<(usize, usize)> {
 		for row in 0..self.rows() {
 			for column in 0..self.columns() {
-				let tile_area = self.tile_area(row, column);
+				let (from_tile, to_tile) = self.tile_span((row, column));
+				let cell = self.weighted_area(area, border_margins, margins, from_tile, to_tile);
+
+				if cell.contains_point(point) {
+					return Some((row, column));
+				}
+			}
+		}
 
-				if tile_area.contains_point(point) {
-					self.tiles[row][column].hovered = true;
+		None
+	}
 
-					self.hovered_tile = Some((row, column));
-					hovered_rect = Some(self.zone_area(row, column));
-				} else {
-					self.tiles[row][column].hovered = false;
+	/// The zones of every cell on the active monitor
+	pub fn cell_areas(&self) -> Vec<Rect> {
+		let mut areas = Vec::new();
+
+		for row in 0..self.rows() {
+			for column in 0..self.columns() {
+				let (from_tile, to_tile) = self.tile_span((row, column));
+
+				if from_tile == (row, column) {
+					areas.push(self.span_area(from_tile, to_tile));
 				}
 			}
 		}
 
-		if let Some(rect) = self.shift_hover_and_calc_rect(true) {
-			hovered_rect = Some(rect);
+		areas
+	}
+
+	/// Returns true if a change in highlighting occured
+	pub fn highlight_tiles(&mut self, point: (i32, i32)) -> Option<Rect> {
+		if self.zone_set.is_some() {
+			return self.highlight_zone(point);
 		}
 
+		let original_tiles = self.tiles.clone();
+		let span = self.hover(self.tile_at(point));
+
 		if original_tiles == self.tiles {
 			None
 		} else {
-			hovered_rect
+			span.map(|(from_tile, to_tile)| self.span_area(from_tile, to_tile))
 		}
 	}
 
-	unsafe fn shift_hover_and_calc_rect(&mut self, highlight: bool) -> Option<Rect> {
-		if self.shift_down || self.cursor_down {
-			if let Some(selected_tile) = self.selected_tile {
-				if let Some(hovered_tile) = self.hovered_tile {
-					let selected_zone = self.zone_area(selected_tile.0, selected_tile.1);
-					let hovered_zone = self.zone_area(hovered_tile.0, hovered_tile.1);
+	/// Hovers `hovered_tile` and highlights the tiles of the resulting span
+	fn hover(
+		&mut self,
+		hovered_tile: Option<(usize, usize)>,
+	) -> Option<((usize, usize), (usize, usize))> {
+		if hovered_tile.is_some() {
+			self.hovered_tile = hovered_tile;
+		}
 
-					let from_tile;
-					let to_tile;
+		let span = self
+			.span()
+			.or_else(|| hovered_tile.map(|tile| self.tile_span(tile)));
 
-					let hovered_rect = if hovered_zone.x < selected_zone.x
-						&& hovered_zone.y > selected_zone.y
-					{
-						from_tile = (selected_tile.0, hovered_tile.1);
-						to_tile = (hovered_tile.0, selected_tile.1);
+		for (row, tiles) in self.tiles.iter_mut().enumerate() {
+			for (column, tile) in tiles.iter_mut().enumerate() {
+				tile.hovered = in_span(span, (row, column));
+			}
+		}
 
-						let from_zone = self.zone_area(from_tile.0, from_tile.1);
-						let to_zone = self.zone_area(to_tile.0, to_tile.1);
+		span
+	}
 
-						Rect {
-							x: from_zone.x,
-							y: from_zone.y,
-							w: (to_zone.x + to_zone.w) - from_zone.x,
-							h: (to_zone.y + to_zone.h) - from_zone.y,
-						}
-					} else if hovered_zone.y < selected_zone.y && hovered_zone.x > selected_zone.x {
-						from_tile = (hovered_tile.0, selected_tile.1);
-						to_tile = (selected_tile.0, hovered_tile.1);
-
-						let from_zone = self.zone_area(from_tile.0, from_tile.1);
-						let to_zone = self.zone_area(to_tile.0, to_tile.1);
-
-						Rect {
-							x: from_zone.x,
-							y: from_zone.y,
-							w: (to_zone.x + to_zone.w) - from_zone.x,
-							h: (to_zone.y + to_zone.h) - from_zone.y,
-						}
-					} else if hovered_zone.x > selected_zone.x || hovered_zone.y > selected_zone.y {
-						from_tile = selected_tile;
-						to_tile = hovered_tile;
-
-						Rect {
-							x: selected_zone.x,
-							y: selected_zone.y,
-							w: (hovered_zone.x + hovered_zone.w) - selected_zone.x,
-							h: (hovered_zone.y + hovered_zone.h) - selected_zone.y,
-						}
-					} else {
-						from_tile = hovered_tile;
-						to_tile = selected_tile;
-
-						Rect {
-							x: hovered_zone.x,
-							y: hovered_zone.y,
-							w: (selected_zone.x + selected_zone.w) - hovered_zone.x,
-							h: (selected_zone.y + selected_zone.h) - hovered_zone.y,
-						}
-					};
+	/// The first and last tile of the span between the selected and the hovered tile
+	fn span(&self) -> Option<((usize, usize), (usize, usize))> {
+		if !(self.shift_down || self.cursor_down || self.keyboard_span) {
+			return None;
+		}
 
-					if highlight {
-						for row in from_tile.0..=to_tile.0 {
-							for column in from_tile.1..=to_tile.1 {
-								self.tiles[row][column].hovered = true;
-							}
-						}
-					}
+		Some(self.expand_span(self.selected_tile?, self.hovered_tile?))
+	}
+
+	/// The first and last tile of the cell `tile` belongs to
+	fn tile_span(&self, tile: (usize, usize)) -> ((usize, usize), (usize, usize)) {
+		self.expand_span(tile, tile)
+	}
 
-					return Some(hovered_rect);
+	/// The smallest span that contains both tiles and doesn't cut through a merged cell
+	fn expand_span(
+		&self,
+		a: (usize, usize),
+		b: (usize, usize),
+	) -> ((usize, usize), (usize, usize)) {
+		let max_row = self.rows() - 1;
+		let max_column = self.columns() - 1;
+
+		let mut from_tile = (a.0.min(b.0).min(max_row), a.1.min(b.1).min(max_column));
+		let mut to_tile = (a.0.max(b.0).min(max_row), a.1.max(b.1).min(max_column));
+
+		// Growing the span can make it reach other merged cells, so repeat until nothing changes
+		loop {
+			let mut changed = false;
+
+			for cell in self.merged.iter() {
+				if !cell.intersects(from_tile, to_tile) {
+					continue;
+				}
+
+				let (cell_from, cell_to) = cell.span();
+
+				if cell_from.0 < from_tile.0
+					|| cell_from.1 < from_tile.1
+					|| cell_to.0 > to_tile.0
+					|| cell_to.1 > to_tile.1
+				{
+					from_tile = (from_tile.0.min(cell_from.0), from_tile.1.min(cell_from.1));
+					to_tile = (to_tile.0.max(cell_to.0), to_tile.1.max(cell_to.1));
+					changed = true;
 				}
 			}
-		}
 
-		None
+			if !changed {
+				return (from_tile, to_tile);
+			}
+		}
 	}
 
 	/// Selects a tile
-	pub unsafe fn select_tile(&mut self, point: (i32, i32)) -> bool {
+	pub fn select_tile(&mut self, point: (i32, i32)) -> bool {
+		if self.zone_set.is_some() {
+			return self.select_zone_at(point);
+		}
+
 		if self.cursor_down || self.shift_down {
 			return false;
 		}
 
 		let previously_selected = self.selected_tile;
+		let selected_span = self.tile_at(point).map(|tile| self.tile_span(tile));
 
-		for row in 0..self.rows() {
-			for column in 0..self.columns() {
-				let tile_area = self.tile_area(row, column);
-
-				if tile_area.contains_point(point) {
-					self.tiles[row][column].selected = true;
+		self.keyboard_span = false;
+		self.selected_tile = selected_span.map(|(from_tile, _)| from_tile);
 
-					self.selected_tile = Some((row, column));
-				} else {
-					self.tiles[row][column].selected = false;
-				}
+		for (row, tiles) in self.tiles.iter_mut().enumerate() {
+			for (column, tile) in tiles.iter_mut().enumerate() {
+				tile.selected = in_span(selected_span, (row, column));
 			}
 		}
 
 		self.selected_tile != previously_selected
 	}
 
+	/// Moves the keyboard cursor one tile in `direction`
+	///
+	/// The cursor is the hovered tile. When `extend` is set the selected tile stays in place and
+	/// the span between the two is highlighted, otherwise the selection follows the cursor.
+	pub fn move_cursor(&mut self, direction: Direction, extend: bool) {
+		let cursor = match self.hovered_tile.or(self.selected_tile) {
+			Some(tile) => {
+				// Merged cells are skipped as a whole
+				let (from_tile, to_tile) = self.tile_span(tile);
+
+				let (row, column) = match direction {
+					Direction::Left => (tile.0, from_tile.1.saturating_sub(1)),
+					Direction::Right => (tile.0, to_tile.1 + 1),
+					Direction::Up => (from_tile.0.saturating_sub(1), tile.1),
+					Direction::Down => (to_tile.0 + 1, tile.1),
+				};
+
+				(row.min(self.rows() - 1), column.min(self.columns() - 1))
+			}
+			// The first key press only shows where the cursor is
+			None => (0, 0),
+		};
+
+		if extend {
+			self.selected_tile = self.selected_tile.or(self.hovered_tile).or(Some(cursor));
+		} else {
+			self.selected_tile = Some(cursor);
+		}
+
+		self.hovered_tile = Some(cursor);
+		self.keyboard_span = extend;
+
+		self.update_tiles();
+	}
+
+	/// Syncs the highlighting of the tiles with the selected and hovered tile
+	fn update_tiles(&mut self) {
+		let selected_span = self.selected_tile.map(|tile| self.tile_span(tile));
+		let hovered_span = self
+			.span()
+			.or_else(|| self.hovered_tile.map(|tile| self.tile_span(tile)));
+
+		for (row, tiles) in self.tiles.iter_mut().enumerate() {
+			for (column, tile) in tiles.iter_mut().enumerate() {
+				tile.selected = in_span(selected_span, (row, column));
+				tile.hovered = in_span(hovered_span, (row, column));
+			}
+		}
+	}
+
 	/// Gets the selected area
-	pub unsafe fn selected_area(&mut self) -> Option<Rect> {
-		if let Some(shift_rect) = self.shift_hover_and_calc_rect(false) {
-			return Some(shift_rect);
+	pub fn selected_area(&mut self) -> Option<Rect> {
+		if self.zone_set.is_some() {
+			return self.selected_zone_area();
 		}
 
-		if let Some(selected_tile) = self.selected_tile {
-			Some(self.zone_area(selected_tile.0, selected_tile.1))
-		} else {
+		if let Some((from_tile, to_tile)) = self.span() {
+			return Some(self.span_area(from_tile, to_tile));
+		}
+
+		self.selected_tile
+			.map(|(row, column)| self.zone_area(row, column))
+	}
+
+	/// The active window and the selected area it has to be moved into
+	///
+	/// Returns nothing if nothing is selected, or if the window was just moved there.
+	pub fn active_window_placement(&mut self) -> Option<(Window, Rect)> {
+		let placement = (self.active_window?, self.selected_area()?);
+		let previous = self
+			.history
+			.last()
+			.map(|placement| (placement.window, placement.area));
+
+		if previous == Some(placement) {
 			None
+		} else {
+			Some(placement)
 		}
 	}
 
@@ -308,6 +684,7 @@ impl Grid {
 		self.tiles
 			.iter_mut()
 			.for_each(|row| row.iter_mut().for_each(|tile| tile.hovered = false));
+		self.hovered_zone = None;
 	}
 
 	/// Unselects all tiles
@@ -315,44 +692,321 @@ impl Grid {
 		self.tiles
 			.iter_mut()
 			.for_each(|row| row.iter_mut().for_each(|tile| tile.selected = false));
+		self.selected_zone = None;
 	}
 
 	/// Draws the grid to the window
 	pub unsafe fn draw(&self, window: Window) {
-		let mut paint: PAINTSTRUCT = mem::zeroed();
-		//paint.fErase = 1;
+		if self.zone_set.is_some() {
+			return self.draw_zone_map(window);
+		}
 
-		let hdc = BeginPaint(window.0, &mut paint);
+		let hdc = GetDC(window.0);
+
+		let background = CreateSolidBrush(self.colors.background.to_colorref());
+		FillRect(hdc, &self.window_area().into(), background);
+		DeleteObject(background as *mut _);
 
 		for row in 0..self.rows() {
 			for column in 0..self.columns() {
-				self.tiles[row][column].draw(hdc, self.tile_area(row, column));
+				let (from_tile, to_tile) = self.tile_span((row, column));
+
+				// A merged cell is drawn once, as a single tile
+				if from_tile == (row, column) {
+					let area = self.tile_area(from_tile, to_tile);
+
+					self.tiles[row][column].draw(hdc, area, &self.colors);
+				}
 			}
 		}
 
-		EndPaint(window.0, &paint);
+		ReleaseDC(window.0, hdc);
 	}
 }
 
-impl Default for Grid {
-	fn default() -> Self {
-		let rows = 2;
-		let columns = 2;
+/// Splits `length` into parts proportional to `weights`
+///
+/// Returns the offset and length of the parts from `from` to `to`. The parts are rounded so that
+/// they always add up to `length`.
+fn weighted_span(length: i32, weights: &[u32], from: usize, to: usize) -> (i32, i32) {
+	let total: i64 = weights.iter().map(|&weight| weight as i64).sum();
+	let edge = |index: usize| {
+		let before: i64 = weights[..index].iter().map(|&weight| weight as i64).sum();
 
-		Grid {
-			shift_down: false,
-			control_down: false,
-			cursor_down: false,
-			selected_tile: None,
-			hovered_tile: None,
-			active_window: None,
-			grid_window: None,
-			previous_resize: None,
-			quick_resize: false,
-			grid_margins: 3,
-			zone_margins: 10,
-			border_margins: 10,
-			tiles: vec![vec![Tile::default(); columns]; rows],
+		(length as i64 * before / total) as i32
+	};
+
+	let start = edge(from);
+
+	(start, edge(to + 1) - start)
+}
+
+/// The configured weights, or equal weights if there are none
+fn weights_or_uniform(weights: &[u32], count: usize) -> Vec<u32> {
+	if weights.is_empty() {
+		vec![1; count]
+	} else {
+		weights.to_vec()
+	}
+}
+
+/// Changes `weight` by `delta`, keeping it inside of the allowed range
+fn change_weight(weight: &mut u32, delta: i32) -> bool {
+	let changed = (*weight as i32 + delta).max(1).min(MAX_WEIGHT as i32) as u32;
+
+	if changed == *weight {
+		false
+	} else {
+		*weight = changed;
+		true
+	}
+}
+
+/// The invisible border of a window, `frame` if its rule sets one and the `measured` one
+/// otherwise
+pub fn window_insets(frame: Option<[i32; 4]>, measured: impl FnOnce() -> Insets) -> Insets {
+	match frame {
+		Some([left, top, right, bottom]) => Insets {
+			left,
+			top,
+			right,
+			bottom,
+		},
+		None => measured(),
+	}
+}
+
+fn in_span(span: Option<((usize, usize), (usize, usize))>, tile: (usize, usize)) -> bool {
+	match span {
+		Some((from, to)) => (from.0..=to.0).contains(&tile.0) && (from.1..=to.1).contains(&tile.1),
+		None => false,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn grid(rows: usize, columns: usize, merged: Vec<MergedCell>) -> Grid {
+		let mut grid = Grid::new(&WtmConfig::default());
+		grid.apply_profile(&Profile {
+			name: String::from("test"),
+			rows,
+			columns,
+			zone_margins: 0,
+			border_margins: 0,
+			row_weights: Vec::new(),
+			column_weights: Vec::new(),
+			merged,
+		});
+
+		grid
+	}
+
+	fn cell(row: usize, column: usize, rows: usize, columns: usize) -> MergedCell {
+		MergedCell {
+			row,
+			column,
+			rows,
+			columns,
+		}
+	}
+
+	/// The tiles for which `flag` is set, row by row
+	fn tiles(grid: &Grid, flag: fn(&Tile) -> bool) -> Vec<(usize, usize)> {
+		let mut tiles = Vec::new();
+
+		for (row, columns) in grid.tiles.iter().enumerate() {
+			for (column, tile) in columns.iter().enumerate() {
+				if flag(tile) {
+					tiles.push((row, column));
+				}
+			}
+		}
+
+		tiles
+	}
+
+	fn hovered(grid: &Grid) -> Vec<(usize, usize)> {
+		tiles(grid, |tile| tile.hovered)
+	}
+
+	fn selected(grid: &Grid) -> Vec<(usize, usize)> {
+		tiles(grid, |tile| tile.selected)
+	}
+
+	#[test]
+	fn first_key_press_shows_the_cursor() {
+		let mut grid = grid(3, 3, Vec::new());
+
+		grid.move_cursor(Direction::Right, false);
+
+		assert_eq!(grid.hovered_tile, Some((0, 0)));
+		assert_eq!(grid.selected_tile, Some((0, 0)));
+		assert_eq!(hovered(&grid), vec![(0, 0)]);
+		assert_eq!(selected(&grid), vec![(0, 0)]);
+	}
+
+	#[test]
+	fn selection_follows_the_cursor() {
+		let mut grid = grid(3, 3, Vec::new());
+
+		grid.move_cursor(Direction::Right, false);
+		grid.move_cursor(Direction::Right, false);
+		grid.move_cursor(Direction::Down, false);
+
+		assert_eq!(grid.hovered_tile, Some((1, 1)));
+		assert_eq!(grid.selected_tile, Some((1, 1)));
+		assert_eq!(selected(&grid), vec![(1, 1)]);
+		assert!(!grid.keyboard_span);
+	}
+
+	#[test]
+	fn cursor_stays_inside_at_the_edges() {
+		let mut grid = grid(2, 3, Vec::new());
+
+		grid.move_cursor(Direction::Up, false);
+		grid.move_cursor(Direction::Up, false);
+		grid.move_cursor(Direction::Left, false);
+		assert_eq!(grid.hovered_tile, Some((0, 0)));
+
+		for _ in 0..5 {
+			grid.move_cursor(Direction::Right, false);
+			grid.move_cursor(Direction::Down, false);
+		}
+		assert_eq!(grid.hovered_tile, Some((1, 2)));
+		assert_eq!(selected(&grid), vec![(1, 2)]);
+	}
+
+	#[test]
+	fn extending_keeps_the_selected_tile() {
+		let mut grid = grid(3, 3, Vec::new());
+
+		grid.move_cursor(Direction::Right, false);
+		grid.move_cursor(Direction::Right, true);
+		grid.move_cursor(Direction::Down, true);
+
+		assert_eq!(grid.selected_tile, Some((0, 0)));
+		assert_eq!(grid.hovered_tile, Some((1, 1)));
+		assert_eq!(selected(&grid), vec![(0, 0)]);
+		assert_eq!(hovered(&grid), vec![(0, 0), (0, 1), (1, 0), (1, 1)]);
+		assert_eq!(grid.span(), Some(((0, 0), (1, 1))));
+	}
+
+	#[test]
+	fn extending_without_a_selection_starts_at_the_cursor() {
+		let mut grid = grid(3, 3, Vec::new());
+
+		grid.move_cursor(Direction::Down, true);
+
+		assert_eq!(grid.selected_tile, Some((0, 0)));
+		assert_eq!(grid.hovered_tile, Some((0, 0)));
+		assert_eq!(hovered(&grid), vec![(0, 0)]);
+	}
+
+	#[test]
+	fn moving_without_extending_ends_the_span() {
+		let mut grid = grid(3, 3, Vec::new());
+
+		grid.move_cursor(Direction::Right, false);
+		grid.move_cursor(Direction::Right, true);
+		grid.move_cursor(Direction::Down, false);
+
+		assert_eq!(grid.selected_tile, Some((1, 1)));
+		assert_eq!(hovered(&grid), vec![(1, 1)]);
+		assert_eq!(grid.span(), None);
+	}
+
+	#[test]
+	fn merged_cells_are_skipped_as_a_whole() {
+		let mut grid = grid(2, 4, vec![cell(0, 1, 1, 2)]);
+
+		grid.move_cursor(Direction::Right, false);
+		grid.move_cursor(Direction::Right, false);
+		assert_eq!(grid.hovered_tile, Some((0, 1)));
+		assert_eq!(selected(&grid), vec![(0, 1), (0, 2)]);
+
+		grid.move_cursor(Direction::Right, false);
+		assert_eq!(grid.hovered_tile, Some((0, 3)));
+
+		grid.move_cursor(Direction::Left, false);
+		grid.move_cursor(Direction::Left, false);
+		assert_eq!(grid.hovered_tile, Some((0, 0)));
+	}
+
+	#[test]
+	fn entering_a_merged_cell_highlights_all_of_it() {
+		let mut grid = grid(3, 3, vec![cell(0, 1, 2, 2)]);
+
+		grid.move_cursor(Direction::Down, false);
+		grid.move_cursor(Direction::Down, false);
+		grid.move_cursor(Direction::Down, false);
+		grid.move_cursor(Direction::Right, false);
+		assert_eq!(grid.hovered_tile, Some((2, 1)));
+
+		grid.move_cursor(Direction::Up, false);
+		assert_eq!(grid.hovered_tile, Some((1, 1)));
+		assert_eq!(hovered(&grid), vec![(0, 1), (0, 2), (1, 1), (1, 2)]);
+		assert_eq!(selected(&grid), vec![(0, 1), (0, 2), (1, 1), (1, 2)]);
+
+		// Leaving downwards starts below the whole cell
+		grid.move_cursor(Direction::Down, false);
+		assert_eq!(grid.hovered_tile, Some((2, 1)));
+	}
+
+	#[test]
+	fn extended_span_grows_around_merged_cells() {
+		let mut grid = grid(3, 3, vec![cell(1, 1, 2, 1)]);
+
+		grid.move_cursor(Direction::Right, false);
+		grid.move_cursor(Direction::Right, true);
+		grid.move_cursor(Direction::Down, true);
+
+		assert_eq!(grid.hovered_tile, Some((1, 1)));
+		assert_eq!(grid.span(), Some(((0, 0), (2, 1))));
+		assert_eq!(
+			hovered(&grid),
+			vec![(0, 0), (0, 1), (1, 0), (1, 1), (2, 0), (2, 1)]
+		);
+	}
+
+	#[test]
+	fn removing_rows_keeps_the_cursor_inside() {
+		let mut grid = grid(3, 3, Vec::new());
+
+		for _ in 0..3 {
+			grid.move_cursor(Direction::Down, false);
 		}
+		assert_eq!(grid.hovered_tile, Some((2, 0)));
+
+		grid.remove_row();
+
+		assert_eq!(grid.hovered_tile, Some((1, 0)));
+		assert_eq!(selected(&grid), vec![(1, 0)]);
+	}
+
+	#[test]
+	fn the_frame_of_a_rule_replaces_the_measured_border() {
+		let measured = Insets {
+			left: 7,
+			top: 0,
+			right: 7,
+			bottom: 7,
+		};
+
+		assert_eq!(window_insets(None, || measured), measured);
+		assert_eq!(
+			window_insets(Some([1, 2, 3, 4]), || panic!("measured despite the rule")),
+			Insets {
+				left: 1,
+				top: 2,
+				right: 3,
+				bottom: 4,
+			}
+		);
+		assert_eq!(
+			window_insets(Some([0, 0, 0, 0]), || measured),
+			Insets::default()
+		);
 	}
 }
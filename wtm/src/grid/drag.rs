@@ -0,0 +1,153 @@
+use super::Grid;
+use winsapi::{Rect, Window};
+
+impl Grid {
+	/// Starts snapping `window` while it is dragged
+	pub fn start_drag(&mut self, window: Window) {
+		self.animator.cancel(window);
+		self.drag_window = Some(window);
+		self.drag_area = None;
+		self.selected_tile = None;
+		self.hovered_tile = None;
+	}
+
+	/// Highlights the zone under `point` on the monitor the cursor is on
+	///
+	/// The first zone is remembered, with `extend` set the span between it and the zone under
+	/// `point` is highlighted, just like holding Shift in the grid window. Returns the area the
+	/// window would snap to.
+	pub fn drag_to(&mut self, point: (i32, i32), extend: bool) -> Option<Rect> {
+		// Keep the previous zone while the cursor is between two zones
+		let tile = self.zone_tile_at(point).or(self.hovered_tile);
+
+		if !extend || self.selected_tile.is_none() {
+			self.selected_tile = tile;
+		}
+
+		self.shift_down = extend;
+		self.drag_area = self
+			.hover(tile)
+			.map(|(from_tile, to_tile)| self.span_area(from_tile, to_tile));
+
+		self.drag_area
+	}
+
+	/// Stops highlighting zones, the window won't be snapped when it is released
+	pub fn cancel_drag_snap(&mut self) {
+		self.drag_area = None;
+		self.selected_tile = None;
+		self.hovered_tile = None;
+		self.shift_down = false;
+	}
+
+	/// Stops tracking the dragged window
+	///
+	/// Returns the highlighted area the window has to snap to.
+	pub fn end_drag(&mut self) -> Option<Rect> {
+		let drag_area = self.drag_area;
+
+		self.cancel_drag_snap();
+		self.drag_window = None;
+		self.unhighlight_all_tiles();
+		self.unselect_all_tiles();
+
+		drag_area
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::config::WtmConfig;
+
+	const TOP_LEFT: Rect = Rect {
+		x: 10,
+		y: 10,
+		w: 485,
+		h: 485,
+	};
+	const BOTTOM_RIGHT: Rect = Rect {
+		x: 505,
+		y: 505,
+		w: 485,
+		h: 485,
+	};
+
+	/// A 2x2 grid on a square monitor, with 10 pixels between the zones and around them
+	fn dragging() -> Grid {
+		let mut grid = Grid::new(&WtmConfig::default());
+		grid.work_area = Rect {
+			x: 0,
+			y: 0,
+			w: 1000,
+			h: 1000,
+		};
+		grid.start_drag(Window(1 as _));
+
+		grid
+	}
+
+	#[test]
+	fn the_zone_under_the_cursor_is_highlighted() {
+		let mut grid = dragging();
+
+		assert_eq!(grid.drag_to((200, 200), false), Some(TOP_LEFT));
+		assert_eq!(grid.drag_to((800, 800), false), Some(BOTTOM_RIGHT));
+	}
+
+	#[test]
+	fn the_previous_zone_stays_while_the_cursor_is_between_zones() {
+		let mut grid = dragging();
+		grid.drag_to((200, 200), false);
+
+		assert_eq!(grid.drag_to((500, 200), false), Some(TOP_LEFT));
+		assert_eq!(grid.drag_to((5, 5), false), Some(TOP_LEFT));
+	}
+
+	#[test]
+	fn nothing_is_highlighted_before_the_cursor_reaches_a_zone() {
+		let mut grid = dragging();
+
+		assert_eq!(grid.drag_to((500, 500), false), None);
+		assert_eq!(grid.end_drag(), None);
+	}
+
+	#[test]
+	fn extending_spans_from_the_first_zone_to_the_one_under_the_cursor() {
+		let mut grid = dragging();
+		grid.drag_to((200, 200), false);
+
+		let span = Rect {
+			x: 10,
+			y: 10,
+			w: 980,
+			h: 980,
+		};
+		assert_eq!(grid.drag_to((800, 800), true), Some(span));
+
+		// Letting go of the modifier goes back to the zone under the cursor
+		assert_eq!(grid.drag_to((800, 800), false), Some(BOTTOM_RIGHT));
+	}
+
+	#[test]
+	fn releasing_snaps_to_the_highlighted_zone() {
+		let mut grid = dragging();
+		grid.drag_to((800, 800), false);
+
+		assert_eq!(grid.end_drag(), Some(BOTTOM_RIGHT));
+		assert_eq!(grid.drag_window, None);
+	}
+
+	#[test]
+	fn releasing_after_cancelling_does_not_snap() {
+		let mut grid = dragging();
+		grid.drag_to((800, 800), true);
+		grid.cancel_drag_snap();
+
+		assert_eq!(grid.end_drag(), None);
+
+		// A new drag starts from nothing
+		grid.start_drag(Window(1 as _));
+		assert_eq!(grid.drag_to((500, 500), false), None);
+	}
+}
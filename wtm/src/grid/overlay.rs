@@ -0,0 +1,156 @@
+use super::Grid;
+use winapi::{
+	shared::windef::HDC,
+	um::{
+		wingdi::{
+			CreateSolidBrush, DeleteObject, SelectObject, SetBkMode, SetTextColor, TRANSPARENT,
+		},
+		winuser::{
+			DrawTextW, FillRect, FrameRect, GetDC, ReleaseDC, DT_CENTER, DT_SINGLELINE, DT_VCENTER,
+		},
+	},
+};
+use winsapi::{Color, Font, Rect, Window};
+
+/// Color of the parts of the zone overlay that are see-through
+pub const OVERLAY_COLOR_KEY: Color = Color::new(255, 0, 255);
+
+/// Width of the frame around each zone of the overlay
+const OVERLAY_FRAME: i32 = 4;
+
+impl Grid {
+	/// Is the preview window used to show all zones instead of a single one
+	pub fn shows_overlay(&self) -> bool {
+		self.zone_set.is_some() || self.drag_window.is_some()
+	}
+
+	/// Draws the zones of the active monitor to the preview window
+	///
+	/// The preview window covers the whole work area, everything but the zones is drawn in
+	/// [`OVERLAY_COLOR_KEY`] so it can be made see-through. The zones of a zone set are numbered.
+	pub unsafe fn draw_overlay(&self, window: Window) {
+		let hdc = GetDC(window.0);
+
+		let work_area = self.work_area;
+		let to_window = |rect: Rect| Rect {
+			x: rect.x - work_area.x,
+			y: rect.y - work_area.y,
+			..rect
+		};
+
+		let key_brush = CreateSolidBrush(OVERLAY_COLOR_KEY.to_colorref());
+		let zone_brush = CreateSolidBrush(self.colors.preview.to_colorref());
+
+		FillRect(hdc, &to_window(work_area).into(), key_brush);
+
+		let numbered = self.zone_set.is_some();
+		let (zones, highlighted_zone) = if numbered {
+			(self.zone_areas(), self.highlighted_zone())
+		} else {
+			if let Some(drag_area) = self.drag_area {
+				FillRect(hdc, &to_window(drag_area).into(), zone_brush);
+			}
+
+			(self.cell_areas(), None)
+		};
+
+		let font = Font::create("Segoe UI", 72).ok();
+		if let Some(font) = font {
+			SelectObject(hdc, font.to_inner() as *mut _);
+		}
+
+		SetBkMode(hdc, TRANSPARENT as i32);
+		SetTextColor(hdc, self.colors.tile.to_colorref());
+
+		for (index, zone) in draw_order(zones) {
+			let area = to_window(zone);
+
+			if numbered {
+				// Covers the frames of the zones below
+				FillRect(hdc, &area.into(), key_brush);
+
+				if highlighted_zone == Some(index) {
+					FillRect(hdc, &area.into(), zone_brush);
+				}
+			}
+
+			for inset in 0..OVERLAY_FRAME {
+				let frame = Rect {
+					x: area.x + inset,
+					y: area.y + inset,
+					w: area.w - inset * 2,
+					h: area.h - inset * 2,
+				};
+
+				FrameRect(hdc, &frame.into(), zone_brush);
+			}
+
+			if numbered {
+				// A solid badge behind the number keeps its edges from blending with the color key
+				let badge_size = 120.min(area.w).min(area.h);
+				let badge = Rect {
+					x: area.x + (area.w - badge_size) / 2,
+					y: area.y + (area.h - badge_size) / 2,
+					w: badge_size,
+					h: badge_size,
+				};
+
+				FillRect(hdc, &badge.into(), zone_brush);
+				draw_number(hdc, badge, index);
+			}
+		}
+
+		if let Some(font) = font {
+			DeleteObject(font.to_inner() as *mut _);
+		}
+
+		DeleteObject(key_brush as *mut _);
+		DeleteObject(zone_brush as *mut _);
+
+		ReleaseDC(window.0, hdc);
+	}
+
+	/// Fills the preview window with the preview color, while it highlights a single zone
+	pub unsafe fn draw_preview(&self, window: Window) {
+		let hdc = GetDC(window.0);
+		let brush = CreateSolidBrush(self.colors.preview.to_colorref());
+
+		let client_rect = window.info().client_rect;
+		let area = Rect {
+			x: 0,
+			y: 0,
+			..client_rect
+		};
+
+		FillRect(hdc, &area.into(), brush);
+
+		DeleteObject(brush as *mut _);
+		ReleaseDC(window.0, hdc);
+	}
+}
+
+/// Sorts the zones from the largest to the smallest, so overlapping zones stay visible
+pub(crate) fn draw_order(zones: Vec<Rect>) -> Vec<(usize, Rect)> {
+	let mut zones: Vec<_> = zones.into_iter().enumerate().collect();
+	zones.sort_by_key(|(_, zone)| -(zone.w as i64 * zone.h as i64));
+
+	zones
+}
+
+/// Draws the number key of the zone at `index` centered in `area`
+pub(crate) unsafe fn draw_number(hdc: HDC, area: Rect, index: usize) {
+	let mut text: Vec<u16> = zone_key(index).encode_utf16().collect();
+
+	DrawTextW(
+		hdc,
+		text.as_mut_ptr(),
+		text.len() as i32,
+		&mut area.into(),
+		DT_CENTER | DT_VCENTER | DT_SINGLELINE,
+	);
+}
+
+/// The number key that selects the zone at `index`
+fn zone_key(index: usize) -> String {
+	((index + 1) % 10).to_string()
+}
@@ -1,15 +1,13 @@
+use crate::config::ColorConfig;
 use winapi::{
 	shared::windef::{HBRUSH, HDC},
 	um::{
-		wingdi::{CreateSolidBrush, DeleteObject, RGB},
+		wingdi::{CreateSolidBrush, DeleteObject},
 		winuser::{FillRect, FrameRect},
 	},
 };
 use winsapi::Rect;
 
-pub(super) const TILE_WIDTH: u32 = 48;
-pub(super) const TILE_HEIGHT: u32 = 48;
-
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub(super) struct Tile {
 	pub selected: bool,
@@ -17,9 +15,9 @@ pub(super) struct Tile {
 }
 
 impl Tile {
-	pub unsafe fn draw(self, hdc: HDC, area: Rect) {
-		let fill_brush = self.fill_brush();
-		let frame_brush = CreateSolidBrush(RGB(0, 0, 0));
+	pub unsafe fn draw(self, hdc: HDC, area: Rect, colors: &ColorConfig) {
+		let fill_brush = self.fill_brush(colors);
+		let frame_brush = CreateSolidBrush(colors.border.to_colorref());
 
 		FillRect(hdc, &area.into(), fill_brush);
 		FrameRect(hdc, &area.into(), frame_brush);
@@ -28,19 +26,15 @@ impl Tile {
 		DeleteObject(frame_brush as *mut _);
 	}
 
-	unsafe fn fill_brush(self) -> HBRUSH {
+	unsafe fn fill_brush(self, colors: &ColorConfig) -> HBRUSH {
 		let color = if self.selected {
-			RGB(0, 77, 128)
+			colors.selected
 		} else if self.hovered {
-			RGB(0, 100, 148)
+			colors.hovered
 		} else {
-			RGB(
-				(255.0 * (70.0 / 100.0)) as u8,
-				(255.0 * (70.0 / 100.0)) as u8,
-				(255.0 * (70.0 / 100.0)) as u8,
-			)
+			colors.tile
 		};
 
-		CreateSolidBrush(color)
+		CreateSolidBrush(color.to_colorref())
 	}
 }
@@ -0,0 +1,151 @@
+use super::{
+	overlay::{draw_number, draw_order},
+	Grid, Tile,
+};
+use crate::zones::ZoneSet;
+use std::mem;
+use winapi::um::{
+	wingdi::{CreateSolidBrush, DeleteObject, SetBkMode, SetTextColor, TRANSPARENT},
+	winuser::{FillRect, GetDC, ReleaseDC},
+};
+use winsapi::{Rect, Window};
+
+impl Grid {
+	/// The zone set that is shown instead of the grid
+	pub fn active_zone_set(&self) -> Option<&ZoneSet> {
+		self.zone_set.map(|index| &self.zone_sets[index])
+	}
+
+	/// Switches to the next zone set, or back to the grid after the last one
+	///
+	/// Returns false if there are no zone sets.
+	pub fn next_zone_set(&mut self) -> bool {
+		if self.zone_sets.is_empty() {
+			return false;
+		}
+
+		self.zone_set = match self.zone_set {
+			None => Some(0),
+			Some(index) if index + 1 < self.zone_sets.len() => Some(index + 1),
+			Some(_) => None,
+		};
+		self.hovered_zone = None;
+		self.selected_zone = None;
+
+		true
+	}
+
+	/// Selects the zone at `index` of the active zone set
+	pub fn select_zone(&mut self, index: usize) -> bool {
+		match self.active_zone_set() {
+			Some(zone_set) if index < zone_set.zones.len() => {
+				self.selected_zone = Some(index);
+				true
+			}
+			_ => false,
+		}
+	}
+
+	/// The zone the mouse is hovering over or, if there is none, the selected zone
+	pub fn highlighted_zone(&self) -> Option<usize> {
+		self.hovered_zone.or(self.selected_zone)
+	}
+
+	/// The zones of the active zone set on the active monitor
+	pub fn zone_areas(&self) -> Vec<Rect> {
+		match self.active_zone_set() {
+			Some(zone_set) => zone_set.resolve(self.work_area),
+			None => Vec::new(),
+		}
+	}
+
+	/// Where the zones are drawn inside of the grid window
+	fn zone_map_area(&self) -> Rect {
+		let (width, height) = self.dimensions();
+		let margins = self.grid_margins as i32;
+
+		Rect {
+			x: margins,
+			y: margins,
+			w: width as i32 - margins * 2,
+			h: height as i32 - margins * 2,
+		}
+	}
+
+	/// The zones of the active zone set, scaled down to the grid window
+	fn zone_map(&self) -> Vec<Rect> {
+		let work_area = self.work_area;
+		let map = self.zone_map_area();
+
+		self.zone_areas()
+			.into_iter()
+			.map(|zone| Rect {
+				x: map.x + (zone.x - work_area.x) * map.w / work_area.w,
+				y: map.y + (zone.y - work_area.y) * map.h / work_area.h,
+				w: zone.w * map.w / work_area.w,
+				h: zone.h * map.h / work_area.h,
+			})
+			.collect()
+	}
+
+	/// The zone at `point` inside of the grid window
+	fn zone_at(&self, point: (i32, i32)) -> Option<usize> {
+		let work_area = self.work_area;
+		let map = self.zone_map_area();
+
+		let point = (
+			work_area.x + (point.0 - map.x) * work_area.w / map.w,
+			work_area.y + (point.1 - map.y) * work_area.h / map.h,
+		);
+
+		self.active_zone_set()?.zone_at(work_area, point)
+	}
+
+	pub(super) fn highlight_zone(&mut self, point: (i32, i32)) -> Option<Rect> {
+		let hovered_zone = self.zone_at(point);
+
+		if mem::replace(&mut self.hovered_zone, hovered_zone) == hovered_zone {
+			None
+		} else {
+			Some(
+				hovered_zone
+					.map(|index| self.zone_areas()[index])
+					.unwrap_or_else(Rect::zero),
+			)
+		}
+	}
+
+	pub(super) fn select_zone_at(&mut self, point: (i32, i32)) -> bool {
+		let selected_zone = self.zone_at(point);
+
+		mem::replace(&mut self.selected_zone, selected_zone) != selected_zone
+	}
+
+	pub(super) fn selected_zone_area(&self) -> Option<Rect> {
+		self.selected_zone.map(|index| self.zone_areas()[index])
+	}
+
+	/// Draws the zones of the active zone set to the grid window
+	pub(super) unsafe fn draw_zone_map(&self, window: Window) {
+		let hdc = GetDC(window.0);
+
+		let background = CreateSolidBrush(self.colors.background.to_colorref());
+		FillRect(hdc, &self.window_area().into(), background);
+		DeleteObject(background as *mut _);
+
+		SetBkMode(hdc, TRANSPARENT as i32);
+		SetTextColor(hdc, self.colors.border.to_colorref());
+
+		for (index, area) in draw_order(self.zone_map()) {
+			let tile = Tile {
+				selected: self.selected_zone == Some(index),
+				hovered: self.hovered_zone == Some(index),
+			};
+
+			tile.draw(hdc, area, &self.colors);
+			draw_number(hdc, area, index);
+		}
+
+		ReleaseDC(window.0, hdc);
+	}
+}
@@ -0,0 +1,449 @@
+//! Hints module
+
+use crate::{
+	config::ColorConfig,
+	grid::{draw_number, draw_order, OVERLAY_COLOR_KEY},
+};
+use serde::{Deserialize, Serialize};
+use winapi::um::{
+	wingdi::{CreateSolidBrush, DeleteObject, SelectObject, SetBkMode, SetTextColor, TRANSPARENT},
+	winuser::{
+		DrawTextW, FillRect, FrameRect, GetDC, ReleaseDC, DT_CENTER, DT_SINGLELINE, DT_VCENTER,
+	},
+};
+use winsapi::{Font, Rect, Window};
+
+/// Height of the badge a label is drawn on
+const BADGE_HEIGHT: i32 = 40;
+
+/// Width of each letter of a label, the badge is as wide as its letters and the padding
+const LETTER_WIDTH: i32 = 24;
+
+/// Width of the frame around each zone, while a zone is picked for a window
+const ZONE_FRAME: i32 = 4;
+
+/// What happens to the window whose label was typed
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum HintAction {
+	/// The window is focused
+	Focus,
+	/// The window swaps its place with the foreground window
+	Swap,
+	/// The window is moved to the zone whose number is typed next
+	Zone,
+}
+
+/// A window that can be picked by typing its label
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hint {
+	/// The window
+	pub window: Window,
+	/// The visible part of the window, the label is drawn at its center
+	pub rect: Rect,
+	/// The letters that pick the window
+	pub label: String,
+}
+
+/// The state of hint mode, while the labels are shown
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hints {
+	/// What happens to the picked window
+	pub action: HintAction,
+	/// The window that was in the foreground before the labels were shown
+	pub foreground: Window,
+	/// The area the labels are drawn in, covers every monitor
+	pub area: Rect,
+	/// The labeled windows
+	pub hints: Vec<Hint>,
+	/// The letters typed so far
+	pub typed: String,
+	/// The window that waits for the number of its zone
+	pub picked: Option<Window>,
+	/// Index of the zone set the zones are taken from
+	pub zone_set: usize,
+	/// The zones the picked window can be moved to
+	pub zones: Vec<Rect>,
+}
+
+/// What typing a letter did
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Typed {
+	/// The letters typed so far start one or more labels
+	Pending,
+	/// The letters typed so far are the label of this window
+	Picked(Window),
+	/// No label starts with the letters typed so far and this one, the letter was ignored
+	Unknown,
+}
+
+/// Labels for `targets`, made of the letters of `alphabet`
+///
+/// No label is the start of another one, so typing a label always picks a single target. The
+/// target closest to `cursor` gets the first label, labels only get longer the further the
+/// targets are away. Returns no labels if `alphabet` has less than two letters.
+pub fn assign_labels(alphabet: &[char], targets: &[(i32, i32)], cursor: (i32, i32)) -> Vec<String> {
+	if alphabet.len() < 2 {
+		return Vec::new();
+	}
+
+	let mut labels: Vec<String> = alphabet.iter().map(char::to_string).collect();
+
+	// The last of the shortest labels turns into a prefix of one longer label per letter, which
+	// keeps the labels sorted by their length
+	while labels.len() < targets.len() {
+		let shortest = labels[0].len();
+		let last = labels
+			.iter()
+			.rposition(|label| label.len() == shortest)
+			.unwrap_or(0);
+
+		let prefix = labels.remove(last);
+		labels.extend(
+			alphabet
+				.iter()
+				.map(|letter| format!("{}{}", prefix, letter)),
+		);
+	}
+
+	let distance = |(x, y): (i32, i32)| {
+		let dx = (x - cursor.0) as i64;
+		let dy = (y - cursor.1) as i64;
+
+		dx * dx + dy * dy
+	};
+
+	let mut order: Vec<usize> = (0..targets.len()).collect();
+	order.sort_by_key(|index| distance(targets[*index]));
+
+	let mut assigned = vec![String::new(); targets.len()];
+
+	for (index, label) in order.into_iter().zip(labels) {
+		assigned[index] = label;
+	}
+
+	assigned
+}
+
+/// The smallest rectangle that contains every one of `rects`
+pub fn bounds(rects: &[Rect]) -> Rect {
+	let left = rects.iter().map(|rect| rect.x).min().unwrap_or(0);
+	let top = rects.iter().map(|rect| rect.y).min().unwrap_or(0);
+	let right = rects.iter().map(|rect| rect.x + rect.w).max().unwrap_or(0);
+	let bottom = rects.iter().map(|rect| rect.y + rect.h).max().unwrap_or(0);
+
+	Rect {
+		x: left,
+		y: top,
+		w: right - left,
+		h: bottom - top,
+	}
+}
+
+/// The center of `rect`
+fn center(rect: Rect) -> (i32, i32) {
+	(rect.x + rect.w / 2, rect.y + rect.h / 2)
+}
+
+impl Hints {
+	/// Labels `windows`, the windows closest to `cursor` get the shortest labels
+	///
+	/// `windows` holds each window together with its visible rectangle.
+	pub fn new(
+		action: HintAction,
+		foreground: Window,
+		area: Rect,
+		windows: Vec<(Window, Rect)>,
+		alphabet: &[char],
+		cursor: (i32, i32),
+	) -> Self {
+		let centers: Vec<_> = windows.iter().map(|(_, rect)| center(*rect)).collect();
+		let labels = assign_labels(alphabet, &centers, cursor);
+
+		let hints = windows
+			.into_iter()
+			.zip(labels)
+			.map(|((window, rect), label)| Hint {
+				window,
+				rect,
+				label,
+			})
+			.collect();
+
+		Self {
+			action,
+			foreground,
+			area,
+			hints,
+			typed: String::new(),
+			picked: None,
+			zone_set: 0,
+			zones: Vec::new(),
+		}
+	}
+
+	/// Adds `letter` to the letters typed so far
+	pub fn type_letter(&mut self, letter: char) -> Typed {
+		let typed = format!("{}{}", self.typed, letter);
+
+		if let Some(hint) = self.hints.iter().find(|hint| hint.label == typed) {
+			return Typed::Picked(hint.window);
+		}
+
+		if self.hints.iter().any(|hint| hint.label.starts_with(&typed)) {
+			self.typed = typed;
+			Typed::Pending
+		} else {
+			Typed::Unknown
+		}
+	}
+
+	/// Removes the last letter typed, returns false if nothing was typed
+	pub fn erase(&mut self) -> bool {
+		self.typed.pop().is_some()
+	}
+
+	/// Keeps `window` to be moved into one of `zones`, and hides the labels
+	pub fn pick(&mut self, window: Window, zones: Vec<Rect>) {
+		self.picked = Some(window);
+		self.zones = zones;
+	}
+
+	/// Draws the labels that start with the letters typed so far, or the zones once a window was
+	/// picked
+	///
+	/// The window covers [`Hints::area`], everything but the labels and zones is drawn in
+	/// [`OVERLAY_COLOR_KEY`] so it can be made see-through.
+	pub unsafe fn draw(&self, window: Window, colors: &ColorConfig) {
+		let hdc = GetDC(window.0);
+
+		let area = self.area;
+		let to_window = |rect: Rect| Rect {
+			x: rect.x - area.x,
+			y: rect.y - area.y,
+			..rect
+		};
+
+		let key_brush = CreateSolidBrush(OVERLAY_COLOR_KEY.to_colorref());
+		let badge_brush = CreateSolidBrush(colors.preview.to_colorref());
+
+		FillRect(hdc, &to_window(area).into(), key_brush);
+
+		SetBkMode(hdc, TRANSPARENT as i32);
+		SetTextColor(hdc, colors.tile.to_colorref());
+
+		if self.picked.is_some() {
+			let font = Font::create("Segoe UI", 72).ok();
+			if let Some(font) = font {
+				SelectObject(hdc, font.to_inner() as *mut _);
+			}
+
+			for (index, zone) in draw_order(self.zones.clone()) {
+				let zone = to_window(zone);
+
+				for inset in 0..ZONE_FRAME {
+					let frame = Rect {
+						x: zone.x + inset,
+						y: zone.y + inset,
+						w: zone.w - inset * 2,
+						h: zone.h - inset * 2,
+					};
+
+					FrameRect(hdc, &frame.into(), badge_brush);
+				}
+
+				let badge_size = 120.min(zone.w).min(zone.h);
+				let badge = Rect {
+					x: zone.x + (zone.w - badge_size) / 2,
+					y: zone.y + (zone.h - badge_size) / 2,
+					w: badge_size,
+					h: badge_size,
+				};
+
+				FillRect(hdc, &badge.into(), badge_brush);
+				draw_number(hdc, badge, index);
+			}
+
+			if let Some(font) = font {
+				DeleteObject(font.to_inner() as *mut _);
+			}
+		} else {
+			let font = Font::create("Segoe UI", 32).ok();
+			if let Some(font) = font {
+				SelectObject(hdc, font.to_inner() as *mut _);
+			}
+
+			let mut badges: Vec<Rect> = Vec::new();
+
+			for hint in &self.hints {
+				if hint.label.is_empty() || !hint.label.starts_with(&self.typed) {
+					continue;
+				}
+
+				let (x, y) = center(to_window(hint.rect));
+				let width = LETTER_WIDTH * hint.label.len() as i32 + LETTER_WIDTH / 2;
+
+				let mut badge = Rect {
+					x: x - width / 2,
+					y: y - BADGE_HEIGHT / 2,
+					w: width,
+					h: BADGE_HEIGHT,
+				};
+
+				// Windows with the same center, like maximized ones, get their labels stacked
+				while badges.iter().any(|other| overlaps(*other, badge)) {
+					badge.y += BADGE_HEIGHT + 4;
+				}
+
+				badges.push(badge);
+
+				FillRect(hdc, &badge.into(), badge_brush);
+
+				let mut text: Vec<u16> = hint.label.to_uppercase().encode_utf16().collect();
+
+				DrawTextW(
+					hdc,
+					text.as_mut_ptr(),
+					text.len() as i32,
+					&mut badge.into(),
+					DT_CENTER | DT_VCENTER | DT_SINGLELINE,
+				);
+			}
+
+			if let Some(font) = font {
+				DeleteObject(font.to_inner() as *mut _);
+			}
+		}
+
+		DeleteObject(key_brush as *mut _);
+		DeleteObject(badge_brush as *mut _);
+
+		ReleaseDC(window.0, hdc);
+	}
+}
+
+/// Do `rect` and `other` share any pixels
+fn overlaps(rect: Rect, other: Rect) -> bool {
+	rect.x < other.x + other.w
+		&& other.x < rect.x + rect.w
+		&& rect.y < other.y + other.h
+		&& other.y < rect.y + rect.h
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn window(id: usize) -> Window {
+		Window(id as _)
+	}
+
+	fn rect(x: i32, y: i32, w: i32, h: i32) -> Rect {
+		Rect { x, y, w, h }
+	}
+
+	/// `count` targets in a row, each 100 pixels further away from the origin
+	fn targets(count: usize) -> Vec<(i32, i32)> {
+		(0..count).map(|index| (index as i32 * 100, 0)).collect()
+	}
+
+	/// Hints for three windows labeled with the letters `a` and `b`
+	///
+	/// The window closest to the cursor gets `a`, the other two `ba` and `bb`.
+	fn hints() -> Hints {
+		let windows = (1..=3)
+			.map(|id| (window(id), rect(id as i32 * 1000, 0, 500, 500)))
+			.collect();
+
+		Hints::new(
+			HintAction::Focus,
+			window(0),
+			rect(0, 0, 4000, 500),
+			windows,
+			&['a', 'b'],
+			(0, 0),
+		)
+	}
+
+	#[test]
+	fn labels_are_unique_and_no_label_starts_another_one() {
+		for alphabet in [
+			&['a', 'b'][..],
+			&['a', 's', 'd'],
+			&['a', 's', 'd', 'f', 'j', 'k', 'l'],
+		] {
+			for count in 0..=60 {
+				let labels = assign_labels(alphabet, &targets(count), (0, 0));
+				assert_eq!(labels.len(), count);
+
+				for (index, label) in labels.iter().enumerate() {
+					assert!(!label.is_empty());
+					assert!(label.chars().all(|letter| alphabet.contains(&letter)));
+
+					for (other_index, other) in labels.iter().enumerate() {
+						if index != other_index {
+							assert!(!other.starts_with(label.as_str()), "{} {}", label, other);
+						}
+					}
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn closer_targets_get_labels_that_are_no_longer() {
+		let labels = assign_labels(&['a', 's', 'd'], &targets(5), (0, 0));
+		assert_eq!(labels, ["a", "s", "da", "ds", "dd"]);
+
+		let labels = assign_labels(&['a', 's', 'd'], &targets(20), (0, 0));
+		assert!(labels.windows(2).all(|pair| pair[0].len() <= pair[1].len()));
+	}
+
+	#[test]
+	fn labels_need_two_letters() {
+		assert!(assign_labels(&[], &targets(3), (0, 0)).is_empty());
+		assert!(assign_labels(&['a'], &targets(3), (0, 0)).is_empty());
+	}
+
+	#[test]
+	fn typed_letters_narrow_down_the_labels() {
+		let mut hints = hints();
+		let labels: Vec<&str> = hints.hints.iter().map(|hint| hint.label.as_str()).collect();
+		assert_eq!(labels, ["a", "ba", "bb"]);
+
+		assert_eq!(hints.type_letter('b'), Typed::Pending);
+		assert_eq!(hints.typed, "b");
+		assert_eq!(hints.type_letter('b'), Typed::Picked(window(3)));
+	}
+
+	#[test]
+	fn unknown_letters_are_ignored() {
+		let mut hints = hints();
+
+		assert_eq!(hints.type_letter('c'), Typed::Unknown);
+		assert_eq!(hints.typed, "");
+
+		hints.type_letter('b');
+		assert_eq!(hints.type_letter('c'), Typed::Unknown);
+		assert_eq!(hints.typed, "b");
+		assert_eq!(hints.type_letter('a'), Typed::Picked(window(2)));
+	}
+
+	#[test]
+	fn erasing_removes_the_last_typed_letter() {
+		let mut hints = hints();
+		assert!(!hints.erase());
+
+		hints.type_letter('b');
+		assert!(hints.erase());
+		assert_eq!(hints.typed, "");
+		assert_eq!(hints.type_letter('a'), Typed::Picked(window(1)));
+	}
+
+	#[test]
+	fn bounds_cover_every_rect() {
+		let rects = [rect(-1920, 100, 1920, 1080), rect(0, 0, 2560, 1440)];
+
+		assert_eq!(bounds(&rects), rect(-1920, 0, 4480, 1440));
+		assert_eq!(bounds(&[]), Rect::zero());
+	}
+}
@@ -0,0 +1,279 @@
+//! Placement history module
+
+use std::collections::{HashMap, VecDeque};
+use winsapi::{Rect, Window};
+
+/// The largest amount of placements that can be undone
+pub const MAX_HISTORY: usize = 100;
+
+/// A window that wtm moved
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement {
+	/// The window
+	pub window: Window,
+	/// The area the window was placed in
+	pub area: Rect,
+	/// The window's rectangle before it was moved
+	pub from: Rect,
+	/// The window's rectangle after it was moved
+	pub to: Rect,
+}
+
+/// The placements wtm made, so they can be undone and redone
+///
+/// The position of each window before wtm moved it for the first time is kept as well, so it can
+/// be restored no matter how often the window was moved since.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct History {
+	/// Placements that can be undone, the latest one last
+	undo: VecDeque<Placement>,
+	/// Placements that were undone, the latest one last
+	redo: Vec<Placement>,
+	/// The rectangle of each window before wtm moved it for the first time
+	originals: HashMap<Window, Rect>,
+}
+
+impl History {
+	/// Records a placement
+	///
+	/// Placements that were undone can't be redone afterwards. The oldest placement is forgotten
+	/// once there are more than [`MAX_HISTORY`].
+	pub fn record(&mut self, placement: Placement) {
+		self.originals
+			.entry(placement.window)
+			.or_insert(placement.from);
+
+		self.undo.push_back(placement);
+		self.redo.clear();
+
+		if self.undo.len() > MAX_HISTORY {
+			self.undo.pop_front();
+		}
+	}
+
+	/// The latest placement that can be undone
+	pub fn last(&self) -> Option<&Placement> {
+		self.undo.back()
+	}
+
+	/// The latest placement, so it can be corrected
+	pub fn last_mut(&mut self) -> Option<&mut Placement> {
+		self.undo.back_mut()
+	}
+
+	/// The area `window` was placed in, if it is still at `window_rect` since it was placed there
+	pub fn area_of(&self, window: Window, window_rect: Rect) -> Option<Rect> {
+		self.undo
+			.iter()
+			.rev()
+			.find(|placement| placement.window == window)
+			.filter(|placement| placement.to == window_rect)
+			.map(|placement| placement.area)
+	}
+
+	/// Takes the latest placement of a window that still exists, it can be redone afterwards
+	///
+	/// Placements of windows that `exists` returns false for are dropped.
+	pub fn undo(&mut self, exists: impl Fn(Window) -> bool) -> Option<Placement> {
+		while let Some(placement) = self.undo.pop_back() {
+			if exists(placement.window) {
+				self.redo.push(placement);
+				return Some(placement);
+			}
+		}
+
+		None
+	}
+
+	/// Takes the latest undone placement of a window that still exists, it can be undone again
+	///
+	/// Placements of windows that `exists` returns false for are dropped.
+	pub fn redo(&mut self, exists: impl Fn(Window) -> bool) -> Option<Placement> {
+		while let Some(placement) = self.redo.pop() {
+			if exists(placement.window) {
+				self.undo.push_back(placement);
+				return Some(placement);
+			}
+		}
+
+		None
+	}
+
+	/// The rectangle of `window` before wtm moved it for the first time
+	pub fn original(&self, window: Window) -> Option<Rect> {
+		self.originals.get(&window).copied()
+	}
+
+	/// Forgets everything about `window`, like after it was moved back to where it was originally
+	pub fn forget(&mut self, window: Window) {
+		self.undo.retain(|placement| placement.window != window);
+		self.redo.retain(|placement| placement.window != window);
+		self.originals.remove(&window);
+	}
+
+	/// Takes the original rectangle of every window that `exists` returns true for
+	///
+	/// The history is empty afterwards.
+	pub fn take_originals(&mut self, exists: impl Fn(Window) -> bool) -> Vec<(Window, Rect)> {
+		self.undo.clear();
+		self.redo.clear();
+
+		self.originals
+			.drain()
+			.filter(|(window, _)| exists(*window))
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn window(id: usize) -> Window {
+		Window(id as _)
+	}
+
+	/// A square at `x`, so the rectangles of different placements can be told apart
+	fn rect(x: i32) -> Rect {
+		Rect {
+			x,
+			y: 0,
+			w: 100,
+			h: 100,
+		}
+	}
+
+	/// `window` moved from `from` to `to`, placed in the area at `to`
+	fn placement(window: Window, from: i32, to: i32) -> Placement {
+		Placement {
+			window,
+			area: rect(to),
+			from: rect(from),
+			to: rect(to),
+		}
+	}
+
+	fn exists(_: Window) -> bool {
+		true
+	}
+
+	#[test]
+	fn placements_are_undone_and_redone_latest_first() {
+		let mut history = History::default();
+		history.record(placement(window(1), 0, 1));
+		history.record(placement(window(2), 0, 2));
+
+		assert_eq!(history.undo(exists), Some(placement(window(2), 0, 2)));
+		assert_eq!(history.undo(exists), Some(placement(window(1), 0, 1)));
+		assert_eq!(history.undo(exists), None);
+
+		assert_eq!(history.redo(exists), Some(placement(window(1), 0, 1)));
+		assert_eq!(history.redo(exists), Some(placement(window(2), 0, 2)));
+		assert_eq!(history.redo(exists), None);
+	}
+
+	#[test]
+	fn recording_a_placement_drops_what_could_be_redone() {
+		let mut history = History::default();
+		history.record(placement(window(1), 0, 1));
+		history.undo(exists);
+
+		history.record(placement(window(1), 0, 2));
+
+		assert_eq!(history.redo(exists), None);
+		assert_eq!(history.undo(exists), Some(placement(window(1), 0, 2)));
+	}
+
+	#[test]
+	fn only_the_latest_placements_are_kept() {
+		let mut history = History::default();
+
+		for index in 0..MAX_HISTORY + 5 {
+			history.record(placement(window(1), index as i32, index as i32 + 1));
+		}
+
+		let mut undone = 0;
+		while history.undo(exists).is_some() {
+			undone += 1;
+		}
+
+		assert_eq!(undone, MAX_HISTORY);
+		assert_eq!(history.redo(exists), Some(placement(window(1), 5, 6)));
+	}
+
+	#[test]
+	fn placements_of_closed_windows_are_skipped() {
+		let mut history = History::default();
+		history.record(placement(window(1), 0, 1));
+		history.record(placement(window(2), 0, 2));
+		history.record(placement(window(3), 0, 3));
+
+		let open = |window: Window| window != self::window(2);
+
+		assert_eq!(history.undo(open), Some(placement(window(3), 0, 3)));
+		assert_eq!(history.undo(open), Some(placement(window(1), 0, 1)));
+		assert_eq!(history.undo(open), None);
+
+		assert_eq!(
+			history.redo(|window| window != self::window(1)),
+			Some(placement(window(3), 0, 3))
+		);
+		assert_eq!(history.redo(exists), None);
+	}
+
+	#[test]
+	fn the_original_rectangle_is_where_the_window_was_first_moved_from() {
+		let mut history = History::default();
+		history.record(placement(window(1), 0, 1));
+		history.record(placement(window(1), 1, 2));
+
+		assert_eq!(history.original(window(1)), Some(rect(0)));
+
+		// Undoing doesn't change where the window came from
+		history.undo(exists);
+		history.undo(exists);
+		history.record(placement(window(1), 0, 3));
+		assert_eq!(history.original(window(1)), Some(rect(0)));
+	}
+
+	#[test]
+	fn the_area_is_only_known_while_the_window_stays_where_it_was_placed() {
+		let mut history = History::default();
+		history.record(placement(window(1), 0, 1));
+
+		assert_eq!(history.area_of(window(1), rect(1)), Some(rect(1)));
+		assert_eq!(history.area_of(window(1), rect(5)), None);
+		assert_eq!(history.area_of(window(2), rect(1)), None);
+	}
+
+	#[test]
+	fn forgetting_a_window_removes_all_of_its_placements() {
+		let mut history = History::default();
+		history.record(placement(window(1), 0, 1));
+		history.record(placement(window(2), 0, 2));
+		history.record(placement(window(1), 1, 3));
+		history.undo(exists);
+
+		history.forget(window(1));
+
+		assert_eq!(history.original(window(1)), None);
+		assert_eq!(history.redo(exists), None);
+		assert_eq!(history.undo(exists), Some(placement(window(2), 0, 2)));
+		assert_eq!(history.undo(exists), None);
+	}
+
+	#[test]
+	fn taking_the_originals_empties_the_history() {
+		let mut history = History::default();
+		history.record(placement(window(1), 0, 1));
+		history.record(placement(window(2), 5, 2));
+		history.record(placement(window(3), 7, 3));
+		history.undo(exists);
+
+		let mut originals = history.take_originals(|window| window != self::window(3));
+		originals.sort_by_key(|(_, rect)| rect.x);
+
+		assert_eq!(originals, vec![(window(1), rect(0)), (window(2), rect(5))]);
+		assert_eq!(history, History::default());
+	}
+}
@@ -0,0 +1,203 @@
+//! Input module
+
+use crate::{
+	command::Command,
+	grid::Direction,
+	hints::{Hints, Typed},
+	TilingManager,
+};
+use winapi::um::winuser::{
+	VK_BACK, VK_CONTROL, VK_DOWN, VK_ESCAPE, VK_F1, VK_F6, VK_LEFT, VK_RETURN, VK_RIGHT, VK_SHIFT,
+	VK_SPACE, VK_TAB, VK_UP,
+};
+use winsapi::Rect;
+
+const VK_0: i32 = 0x30;
+const VK_9: i32 = 0x39;
+const VK_A: i32 = 0x41;
+const VK_H: i32 = 0x48;
+const VK_J: i32 = 0x4A;
+const VK_K: i32 = 0x4B;
+const VK_L: i32 = 0x4C;
+const VK_M: i32 = 0x4D;
+const VK_Z: i32 = 0x5A;
+
+impl TilingManager {
+	/// Handles a key that was pressed while the grid window has the focus
+	pub(crate) fn grid_key_down(&mut self, key: i32) {
+		let grid = &mut self.grid;
+		let weights = grid.control_down && grid.shift_down;
+
+		let repaint = match key {
+			VK_ESCAPE => {
+				self.close_windows();
+				false
+			}
+			VK_CONTROL => {
+				grid.control_down = true;
+				false
+			}
+			VK_SHIFT => {
+				grid.shift_down = true;
+				false
+			}
+			VK_RIGHT if weights => grid.change_column_weight(1),
+			VK_LEFT if weights => grid.change_column_weight(-1),
+			VK_UP if weights => grid.change_row_weight(1),
+			VK_DOWN if weights => grid.change_row_weight(-1),
+			VK_M => grid.toggle_merge(),
+			VK_RIGHT | VK_LEFT | VK_UP | VK_DOWN if grid.control_down => {
+				match key {
+					VK_RIGHT => grid.add_column(),
+					VK_LEFT => grid.remove_column(),
+					VK_UP => grid.add_row(),
+					_ => grid.remove_row(),
+				}
+
+				self.reposition_grid();
+				false
+			}
+			VK_LEFT | VK_H => self.move_cursor(Direction::Left),
+			VK_RIGHT | VK_L => self.move_cursor(Direction::Right),
+			VK_UP | VK_K => self.move_cursor(Direction::Up),
+			VK_DOWN | VK_J => self.move_cursor(Direction::Down),
+			VK_TAB if grid.next_zone_set() => {
+				self.show_zone_overlay();
+				true
+			}
+			VK_0..=VK_9 if grid.zone_set.is_some() => {
+				// 1 is the first zone and 0 the tenth
+				let index = (key - VK_0 + 9) as usize % 10;
+
+				if grid.select_zone(index) {
+					self.highlight_zone(Rect::zero());
+
+					if self.resize_active_window() && self.grid.quick_resize {
+						self.close_windows();
+					}
+
+					true
+				} else {
+					false
+				}
+			}
+			VK_RETURN => {
+				self.resize_active_window();
+				self.close_windows();
+				false
+			}
+			VK_SPACE => {
+				self.resize_active_window();
+				false
+			}
+			_ => false,
+		};
+
+		if repaint {
+			self.redraw_grid();
+		}
+	}
+
+	/// Handles a key that was released while the grid window has the focus
+	pub(crate) fn grid_key_up(&mut self, key: i32) {
+		match key {
+			VK_CONTROL => self.grid.control_down = false,
+			VK_SHIFT => self.grid.shift_down = false,
+			VK_F1..=VK_F6 => self.change_profile((key - VK_F1) as usize),
+			_ => {}
+		}
+	}
+
+	/// Handles a key that was pressed while the hint window has the focus
+	///
+	/// Letters pick a window by its label, once a window waits for its zone the number keys pick
+	/// the zone and Tab switches to the next zone set.
+	pub(crate) fn hint_key_down(&mut self, key: i32) {
+		let picked = match &self.hints {
+			Some(hints) => hints.picked,
+			None => return,
+		};
+
+		let repaint = match key {
+			VK_ESCAPE => {
+				if let Some(hints) = self.close_hints() {
+					self.commands.push(Command::Focus(hints.foreground));
+				}
+
+				false
+			}
+			VK_BACK if picked.is_none() => self.hints.as_mut().is_some_and(Hints::erase),
+			VK_TAB if picked.is_some() => self.next_hint_zone_set(),
+			VK_0..=VK_9 if picked.is_some() => {
+				// 1 is the first zone and 0 the tenth
+				self.place_in_hint_zone((key - VK_0 + 9) as usize % 10);
+				false
+			}
+			VK_A..=VK_Z if picked.is_none() => {
+				let letter = (key as u8 as char).to_ascii_lowercase();
+
+				match self.hints.as_mut().map(|hints| hints.type_letter(letter)) {
+					Some(Typed::Picked(window)) => self.apply_hint(window),
+					Some(Typed::Pending) => true,
+					_ => false,
+				}
+			}
+			_ => false,
+		};
+
+		if repaint {
+			if let Some(hint_window) = self.hint_window {
+				self.commands.push(Command::Redraw(hint_window));
+			}
+		}
+	}
+
+	/// Highlights the tiles under the mouse at `point` inside of the grid window
+	pub(crate) fn grid_mouse_move(&mut self, point: (i32, i32)) {
+		if let Some(rect) = self.grid.highlight_tiles(point) {
+			self.highlight_zone(rect);
+			self.redraw_grid();
+		}
+	}
+
+	/// Selects the tile under the mouse at `point` inside of the grid window
+	pub(crate) fn grid_mouse_down(&mut self, point: (i32, i32)) {
+		let repaint = self.grid.select_tile(point);
+
+		self.grid.cursor_down = true;
+
+		if repaint {
+			self.redraw_grid();
+		}
+	}
+
+	/// Moves the active window into the selected tiles
+	pub(crate) fn grid_mouse_up(&mut self) {
+		if self.grid.selected_area().is_some() {
+			if self.grid.active_window.is_some() {
+				if self.resize_active_window() && self.grid.quick_resize {
+					self.close_windows();
+				}
+
+				self.grid.unselect_all_tiles();
+			}
+
+			self.redraw_grid();
+		}
+
+		self.grid.cursor_down = false;
+	}
+
+	/// Moves the keyboard cursor of the grid and previews the selected area
+	fn move_cursor(&mut self, direction: Direction) -> bool {
+		let extend = self.grid.shift_down;
+
+		self.grid.move_cursor(direction, extend);
+
+		if let Some(rect) = self.grid.selected_area() {
+			self.highlight_zone(rect);
+		}
+
+		true
+	}
+}
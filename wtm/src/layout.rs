@@ -0,0 +1,529 @@
+//! Layout module
+
+use serde::{de, Deserialize, Deserializer};
+use std::{fmt, str::FromStr};
+use winsapi::Rect;
+
+/// How the windows of a monitor are tiled
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Layout {
+	/// The first windows share a master area, the others are stacked next to it
+	MasterStack,
+	/// Columns of equal width
+	Columns,
+	/// Rows of equal height
+	Rows,
+	/// Each window takes half of the remaining area, split along its longer side
+	Bsp,
+	/// Each window takes half of the remaining area, turning clockwise
+	Spiral,
+	/// Every window fills the whole work area
+	Monocle,
+}
+
+/// Settings shared by the layouts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayoutOptions {
+	/// Width of the master area, as a fraction of the work area
+	pub master_ratio: f32,
+	/// Amount of windows in the master area
+	pub master_count: usize,
+	/// Space between the windows and to the edges of the work area
+	pub gaps: i32,
+}
+
+/// Names of the layouts that can be used in the config
+const LAYOUTS: &[(&str, Layout)] = &[
+	("master-stack", Layout::MasterStack),
+	("columns", Layout::Columns),
+	("rows", Layout::Rows),
+	("bsp", Layout::Bsp),
+	("spiral", Layout::Spiral),
+	("monocle", Layout::Monocle),
+];
+
+/// The direction an area is split in
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Axis {
+	/// Side by side
+	Horizontal,
+	/// On top of each other
+	Vertical,
+}
+
+impl Layout {
+	/// The area of each of `count` windows inside of `work_area`, in the order of the windows
+	///
+	/// The areas don't overlap and cover the whole work area, except for the gaps between them
+	/// and to its edges. Only monocle places every window on the same area.
+	pub fn arrange(self, count: usize, work_area: Rect, options: &LayoutOptions) -> Vec<Rect> {
+		if count == 0 {
+			return Vec::new();
+		}
+
+		let gaps = options.gaps;
+		let area = Rect {
+			x: work_area.x + gaps,
+			y: work_area.y + gaps,
+			w: work_area.w - gaps * 2,
+			h: work_area.h - gaps * 2,
+		};
+
+		match self {
+			Layout::MasterStack => master_stack(area, count, options),
+			Layout::Columns => split_even(area, count, Axis::Horizontal, gaps),
+			Layout::Rows => split_even(area, count, Axis::Vertical, gaps),
+			Layout::Bsp => bsp(area, count, gaps),
+			Layout::Spiral => spiral(area, count, gaps),
+			Layout::Monocle => vec![area; count],
+		}
+	}
+}
+
+fn master_stack(area: Rect, count: usize, options: &LayoutOptions) -> Vec<Rect> {
+	let masters = options.master_count.min(count);
+	let stacked = count - masters;
+
+	// Without a stack the masters take the whole area, and the other way around
+	if masters == 0 || stacked == 0 {
+		return split_even(area, count, Axis::Vertical, options.gaps);
+	}
+
+	let (master_area, stack_area) =
+		split(area, Axis::Horizontal, options.master_ratio, options.gaps);
+
+	let mut areas = split_even(master_area, masters, Axis::Vertical, options.gaps);
+	areas.extend(split_even(
+		stack_area,
+		stacked,
+		Axis::Vertical,
+		options.gaps,
+	));
+
+	areas
+}
+
+fn bsp(mut area: Rect, count: usize, gaps: i32) -> Vec<Rect> {
+	let mut areas = Vec::with_capacity(count);
+
+	for _ in 1..count {
+		let axis = if area.w >= area.h {
+			Axis::Horizontal
+		} else {
+			Axis::Vertical
+		};
+
+		let (window, rest) = split(area, axis, 0.5, gaps);
+		areas.push(window);
+		area = rest;
+	}
+
+	areas.push(area);
+	areas
+}
+
+fn spiral(mut area: Rect, count: usize, gaps: i32) -> Vec<Rect> {
+	let mut areas = Vec::with_capacity(count);
+
+	for index in 1..count {
+		let axis = if index % 2 == 1 {
+			Axis::Horizontal
+		} else {
+			Axis::Vertical
+		};
+
+		// Left, top, right, bottom and around again
+		let (first, second) = split(area, axis, 0.5, gaps);
+		let (window, rest) = if (index - 1) % 4 < 2 {
+			(first, second)
+		} else {
+			(second, first)
+		};
+
+		areas.push(window);
+		area = rest;
+	}
+
+	areas.push(area);
+	areas
+}
+
+/// Splits `area` in two along `axis`, the first part gets `ratio` of the space without the gap
+fn split(area: Rect, axis: Axis, ratio: f32, gaps: i32) -> (Rect, Rect) {
+	let length = match axis {
+		Axis::Horizontal => area.w,
+		Axis::Vertical => area.h,
+	};
+
+	let available = (length - gaps).max(0);
+	let first = ((available as f32 * ratio).round() as i32)
+		.max(0)
+		.min(available);
+	let second = available - first;
+
+	match axis {
+		Axis::Horizontal => (
+			Rect { w: first, ..area },
+			Rect {
+				x: area.x + first + gaps,
+				w: second,
+				..area
+			},
+		),
+		Axis::Vertical => (
+			Rect { h: first, ..area },
+			Rect {
+				y: area.y + first + gaps,
+				h: second,
+				..area
+			},
+		),
+	}
+}
+
+/// Splits `area` into `count` parts of the same size along `axis`
+///
+/// The pixels that can't be shared evenly go to the first parts.
+fn split_even(area: Rect, count: usize, axis: Axis, gaps: i32) -> Vec<Rect> {
+	let (start, length) = match axis {
+		Axis::Horizontal => (area.x, area.w),
+		Axis::Vertical => (area.y, area.h),
+	};
+
+	let count = count as i32;
+	let available = (length - gaps * (count - 1)).max(0);
+	let mut position = start;
+
+	(0..count)
+		.map(|index| {
+			let size = available / count + if index < available % count { 1 } else { 0 };
+			let part = match axis {
+				Axis::Horizontal => Rect {
+					x: position,
+					w: size,
+					..area
+				},
+				Axis::Vertical => Rect {
+					y: position,
+					h: size,
+					..area
+				},
+			};
+
+			position += size + gaps;
+			part
+		})
+		.collect()
+}
+
+impl FromStr for Layout {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		LAYOUTS
+			.iter()
+			.find(|(name, _)| name.eq_ignore_ascii_case(s.trim()))
+			.map(|(_, layout)| *layout)
+			.ok_or_else(|| format!("unknown layout `{}`", s))
+	}
+}
+
+impl fmt::Display for Layout {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let (name, _) = LAYOUTS.iter().find(|(_, layout)| layout == self).unwrap();
+
+		f.write_str(name)
+	}
+}
+
+impl<'de> Deserialize<'de> for Layout {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		String::deserialize(deserializer)?
+			.parse()
+			.map_err(de::Error::custom)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const LAYOUTS: &[Layout] = &[
+		Layout::MasterStack,
+		Layout::Columns,
+		Layout::Rows,
+		Layout::Bsp,
+		Layout::Spiral,
+	];
+
+	/// Work areas of common and odd sizes, some of them not at the origin
+	const WORK_AREAS: &[Rect] = &[
+		Rect {
+			x: 0,
+			y: 0,
+			w: 1920,
+			h: 1080,
+		},
+		Rect {
+			x: -1920,
+			y: 40,
+			w: 1001,
+			h: 667,
+		},
+		Rect {
+			x: 7,
+			y: -3,
+			w: 799,
+			h: 601,
+		},
+		Rect {
+			x: 0,
+			y: 0,
+			w: 1080,
+			h: 1920,
+		},
+	];
+
+	const GAPS: &[i32] = &[0, 1, 7, 10];
+
+	const MAX_COUNT: usize = 10;
+
+	fn options() -> Vec<LayoutOptions> {
+		let mut options = Vec::new();
+
+		for &gaps in GAPS {
+			for &master_ratio in &[0.5, 0.6, 1.0 / 3.0] {
+				for master_count in 0..=3 {
+					options.push(LayoutOptions {
+						master_ratio,
+						master_count,
+						gaps,
+					});
+				}
+			}
+		}
+
+		options
+	}
+
+	/// The work area without the gaps to its edges
+	fn inner(work_area: Rect, gaps: i32) -> Rect {
+		Rect {
+			x: work_area.x + gaps,
+			y: work_area.y + gaps,
+			w: work_area.w - gaps * 2,
+			h: work_area.h - gaps * 2,
+		}
+	}
+
+	fn overlap(start: i32, length: i32, other_start: i32, other_length: i32) -> bool {
+		start < other_start + other_length && other_start < start + length
+	}
+
+	/// Calls `check` with every layout, work area, option and window count
+	fn for_every_case(check: impl Fn(Layout, Rect, &LayoutOptions, usize, &[Rect])) {
+		for &layout in LAYOUTS {
+			for &work_area in WORK_AREAS {
+				for options in options() {
+					for count in 0..=MAX_COUNT {
+						let areas = layout.arrange(count, work_area, &options);
+
+						check(layout, work_area, &options, count, &areas);
+					}
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn every_window_gets_an_area() {
+		for_every_case(|layout, work_area, options, count, areas| {
+			assert_eq!(areas.len(), count, "{} in {:?}", layout, work_area);
+			assert!(
+				areas.iter().all(|area| area.w > 0 && area.h > 0),
+				"{} with {} windows in {:?} and {:?}: {:?}",
+				layout,
+				count,
+				work_area,
+				options,
+				areas
+			);
+		});
+	}
+
+	#[test]
+	fn areas_stay_inside_the_gaps_to_the_edges() {
+		for_every_case(|layout, work_area, options, _, areas| {
+			let inner = inner(work_area, options.gaps);
+
+			for area in areas {
+				assert!(
+					area.x >= inner.x
+						&& area.y >= inner.y
+						&& area.x + area.w <= inner.x + inner.w
+						&& area.y + area.h <= inner.y + inner.h,
+					"{} placed {:?} outside of {:?}",
+					layout,
+					area,
+					inner
+				);
+			}
+		});
+	}
+
+	#[test]
+	fn areas_keep_the_gaps_between_them() {
+		for_every_case(|layout, _, options, _, areas| {
+			let gaps = options.gaps;
+
+			for (i, a) in areas.iter().enumerate() {
+				for b in &areas[i + 1..] {
+					let apart = a.x + a.w + gaps <= b.x
+						|| b.x + b.w + gaps <= a.x
+						|| a.y + a.h + gaps <= b.y
+						|| b.y + b.h + gaps <= a.y;
+
+					assert!(apart, "{} placed {:?} and {:?} too close", layout, a, b);
+				}
+			}
+		});
+	}
+
+	#[test]
+	fn areas_cover_the_work_area() {
+		for_every_case(|layout, work_area, options, count, areas| {
+			if count == 0 || options.gaps != 0 {
+				return;
+			}
+
+			// Without gaps, areas that don't overlap cover everything if their sizes add up
+			let covered: i64 = areas.iter().map(|area| area.w as i64 * area.h as i64).sum();
+
+			assert_eq!(
+				covered,
+				work_area.w as i64 * work_area.h as i64,
+				"{} with {} windows in {:?}",
+				layout,
+				count,
+				work_area
+			);
+		});
+	}
+
+	#[test]
+	fn every_edge_touches_the_work_area_or_is_a_gap_away_from_a_neighbour() {
+		for_every_case(|layout, work_area, options, _, areas| {
+			let gaps = options.gaps;
+			let inner = inner(work_area, gaps);
+
+			for area in areas {
+				let left = area.x == inner.x
+					|| areas.iter().any(|other| {
+						other.x + other.w + gaps == area.x
+							&& overlap(area.y, area.h, other.y, other.h)
+					});
+				let right = area.x + area.w == inner.x + inner.w
+					|| areas.iter().any(|other| {
+						area.x + area.w + gaps == other.x
+							&& overlap(area.y, area.h, other.y, other.h)
+					});
+				let top = area.y == inner.y
+					|| areas.iter().any(|other| {
+						other.y + other.h + gaps == area.y
+							&& overlap(area.x, area.w, other.x, other.w)
+					});
+				let bottom = area.y + area.h == inner.y + inner.h
+					|| areas.iter().any(|other| {
+						area.y + area.h + gaps == other.y
+							&& overlap(area.x, area.w, other.x, other.w)
+					});
+
+				assert!(
+					left && right && top && bottom,
+					"{} left space around {:?} in {:?}",
+					layout,
+					area,
+					areas
+				);
+			}
+		});
+	}
+
+	#[test]
+	fn even_splits_differ_by_at_most_a_pixel() {
+		for &work_area in WORK_AREAS {
+			for &gaps in GAPS {
+				for count in 1..=MAX_COUNT {
+					let options = LayoutOptions {
+						master_ratio: 0.5,
+						master_count: 1,
+						gaps,
+					};
+
+					let columns = Layout::Columns.arrange(count, work_area, &options);
+					let widths: Vec<_> = columns.iter().map(|area| area.w).collect();
+					let rows = Layout::Rows.arrange(count, work_area, &options);
+					let heights: Vec<_> = rows.iter().map(|area| area.h).collect();
+
+					for sizes in &[widths, heights] {
+						let min = sizes.iter().min().unwrap();
+						let max = sizes.iter().max().unwrap();
+
+						assert!(max - min <= 1, "{:?}", sizes);
+						// The extra pixels go to the first parts
+						assert!(sizes.windows(2).all(|pair| pair[0] >= pair[1]));
+					}
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn master_area_follows_the_ratio() {
+		let work_area = WORK_AREAS[0];
+		let options = LayoutOptions {
+			master_ratio: 0.6,
+			master_count: 2,
+			gaps: 0,
+		};
+
+		let areas = Layout::MasterStack.arrange(5, work_area, &options);
+
+		assert_eq!(areas[0].w, 1152);
+		assert_eq!(areas[1].w, 1152);
+		assert!(areas[2..]
+			.iter()
+			.all(|area| area.x == 1152 && area.w == 768));
+		assert_eq!((areas[0].h, areas[1].h), (540, 540));
+		assert_eq!(
+			areas[2..].iter().map(|area| area.h).collect::<Vec<_>>(),
+			vec![360, 360, 360]
+		);
+	}
+
+	#[test]
+	fn monocle_gives_every_window_the_whole_work_area() {
+		for &work_area in WORK_AREAS {
+			for options in options() {
+				for count in 0..=MAX_COUNT {
+					let areas = Layout::Monocle.arrange(count, work_area, &options);
+
+					assert_eq!(areas, vec![inner(work_area, options.gaps); count]);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn layouts_round_trip_through_their_names() {
+		for &(name, layout) in super::LAYOUTS {
+			assert_eq!(name.parse::<Layout>(), Ok(layout));
+			assert_eq!(layout.to_string(), name);
+		}
+
+		assert!("tabbed".parse::<Layout>().is_err());
+	}
+}
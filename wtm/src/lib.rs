@@ -3,180 +3,2143 @@
 //!
 //! A simple tiling manager that works natively for Windows
 
+mod animation;
+mod arrangements;
+mod command;
+mod config;
+mod desktop;
+mod displays;
 mod event;
+mod fit;
 mod grid;
+mod hints;
+mod history;
+mod input;
+mod layout;
+mod monitors;
+mod navigation;
+mod nudge;
+mod placement;
+mod record;
+mod rules;
+mod snap;
+mod tiling;
 mod window;
+mod workspaces;
+mod zones;
+
+pub use crate::{
+	config::*,
+	event::Notification,
+	grid::Direction,
+	layout::*,
+	record::{RecordError, ReplayedPlacement},
+	rules::*,
+	snap::*,
+	zones::*,
+};
 
 use crate::{
-	event::{spawn_foreground_hook, spawn_track_monitor_thread, Event, HotkeyType},
-	grid::Grid,
-	window::{spawn_grid_window, spawn_preview_window},
+	arrangements::{Arrangement, Entry, Setups, Step, WindowState},
+	command::Command,
+	desktop::{Desktop, SystemDesktop},
+	displays::{fingerprint, rescue, Display, DisplayTracker},
+	event::{
+		set_sender, spawn_drag_tracker, spawn_foreground_hook, spawn_move_size_hook,
+		spawn_track_monitor_thread, spawn_window_hooks, Event, HotkeyType,
+	},
+	grid::{Grid, OVERLAY_COLOR_KEY},
+	hints::{bounds, HintAction, Hints},
+	monitors::{adjacent, map_area, relocate, target_monitor, MonitorTarget},
+	navigation::neighbor,
+	nudge::{center, snap_edges, Adjustment},
+	record::Recorder,
+	tiling::Tiling,
+	window::{spawn_display_window, spawn_grid_window, spawn_hint_window, spawn_preview_window},
+	workspaces::{Transition, Workspaces},
+};
+use crossbeam_channel::{after, never, select, unbounded, Receiver, Sender};
+use log::{error, info, warn};
+use std::{
+	cell::RefCell,
+	collections::{HashMap, HashSet},
+	fs, io, mem, thread,
+	time::Instant,
 };
-use crossbeam_channel::select;
-use once_cell::sync::OnceCell;
-use std::{mem, thread};
 use winapi::um::winuser::{
 	SetForegroundWindow, ShowWindow, TrackMouseEvent, SW_SHOW, TME_LEAVE, TRACKMOUSEEVENT,
 };
-use winsapi::{EventChannel, GlobalHotkeySet, Key, Modifier, Window};
-
-static mut INSTANCE: OnceCell<TilingManager> = OnceCell::new();
+use winsapi::{EventChannel, GlobalHotkeySet, HotkeyHandle, Insets, Rect, Signal, Window};
 
 #[derive(Debug)]
 pub struct TilingManager {
 	channel: EventChannel<Event>,
+	config: WtmConfig,
 	grid: Grid,
-
-	margin: u8,
-	padding: u8,
+	hotkeys: Option<HotkeyHandle>,
+	subscribers: Vec<Sender<Notification>>,
+	/// Index of the active profile of each monitor, by monitor name
+	active_profiles: HashMap<String, usize>,
+	/// The last snap hotkey used on each window
+	snap_cycles: HashMap<Window, SnapCycle>,
+	/// The tiled windows of each workspace, by monitor name and workspace index
+	tilings: HashMap<(String, usize), Tiling>,
+	/// The workspaces of each monitor, by monitor name
+	workspaces: HashMap<String, Workspaces>,
+	/// Windows that existed when wtm started or that a rule was applied to
+	ruled_windows: HashSet<Window>,
+	/// Index of the rule matching each window, kept while an event is handled so the properties
+	/// of a window are only read once
+	matched_rules: RefCell<HashMap<Window, Option<usize>>>,
+	/// Every window that was focused, the most recently focused one first
+	focus_history: Vec<Window>,
+	/// Notices changes of the monitor setup
+	displays: DisplayTracker,
+	/// Where the monitors and windows are read from while an event is handled
+	desktop: Box<dyn Desktop>,
+	/// The commands asked for by the event that is handled
+	commands: Vec<Command>,
+	/// Windows moved to another monitor, with the rect they were placed in and the border they
+	/// were placed with
+	border_checks: HashMap<Window, (Rect, Insets)>,
+	/// Writes the events to the recording file while recording is enabled
+	recorder: Option<Recorder>,
 
 	preview_window: Option<Window>,
 	grid_window: Option<Window>,
 	track_mouse: bool,
 
-	close_channel: EventChannel<()>,
+	/// Stops the threads that only run while the grid window is open, created with the first of
+	/// them
+	close_signal: Option<Signal>,
+	/// Stops the threads that only run while a window is dragged
+	drag_signal: Option<Signal>,
+	/// Stops the hooks that run for as long as wtm runs
+	hook_signal: Option<Signal>,
+
+	/// The labeled windows, while hint mode is active
+	hints: Option<Hints>,
+	hint_window: Option<Window>,
+	/// Stops the thread of the hint window
+	hint_signal: Option<Signal>,
 }
 
 impl Default for TilingManager {
 	fn default() -> Self {
+		Self::new(WtmConfig::default(), Box::new(SystemDesktop))
+	}
+}
+
+impl TilingManager {
+	fn new(config: WtmConfig, desktop: Box<dyn Desktop>) -> Self {
 		Self {
 			channel: Default::default(),
-			grid: Default::default(),
-			margin: 10,
-			padding: 10,
+			grid: Grid::new(&config),
+			config,
+			hotkeys: None,
+			subscribers: Vec::new(),
+			active_profiles: HashMap::new(),
+			snap_cycles: HashMap::new(),
+			tilings: HashMap::new(),
+			workspaces: HashMap::new(),
+			ruled_windows: HashSet::new(),
+			matched_rules: RefCell::default(),
+			focus_history: Vec::new(),
+			displays: DisplayTracker::default(),
+			desktop,
+			commands: Vec::new(),
+			border_checks: HashMap::new(),
+			recorder: None,
 			preview_window: Default::default(),
 			grid_window: Default::default(),
 			track_mouse: Default::default(),
-			close_channel: EventChannel::bounded(3),
+			close_signal: None,
+			drag_signal: None,
+			hook_signal: None,
+			hints: None,
+			hint_window: None,
+			hint_signal: None,
 		}
 	}
-}
 
-impl TilingManager {
-	pub fn create() -> &'static mut Self {
-		unsafe {
-			match INSTANCE.get_mut() {
-				Some(instance) => instance,
-				None => {
-					INSTANCE.set(TilingManager::default()).unwrap();
-					INSTANCE.get_mut().unwrap()
-				}
-			}
-		}
+	/// Creates the tiling manager using the config file in the user's config directory
+	///
+	/// The default config is used if the file is missing or invalid.
+	pub fn create() -> Self {
+		let config = WtmConfig::load().unwrap_or_else(|err| {
+			error!("Failed to load config, using defaults: {}", err);
+			WtmConfig::default()
+		});
+
+		Self::with_config(config)
+	}
+
+	/// Creates the tiling manager using `config`
+	pub fn with_config(config: WtmConfig) -> Self {
+		Self::new(config, Box::new(SystemDesktop))
+	}
+
+	/// Returns a receiver for the notifications sent by wtm
+	pub fn subscribe(&mut self) -> Receiver<Notification> {
+		let (sender, receiver) = unbounded();
+		self.subscribers.push(sender);
+
+		receiver
 	}
 
-	pub fn start(&'static self) {
+	/// Starts handling events on a thread that owns the tiling manager from then on
+	pub fn start(mut self) {
 		thread::spawn(move || {
 			let receiver = self.channel.receiver.clone();
+			set_sender(self.channel.sender.clone());
+
+			if let Err(err) = self.setup_hotkeys() {
+				error!("Failed to register the hotkeys: {}", err);
+			}
 
-			self.setup_hotkeys();
+			self.watch_config();
+			self.update_recording();
+			if let Some(stop) = signal(&mut self.hook_signal) {
+				spawn_move_size_hook(stop);
+			}
+
+			// Rules are only applied to new windows
+			self.ruled_windows.extend(self.desktop.windows());
+			if let Some(stop) = signal(&mut self.hook_signal) {
+				spawn_window_hooks(stop);
+			}
+			self.displays = DisplayTracker::new(
+				self.desktop.monitors(),
+				Self::display_windows(self.desktop.as_ref()),
+			);
+			spawn_display_window();
+
+			let monitor = self.desktop.active_monitor();
+			self.notify_profile(&monitor.name);
+			self.notify_workspace(&monitor.name);
+
+			for command in mem::take(&mut self.commands) {
+				self.execute(command);
+			}
 
 			loop {
-				select! {
-					recv(receiver) -> msg => {
-						self.handle_event(msg.unwrap());
-					}
+				let frame = match self.grid.animator.next_frame() {
+					Some(next_frame) => after(next_frame.saturating_duration_since(Instant::now())),
+					None => never(),
+				};
+
+				let event = select! {
+					recv(receiver) -> msg => msg.unwrap(),
+					recv(frame) -> _ => Event::AnimationFrame,
+				};
+
+				self.record(event);
+
+				for command in self.update(event) {
+					self.execute(command);
 				}
 			}
 		});
 	}
 
-	fn setup_hotkeys(&'static self) {
-		let hotkeys = GlobalHotkeySet::new()
+	/// Starts or stops recording the events, as the config says
+	fn update_recording(&mut self) {
+		if !self.config.recording.enabled {
+			self.recorder = None;
+		} else if self.recorder.is_none() {
+			match Recorder::create() {
+				Ok(recorder) => self.recorder = Some(recorder),
+				Err(err) => error!("Could not start recording: {}", err),
+			}
+		}
+	}
+
+	/// Writes `event` to the recording, if recording is enabled
+	fn record(&mut self, event: Event) {
+		if let Some(recorder) = &mut self.recorder {
+			if let Err(err) = recorder.record(event, self.desktop.as_ref()) {
+				error!("Stopped recording: {}", err);
+				self.recorder = None;
+			}
+		}
+	}
+
+	/// Handles `event` and returns the commands that have to be carried out because of it
+	///
+	/// Windows are only read through the desktop and only changed through the commands, which
+	/// keeps the handling replayable.
+	fn update(&mut self, event: Event) -> Vec<Command> {
+		// Titles change between events, so do the rules that match
+		self.matched_rules.get_mut().clear();
+
+		self.handle_event(event);
+
+		mem::take(&mut self.commands)
+	}
+
+	/// Carries out `command`
+	fn execute(&mut self, command: Command) {
+		match command {
+			Command::OpenGridWindow(rect) => {
+				if let Some(stop) = signal(&mut self.close_signal) {
+					spawn_grid_window(stop, rect, self.config.colors.background)
+				}
+			}
+			Command::OpenPreviewWindow => {
+				if let Some(stop) = signal(&mut self.close_signal) {
+					spawn_preview_window(
+						stop,
+						self.config.colors.preview,
+						self.config.preview.opacity,
+					)
+				}
+			}
+			Command::WatchGridWindow => {
+				if let Some(stop) = signal(&mut self.close_signal) {
+					spawn_track_monitor_thread(stop)
+				}
+			}
+			Command::ShowGridWindow(grid_window) => unsafe {
+				if let Some(stop) = signal(&mut self.close_signal) {
+					spawn_foreground_hook(stop);
+				}
+
+				ShowWindow(grid_window.0, SW_SHOW);
+				SetForegroundWindow(grid_window.0);
+			},
+			Command::CloseWindows => raise(&mut self.close_signal),
+			Command::StartDrag => {
+				if let Some(stop) = signal(&mut self.drag_signal) {
+					spawn_drag_tracker(
+						stop.clone(),
+						self.config.drag.modifier,
+						self.config.drag.span_modifier,
+					);
+					spawn_preview_window(
+						stop,
+						self.config.colors.preview,
+						self.config.preview.opacity,
+					);
+				}
+			}
+			Command::EndDrag => raise(&mut self.drag_signal),
+			Command::SetPos {
+				mut window,
+				rect,
+				insert_after,
+			} => window.set_pos(rect, insert_after),
+			Command::SetOpacity(window, opacity) => {
+				window.make_layered();
+				window.set_opacity(opacity);
+			}
+			Command::SetColorKey(window, opacity) => {
+				window.set_color_key(OVERLAY_COLOR_KEY, opacity)
+			}
+			Command::Redraw(window) => window.redraw(),
+			Command::Paint(window) => unsafe {
+				if Some(window) == self.hint_window {
+					if let Some(hints) = &self.hints {
+						hints.draw(window, &self.config.colors);
+					}
+				} else if Some(window) == self.grid_window {
+					self.grid.draw(window);
+				} else if self.grid.shows_overlay() {
+					self.grid.draw_overlay(window);
+				} else {
+					self.grid.draw_preview(window);
+				}
+			},
+			Command::TrackMouse(window) => unsafe {
+				let mut event_track: TRACKMOUSEEVENT = mem::zeroed();
+				event_track.cbSize = mem::size_of::<TRACKMOUSEEVENT>() as u32;
+				event_track.dwFlags = TME_LEAVE;
+				event_track.hwndTrack = window.0;
+
+				TrackMouseEvent(&mut event_track);
+			},
+			Command::MoveWindow(mut window, rect) => window.set_pos(rect, None),
+			Command::CheckBorder(window) => {
+				let _ = self.channel.sender.send(Event::CheckBorder(window));
+			}
+			Command::Restore(mut window) => window.restore(),
+			Command::Maximize(mut window) => window.maximize(),
+			Command::Minimize(mut window) => window.minimize(),
+			Command::Show(window) => window.show(),
+			Command::Hide(window) => window.hide(),
+			Command::OpenHintWindow(rect) => {
+				if let Some(stop) = signal(&mut self.hint_signal) {
+					spawn_hint_window(stop, rect)
+				}
+			}
+			Command::CloseHintWindow => raise(&mut self.hint_signal),
+			Command::Focus(window) => window.focus(),
+			Command::Notify(notification) => {
+				for subscriber in &self.subscribers {
+					let _ = subscriber.send(notification.clone());
+				}
+			}
+		}
+	}
+
+	/// Registers the hotkeys of the config, none of them are registered if one of them fails
+	fn setup_hotkeys(&mut self) -> io::Result<()> {
+		let mut hotkeys = GlobalHotkeySet::new()
 			.add_global_hotkey(
 				Event::HotkeyPressed(HotkeyType::QuickResize),
-				Modifier::Ctrl + Modifier::Alt + Key::Q,
+				self.config.hotkeys.quick_resize,
 			)
 			.add_global_hotkey(
 				Event::HotkeyPressed(HotkeyType::Main),
-				Modifier::Ctrl + Modifier::Alt + Key::S,
+				self.config.hotkeys.main,
+			);
+
+		let optional = [
+			(HotkeyType::NextProfile, self.config.hotkeys.next_profile),
+			(HotkeyType::Tile, self.config.hotkeys.tile),
+			(HotkeyType::NextLayout, self.config.hotkeys.next_layout),
+			(HotkeyType::GrowMaster, self.config.hotkeys.grow_master),
+			(HotkeyType::ShrinkMaster, self.config.hotkeys.shrink_master),
+			(HotkeyType::AddMaster, self.config.hotkeys.add_master),
+			(HotkeyType::RemoveMaster, self.config.hotkeys.remove_master),
+			(HotkeyType::ApplyRules, self.config.hotkeys.apply_rules),
+			(HotkeyType::ExplainRules, self.config.hotkeys.explain_rules),
+			(HotkeyType::Undo, self.config.hotkeys.undo),
+			(HotkeyType::Redo, self.config.hotkeys.redo),
+			(
+				HotkeyType::RestoreWindow,
+				self.config.hotkeys.restore_window,
+			),
+			(HotkeyType::RestoreAll, self.config.hotkeys.restore_all),
+			(
+				HotkeyType::SendToMonitor(MonitorTarget::Next),
+				self.config.hotkeys.next_monitor,
+			),
+			(
+				HotkeyType::SendToMonitor(MonitorTarget::Previous),
+				self.config.hotkeys.previous_monitor,
+			),
+			(
+				HotkeyType::SendToMonitor(MonitorTarget::Direction(Direction::Left)),
+				self.config.hotkeys.monitor_left,
+			),
+			(
+				HotkeyType::SendToMonitor(MonitorTarget::Direction(Direction::Right)),
+				self.config.hotkeys.monitor_right,
+			),
+			(
+				HotkeyType::SendToMonitor(MonitorTarget::Direction(Direction::Up)),
+				self.config.hotkeys.monitor_up,
+			),
+			(
+				HotkeyType::SendToMonitor(MonitorTarget::Direction(Direction::Down)),
+				self.config.hotkeys.monitor_down,
+			),
+			(
+				HotkeyType::Focus(Direction::Left),
+				self.config.hotkeys.focus_left,
+			),
+			(
+				HotkeyType::Focus(Direction::Right),
+				self.config.hotkeys.focus_right,
+			),
+			(
+				HotkeyType::Focus(Direction::Up),
+				self.config.hotkeys.focus_up,
+			),
+			(
+				HotkeyType::Focus(Direction::Down),
+				self.config.hotkeys.focus_down,
+			),
+			(
+				HotkeyType::Swap(Direction::Left),
+				self.config.hotkeys.swap_left,
+			),
+			(
+				HotkeyType::Swap(Direction::Right),
+				self.config.hotkeys.swap_right,
+			),
+			(HotkeyType::Swap(Direction::Up), self.config.hotkeys.swap_up),
+			(
+				HotkeyType::Swap(Direction::Down),
+				self.config.hotkeys.swap_down,
+			),
+			(
+				HotkeyType::Nudge(Adjustment::Move(Direction::Left)),
+				self.config.hotkeys.move_left,
+			),
+			(
+				HotkeyType::Nudge(Adjustment::Move(Direction::Right)),
+				self.config.hotkeys.move_right,
+			),
+			(
+				HotkeyType::Nudge(Adjustment::Move(Direction::Up)),
+				self.config.hotkeys.move_up,
+			),
+			(
+				HotkeyType::Nudge(Adjustment::Move(Direction::Down)),
+				self.config.hotkeys.move_down,
+			),
+			(
+				HotkeyType::Nudge(Adjustment::Grow(Direction::Left)),
+				self.config.hotkeys.grow_left,
+			),
+			(
+				HotkeyType::Nudge(Adjustment::Grow(Direction::Right)),
+				self.config.hotkeys.grow_right,
+			),
+			(
+				HotkeyType::Nudge(Adjustment::Grow(Direction::Up)),
+				self.config.hotkeys.grow_up,
+			),
+			(
+				HotkeyType::Nudge(Adjustment::Grow(Direction::Down)),
+				self.config.hotkeys.grow_down,
+			),
+			(
+				HotkeyType::Nudge(Adjustment::Shrink(Direction::Left)),
+				self.config.hotkeys.shrink_left,
+			),
+			(
+				HotkeyType::Nudge(Adjustment::Shrink(Direction::Right)),
+				self.config.hotkeys.shrink_right,
+			),
+			(
+				HotkeyType::Nudge(Adjustment::Shrink(Direction::Up)),
+				self.config.hotkeys.shrink_up,
+			),
+			(
+				HotkeyType::Nudge(Adjustment::Shrink(Direction::Down)),
+				self.config.hotkeys.shrink_down,
+			),
+			(HotkeyType::Center, self.config.hotkeys.center),
+			(
+				HotkeyType::Hint(HintAction::Focus),
+				self.config.hotkeys.hint_focus,
+			),
+			(
+				HotkeyType::Hint(HintAction::Swap),
+				self.config.hotkeys.hint_swap,
+			),
+			(
+				HotkeyType::Hint(HintAction::Zone),
+				self.config.hotkeys.hint_zone,
+			),
+		];
+
+		for (hotkey_type, hotkey) in optional.iter() {
+			if let Some(hotkey) = hotkey {
+				hotkeys = hotkeys.add_global_hotkey(Event::HotkeyPressed(*hotkey_type), *hotkey);
+			}
+		}
+
+		for (i, snap) in self.config.snap.iter().enumerate() {
+			hotkeys =
+				hotkeys.add_global_hotkey(Event::HotkeyPressed(HotkeyType::Snap(i)), snap.hotkey);
+		}
+
+		for (i, hotkey) in self.config.hotkeys.send_to_monitor.iter().enumerate() {
+			hotkeys = hotkeys.add_global_hotkey(
+				Event::HotkeyPressed(HotkeyType::SendToMonitor(MonitorTarget::Number(i + 1))),
+				*hotkey,
+			);
+		}
+
+		for (i, hotkey) in self.config.workspaces.switch.iter().enumerate() {
+			hotkeys =
+				hotkeys.add_global_hotkey(Event::HotkeyPressed(HotkeyType::Workspace(i)), *hotkey);
+		}
+
+		for (i, hotkey) in self.config.workspaces.move_window.iter().enumerate() {
+			hotkeys = hotkeys.add_global_hotkey(
+				Event::HotkeyPressed(HotkeyType::MoveToWorkspace(i)),
+				*hotkey,
 			);
+		}
 
-		self.channel.listen_for_hotkeys(hotkeys);
+		for (i, arrangement) in self.config.arrangements.iter().enumerate() {
+			let hotkey_types = [
+				HotkeyType::SaveArrangement(i),
+				HotkeyType::RestoreArrangement(i),
+				HotkeyType::DryRunArrangement(i),
+			];
+
+			for (hotkey_type, (_, hotkey)) in hotkey_types.iter().zip(arrangement.hotkeys().iter())
+			{
+				if let Some(hotkey) = hotkey {
+					hotkeys =
+						hotkeys.add_global_hotkey(Event::HotkeyPressed(*hotkey_type), *hotkey);
+				}
+			}
+		}
+
+		self.hotkeys = Some(self.channel.listen_for_hotkeys(hotkeys)?);
+
+		Ok(())
 	}
 
-	fn handle_event(&'static self, msg: Event) {
-		let tm = unsafe { INSTANCE.get_mut().unwrap() };
+	fn watch_config(&mut self) {
+		if let Some(path) = WtmConfig::path() {
+			let result = fs::create_dir_all(path.parent().unwrap())
+				.and_then(|_| self.channel.watch_file(path, Event::ConfigChanged));
 
-		match msg {
-			Event::PreviewWindow(window) => unsafe {
-				tm.preview_window = Some(window);
+			if let Err(err) = result {
+				error!("Failed to watch the config file: {}", err);
+			}
+		}
+	}
 
-				spawn_foreground_hook(self.close_channel.receiver.clone());
+	fn reload_config(&mut self) {
+		let config = match WtmConfig::load() {
+			Ok(config) => config,
+			Err(err) => {
+				error!("Keeping the previous config: {}", err);
+				return;
+			}
+		};
 
-				ShowWindow(self.grid_window.as_ref().unwrap().0, SW_SHOW);
-				SetForegroundWindow(self.grid_window.as_ref().unwrap().0);
-			},
-			Event::GridWindow(window) => {
-				tm.grid_window = Some(window);
+		if config == self.config {
+			return;
+		}
 
-				let mut grid = unsafe { &mut INSTANCE.get_mut().unwrap().grid };
+		info!("Applying new config");
 
-				grid.grid_window = Some(window);
-				grid.active_window = Some(Window::get_foreground_window());
+		let previous = mem::replace(&mut self.config, config);
+		self.matched_rules.get_mut().clear();
 
-				spawn_track_monitor_thread(self.close_channel.receiver.clone());
-				spawn_preview_window(self.close_channel.receiver.clone());
+		if previous.hotkeys != self.config.hotkeys
+			|| previous.snap != self.config.snap
+			|| previous.workspaces != self.config.workspaces
+			|| previous.arrangements != self.config.arrangements
+		{
+			// Hotkeys that are in both configs can't be registered twice, so the previous ones are
+			// unregistered first and registered again if the new ones fail
+			if let Some(hotkeys) = self.hotkeys.take() {
+				hotkeys.unregister();
 			}
-			Event::HighlightZone(rect) => {
-				let mut preview_window = self.preview_window.unwrap_or_default();
-				let grid_window = self.grid_window.unwrap_or_default();
 
-				preview_window.set_pos(rect, Some(grid_window));
+			if let Err(err) = self.setup_hotkeys() {
+				error!(
+					"Keeping the previous config, its hotkeys failed to register: {}",
+					err
+				);
+
+				self.config = previous;
+				self.matched_rules.get_mut().clear();
+
+				if let Err(err) = self.setup_hotkeys() {
+					error!("Failed to register the previous hotkeys again: {}", err);
+				}
+
+				return;
 			}
-			Event::HotkeyPressed(hotkey_type) => self.handle_hotkey(hotkey_type),
-			Event::TrackMouse(window) => unsafe {
-				if !self.track_mouse {
-					let mut event_track: TRACKMOUSEEVENT = mem::zeroed();
-					event_track.cbSize = mem::size_of::<TRACKMOUSEEVENT>() as u32;
-					event_track.dwFlags = TME_LEAVE;
-					event_track.hwndTrack = window.0;
+		}
+
+		if previous.tiling != self.config.tiling {
+			self.tilings.clear();
+		}
+
+		if previous.recording != self.config.recording {
+			self.update_recording();
+		}
+
+		if previous.workspaces.count != self.config.workspaces.count {
+			// Brings back the windows of the workspaces that are about to disappear
+			for workspaces in self.workspaces.values() {
+				self.commands
+					.extend(workspaces.hidden().into_iter().map(Command::Show));
+			}
+
+			self.workspaces.clear();
+			self.tilings.clear();
+			self.notify_workspace(&self.desktop.active_monitor().name);
+		}
+
+		// Placement, animation and frame rules are read by the grid as well
+		self.grid.apply_config(&self.config);
+
+		if previous.grid != self.config.grid
+			|| previous.profiles != self.config.profiles
+			|| previous.tile != self.config.tile
+			|| previous.colors != self.config.colors
+			|| previous.zone_sets != self.config.zone_sets
+		{
+			let profile_count = self.config.profiles().len();
+			self.active_profiles
+				.retain(|_, index| *index < profile_count);
+
+			self.apply_monitor_profile(&self.desktop.active_monitor().name);
+			self.reposition_grid();
+		}
+
+		if let Some(preview_window) = self.preview_window {
+			if previous.colors.preview != self.config.colors.preview {
+				self.commands.push(Command::Redraw(preview_window));
+			}
+
+			if previous.preview != self.config.preview
+				|| previous.zone_sets != self.config.zone_sets
+			{
+				self.show_zone_overlay();
+			}
+		}
+	}
+
+	fn notify(&mut self, notification: Notification) {
+		self.commands.push(Command::Notify(notification));
+	}
+
+	fn notify_profile(&mut self, monitor: &str) {
+		let profile = &self.config.profiles()[self.profile_index(monitor)];
+
+		self.notify(Notification::ProfileChanged {
+			monitor: monitor.to_owned(),
+			profile: profile.name.clone(),
+		});
+	}
+
+	fn notify_workspace(&mut self, monitor: &str) {
+		let workspace = self
+			.workspaces
+			.get(monitor)
+			.map(|workspaces| workspaces.active())
+			.unwrap_or(0);
+
+		self.notify(Notification::WorkspaceChanged {
+			monitor: monitor.to_owned(),
+			workspace: workspace + 1,
+		});
+	}
+
+	/// Index of the profile that is active on `monitor`
+	fn profile_index(&self, monitor: &str) -> usize {
+		self.active_profiles.get(monitor).copied().unwrap_or(0)
+	}
+
+	/// Applies the profile of `monitor` to the grid
+	fn apply_monitor_profile(&mut self, monitor: &str) {
+		let index = self.profile_index(monitor);
+
+		self.grid.apply_profile(&self.config.profiles()[index]);
+	}
+
+	/// Activates the profile at `index` on the active monitor
+	fn change_profile(&mut self, index: usize) {
+		if index >= self.config.profiles().len() {
+			warn!("There is no profile number {}", index + 1);
+			return;
+		}
+
+		let monitor = self.desktop.active_monitor().name;
+		self.active_profiles.insert(monitor.clone(), index);
+
+		self.apply_monitor_profile(&monitor);
+		self.reposition_grid();
+		self.notify_profile(&monitor);
+	}
+
+	/// Opens the grid window on the active monitor
+	fn open_grid(&mut self) {
+		if self.grid.drag_window.is_some() {
+			return;
+		}
+
+		let monitor = self.desktop.active_monitor();
+		self.grid.work_area = monitor.work_area;
+		self.apply_monitor_profile(&monitor.name);
+
+		self.commands
+			.push(Command::OpenGridWindow(self.grid.window_rect()));
+	}
+
+	/// Closes the grid and preview windows
+	fn close_windows(&mut self) {
+		self.preview_window.take();
+		self.grid_window.take();
+
+		self.commands.push(Command::CloseWindows);
+
+		self.grid.reset();
+		self.track_mouse = false;
+	}
+
+	/// Moves the grid window to the center of the work area, it has to be resized after the grid
+	/// changed
+	fn reposition_grid(&mut self) {
+		if let Some(grid_window) = self.grid_window {
+			self.commands.push(Command::SetPos {
+				window: grid_window,
+				rect: self.grid.window_rect(),
+				insert_after: None,
+			});
+			self.commands.push(Command::Redraw(grid_window));
+		}
+	}
+
+	/// Paints the grid window again
+	fn redraw_grid(&mut self) {
+		if let Some(grid_window) = self.grid_window {
+			self.commands.push(Command::Redraw(grid_window));
+		}
+	}
+
+	/// Shows `rect` with the preview window, or the highlighted zone if a zone set is shown
+	fn highlight_zone(&mut self, rect: Rect) {
+		let preview_window = match self.preview_window {
+			Some(preview_window) => preview_window,
+			None => return,
+		};
+
+		// The zone set overlay stays in place and only redraws the highlighted zone
+		if self.grid.zone_set.is_some() {
+			self.commands.push(Command::Redraw(preview_window));
+		} else {
+			self.commands.push(Command::SetPos {
+				window: preview_window,
+				rect,
+				insert_after: self.grid_window,
+			});
+		}
+	}
+
+	/// Moves the active window into the selected area of the grid
+	///
+	/// Returns false if there was nothing to move.
+	fn resize_active_window(&mut self) -> bool {
+		match self.grid.active_window_placement() {
+			Some((window, rect)) if !self.ignores(window) => {
+				self.place_window(window, rect);
+				true
+			}
+			_ => false,
+		}
+	}
+
+	/// Covers the active monitor with the preview window while a zone set is shown, and hides it
+	/// otherwise
+	fn show_zone_overlay(&mut self) {
+		let (preview_window, grid_window) = match (self.preview_window, self.grid_window) {
+			(Some(preview_window), Some(grid_window)) => (preview_window, grid_window),
+			_ => return,
+		};
+		let opacity = self.config.preview.opacity;
+
+		let rect = if self.grid.zone_set.is_some() {
+			self.commands
+				.push(Command::SetColorKey(preview_window, opacity));
+			self.grid.work_area
+		} else {
+			self.commands
+				.push(Command::SetOpacity(preview_window, opacity));
+			Rect::zero()
+		};
 
-					TrackMouseEvent(&mut event_track);
+		self.commands.push(Command::SetPos {
+			window: preview_window,
+			rect,
+			insert_after: Some(grid_window),
+		});
+		self.commands.push(Command::Redraw(preview_window));
+	}
 
-					tm.track_mouse = true;
+	fn handle_event(&mut self, msg: Event) {
+		match msg {
+			Event::PreviewWindow(window) => match self.grid_window {
+				Some(grid_window) => {
+					self.preview_window = Some(window);
+					self.commands.push(Command::ShowGridWindow(grid_window));
 				}
+				// Without the grid window the preview is the overlay of a dragged window
+				None if self.grid.drag_window.is_some() => self.preview_window = Some(window),
+				None => {}
 			},
-			Event::MouseLeft => tm.track_mouse = false,
+			Event::GridWindow(window) => {
+				self.grid_window = Some(window);
+
+				self.grid.grid_window = Some(window);
+				self.grid.active_window = Some(self.desktop.foreground_window());
+
+				self.commands.push(Command::WatchGridWindow);
+				self.commands.push(Command::OpenPreviewWindow);
+			}
+			Event::HotkeyPressed(hotkey_type) => self.handle_hotkey(hotkey_type),
+			Event::TrackMouse(window) => {
+				if !self.track_mouse {
+					self.commands.push(Command::TrackMouse(window));
+					self.track_mouse = true;
+				}
+			}
+			Event::MouseLeft => {
+				self.track_mouse = false;
+
+				self.grid.unhighlight_all_tiles();
+				self.highlight_zone(Rect::zero());
+				self.redraw_grid();
+			}
+			Event::GridKeyDown(key) => self.grid_key_down(key),
+			Event::GridKeyUp(key) => self.grid_key_up(key),
+			Event::GridMouseMove(point) => self.grid_mouse_move(point),
+			Event::GridMouseDown(point) => self.grid_mouse_down(point),
+			Event::GridMouseUp => self.grid_mouse_up(),
+			Event::HintWindow(window) => {
+				// Hint mode may have ended before the window was created
+				if self.hints.is_some() {
+					self.hint_window = Some(window);
+					self.commands.push(Command::Focus(window));
+				}
+			}
+			Event::HintKeyDown(key) => self.hint_key_down(key),
+			// Clicking somewhere else ends hint mode
+			Event::HintWindowDeactivated(window) => {
+				if self.hint_window == Some(window) {
+					self.close_hints();
+				}
+			}
+			Event::Paint(window) => {
+				if Some(window) == self.grid_window
+					|| Some(window) == self.preview_window
+					|| Some(window) == self.hint_window
+				{
+					self.commands.push(Command::Paint(window));
+				}
+			}
+			Event::AnimationFrame => self.draw_animation_frame(),
+			Event::CheckBorder(window) => self.check_border(window),
 			Event::ActiveWindowChange(window) => {
 				if self.grid.grid_window != Some(window) && self.grid.active_window != Some(window)
 				{
-					tm.grid.active_window = Some(window);
+					self.grid.active_window = Some(window);
 				}
 			}
+			Event::CheckDisplays => self.check_displays(),
 			Event::MonitorChange => {
-				tm.grid.grid_window = self.grid_window;
-				tm.grid.reposition();
+				let monitor = self.desktop.active_monitor();
+
+				self.grid.work_area = monitor.work_area;
+				self.apply_monitor_profile(&monitor.name);
+
+				self.grid.grid_window = self.grid_window;
+				self.reposition_grid();
+
+				self.show_zone_overlay();
+				self.notify_profile(&monitor.name);
+			}
+			Event::ConfigChanged => self.reload_config(),
+			Event::MoveSizeStart(window) => {
+				if self.config.drag.enabled && self.grid_window.is_none() && !self.ignores(window) {
+					self.grid.start_drag(window);
+					self.grid.work_area = self.desktop.active_monitor().work_area;
+
+					self.commands.push(Command::StartDrag);
+				}
+			}
+			Event::Drag {
+				point,
+				snap,
+				extend,
+			} => self.drag(point, snap, extend),
+			Event::MoveSizeEnd(window) => {
+				if self.grid.drag_window == Some(window) {
+					if let Some(area) = self.grid.end_drag() {
+						self.place_window(window, area);
+					}
+
+					self.preview_window.take();
+					self.commands.push(Command::EndDrag);
+				}
 			}
-			Event::ProfileChange(_) => todo!(),
-			Event::InitializeWindows => spawn_grid_window(self.close_channel.receiver.clone()),
-			Event::CloseWindows => {
-				tm.preview_window.take();
-				tm.grid_window.take();
+			// Windows may only get the title their rule matches after they were shown
+			Event::WindowShown(window) | Event::WindowRenamed(window) => {
+				if self.desktop.is_managed(window) && !self.ruled_windows.contains(&window) {
+					let desktop = &self.desktop;
+					self.ruled_windows.retain(|window| desktop.exists(*window));
 
-				for _ in 0..4 {
-					let _ = self.close_channel.sender.send(());
+					if self.apply_rules(window) {
+						self.ruled_windows.insert(window);
+					}
 				}
+			}
+			// Keeps what is remembered about each window from growing with every window ever seen
+			Event::WindowDestroyed(window) => {
+				self.grid.history.forget(window);
+				self.grid.floating.remove(&window);
+				self.snap_cycles.remove(&window);
+				self.ruled_windows.remove(&window);
+				self.border_checks.remove(&window);
+			}
+			Event::WindowFocused(window) => {
+				let desktop = &self.desktop;
+				self.focus_history
+					.retain(|other| *other != window && desktop.exists(*other));
+				self.focus_history.insert(0, window);
 
-				tm.grid.reset();
-				tm.track_mouse = false;
+				for workspaces in self.workspaces.values_mut() {
+					if workspaces.focus(window) {
+						break;
+					}
+				}
 			}
 		}
 	}
 
-	fn handle_hotkey(&'static self, hotkey: HotkeyType) {
+	/// Shows the zones under the cursor while a window is dragged with the drag modifier held
+	fn drag(&mut self, point: (i32, i32), snap: bool, extend: bool) {
+		if self.grid.drag_window.is_none() {
+			return;
+		}
+
+		self.grid.work_area = self.desktop.active_monitor().work_area;
+
+		if snap {
+			self.grid.drag_to(point, extend);
+		} else {
+			self.grid.cancel_drag_snap();
+		}
+
+		if let Some(preview_window) = self.preview_window {
+			let rect = if snap {
+				self.commands.push(Command::SetColorKey(
+					preview_window,
+					self.config.preview.opacity,
+				));
+				self.grid.work_area
+			} else {
+				Rect::zero()
+			};
+
+			self.commands.push(Command::SetPos {
+				window: preview_window,
+				rect,
+				insert_after: None,
+			});
+			self.commands.push(Command::Redraw(preview_window));
+		}
+	}
+
+	fn handle_hotkey(&mut self, hotkey: HotkeyType) {
 		match hotkey {
 			HotkeyType::Main => {
 				if self.preview_window.is_some() && self.grid_window.is_some() {
-					let _ = self.channel.sender.send(Event::CloseWindows);
+					self.close_windows();
 				} else {
-					let _ = self.channel.sender.send(Event::InitializeWindows);
+					self.open_grid();
 				}
 			}
 			HotkeyType::QuickResize => {
-				let _ = self.channel.sender.send(Event::InitializeWindows);
-				unsafe { INSTANCE.get_mut().unwrap().grid.quick_resize = true };
+				self.open_grid();
+				self.grid.quick_resize = true;
+			}
+			HotkeyType::NextProfile => {
+				let monitor = self.desktop.active_monitor().name;
+				let index = (self.profile_index(&monitor) + 1) % self.config.profiles().len();
+
+				self.change_profile(index);
+			}
+			HotkeyType::Snap(index) => self.snap(index),
+			HotkeyType::Tile => self.tile(|_| {}),
+			HotkeyType::NextLayout => {
+				let config = self.config.tiling.clone();
+				self.tile(|tiling| tiling.next_layout(&config))
+			}
+			HotkeyType::GrowMaster => {
+				let step = self.config.tiling.master_ratio_step;
+				self.tile(|tiling| tiling.change_master_ratio(step))
+			}
+			HotkeyType::ShrinkMaster => {
+				let step = self.config.tiling.master_ratio_step;
+				self.tile(|tiling| tiling.change_master_ratio(-step))
+			}
+			HotkeyType::AddMaster => self.tile(|tiling| tiling.change_master_count(true)),
+			HotkeyType::RemoveMaster => self.tile(|tiling| tiling.change_master_count(false)),
+			HotkeyType::Workspace(index) => self.switch_workspace(index),
+			HotkeyType::MoveToWorkspace(index) => self.move_to_workspace(index),
+			HotkeyType::ApplyRules => {
+				for window in self.managed_windows() {
+					self.apply_rules(window);
+				}
+			}
+			HotkeyType::ExplainRules => {
+				let window = self.desktop.foreground_window();
+				let explanation =
+					explain(&self.config.rules, self.desktop.window_properties(window));
+
+				info!("Rules for the foreground window:\n{}", explanation);
+			}
+			HotkeyType::Undo => self.undo(),
+			HotkeyType::Redo => self.redo(),
+			HotkeyType::RestoreWindow => self.restore_window(self.desktop.foreground_window()),
+			HotkeyType::RestoreAll => self.restore_all(),
+			HotkeyType::SendToMonitor(target) => self.send_to_monitor(target),
+			HotkeyType::Focus(direction) => {
+				if let Some((_, window)) = self.neighbor(direction) {
+					self.commands.push(Command::Focus(window));
+				}
+			}
+			HotkeyType::Swap(direction) => self.swap(direction),
+			HotkeyType::Nudge(adjustment) => self.nudge(adjustment),
+			HotkeyType::Center => {
+				let window = self.desktop.foreground_window();
+
+				if !self.ignores(window) {
+					let area = self.desktop.monitor_of(window).work_area;
+
+					let rect = center(self.visible_rect(window), area);
+
+					self.place_window(window, rect);
+				}
+			}
+			HotkeyType::SaveArrangement(index) => self.save_arrangement(index),
+			// The hotkey may have been pressed before a reload removed the arrangement
+			HotkeyType::RestoreArrangement(index) => {
+				if let Some(arrangement) = self.config.arrangements.get(index) {
+					let name = arrangement.name.clone();
+					self.restore_arrangement(&name, false)
+				}
+			}
+			HotkeyType::DryRunArrangement(index) => {
+				if let Some(arrangement) = self.config.arrangements.get(index) {
+					let name = arrangement.name.clone();
+					self.restore_arrangement(&name, true)
+				}
+			}
+			HotkeyType::Hint(action) => self.show_hints(action),
+		}
+	}
+
+	/// Saves where the windows are as the arrangement at `index`
+	fn save_arrangement(&self, index: usize) {
+		let name = match self.config.arrangements.get(index) {
+			Some(arrangement) => &arrangement.name,
+			None => return,
+		};
+		let monitors = self.desktop.monitors();
+
+		let windows = self
+			.managed_windows()
+			.into_iter()
+			.filter(|window| !self.ignores(*window))
+			.filter_map(|window| {
+				let monitor = self.desktop.monitor_of(window);
+				let number = monitors
+					.iter()
+					.position(|other| other.name == monitor.name)?
+					+ 1;
+
+				Some(Entry::new(
+					self.desktop.window_properties(window),
+					number,
+					monitor.work_area,
+					self.desktop.window_rect(window),
+					self.desktop.window_state(window),
+				))
+			})
+			.collect();
+
+		match (Arrangement { windows }).save(name) {
+			Ok(()) => {
+				info!("Saved arrangement `{}`", name);
+				self.remember_arrangement(name);
+			}
+			Err(err) => error!("Could not save arrangement `{}`: {}", name, err),
+		}
+	}
+
+	/// Remembers that the arrangement `name` belongs to the current monitor setup
+	fn remember_arrangement(&self, name: &str) {
+		if let Err(err) = Setups::remember(&fingerprint(&self.desktop.monitors()), name) {
+			error!(
+				"Could not remember the arrangement of the monitor setup: {}",
+				err
+			);
+		}
+	}
+
+	/// Moves the windows to where they were in the arrangement `name`
+	///
+	/// Only logs what would happen if `dry_run` is true.
+	fn restore_arrangement(&mut self, name: &str, dry_run: bool) {
+		let arrangement = match Arrangement::load(name) {
+			Ok(arrangement) => arrangement,
+			Err(err) => {
+				error!("Could not load arrangement `{}`: {}", name, err);
+				return;
 			}
+		};
+
+		let windows: Vec<_> = self
+			.managed_windows()
+			.into_iter()
+			.filter(|window| !self.ignores(*window))
+			.collect();
+		let properties: Vec<_> = windows
+			.iter()
+			.map(|window| self.desktop.window_properties(*window))
+			.collect();
+		let areas: Vec<_> = self
+			.desktop
+			.monitors()
+			.iter()
+			.map(|monitor| monitor.work_area)
+			.collect();
+
+		let plan = arrangement.plan(&properties, &areas);
+
+		if dry_run {
+			info!("Dry run of arrangement `{}`: {}", name, plan);
+			return;
 		}
+
+		for (_, step) in &plan.steps {
+			if let Step::Move {
+				window,
+				rect,
+				state,
+				..
+			} = *step
+			{
+				let window = windows[window];
+
+				self.commands.push(Command::Restore(window));
+
+				if let Some(rect) = rect {
+					self.move_window(window, rect, rect);
+				}
+
+				match state {
+					WindowState::Normal => {}
+					WindowState::Maximized => self.commands.push(Command::Maximize(window)),
+					WindowState::Minimized => self.commands.push(Command::Minimize(window)),
+				}
+			}
+		}
+
+		info!("Restored arrangement `{}`: {}", name, plan);
+		self.remember_arrangement(name);
+	}
+
+	/// Moves the animated windows to where they have to be right now
+	fn draw_animation_frame(&mut self) {
+		for (window, rect) in self.grid.animator.frame() {
+			if self.desktop.exists(window) {
+				self.commands.push(Command::SetPos {
+					window,
+					rect,
+					insert_after: None,
+				});
+			}
+		}
+	}
+
+	/// Keeps the windows in place when monitors are added, removed or change their resolution
+	///
+	/// Afterwards the arrangement that was used last with the new monitor setup is restored.
+	fn check_displays(&mut self) {
+		let desktop = self.desktop.as_ref();
+		let change = match self
+			.displays
+			.check(desktop.monitors(), || Self::display_windows(desktop))
+		{
+			Some(change) => change,
+			None => return,
+		};
+
+		let fingerprint = fingerprint(&change.current);
+		info!("The monitor setup changed to {}", fingerprint);
+
+		for (window, rect) in rescue(&change) {
+			if self.desktop.exists(window) && !self.ignores(window) {
+				self.move_window(window, rect, rect);
+			}
+		}
+
+		match Setups::load() {
+			Ok(setups) => {
+				if let Some(name) = setups.arrangements.get(&fingerprint) {
+					self.restore_arrangement(name, false);
+				}
+			}
+			Err(err) => error!(
+				"Could not load the arrangements of the monitor setups: {}",
+				err
+			),
+		}
+	}
+
+	/// Where the windows are that are kept in place when the monitor setup changes
+	///
+	/// Maximized and minimized windows are left to Windows. Rules aren't checked here, because
+	/// this runs after every window that is moved by hand.
+	fn display_windows(desktop: &dyn Desktop) -> Vec<(Window, Rect)> {
+		desktop
+			.windows()
+			.into_iter()
+			.filter(|window| {
+				desktop.is_managed(*window) && desktop.window_state(*window) == WindowState::Normal
+			})
+			.map(|window| (window, desktop.window_rect(window)))
+			.collect()
+	}
+
+	/// Moves the window that was moved last back to where it was before
+	fn undo(&mut self) {
+		let desktop = &self.desktop;
+
+		if let Some(placement) = self.grid.history.undo(|window| desktop.exists(window)) {
+			self.return_window(placement.window, placement.from);
+		}
+	}
+
+	/// Moves the window whose move was undone last again
+	fn redo(&mut self) {
+		let desktop = &self.desktop;
+
+		if let Some(placement) = self.grid.history.redo(|window| desktop.exists(window)) {
+			self.return_window(placement.window, placement.to);
+		}
+	}
+
+	/// The foreground window and the window next to it in `direction`, on any monitor
+	fn neighbor(&self, direction: Direction) -> Option<(Window, Window)> {
+		let foreground = self.desktop.foreground_window();
+
+		let windows: Vec<_> = self
+			.managed_windows()
+			.into_iter()
+			.filter(|window| !self.is_minimized(*window) && !self.ignores(*window))
+			.collect();
+		let current = windows.iter().position(|window| *window == foreground)?;
+
+		let rects: Vec<_> = windows
+			.iter()
+			.map(|window| self.desktop.window_rect(*window))
+			.collect();
+		let recency: Vec<_> = windows
+			.iter()
+			.map(|window| {
+				self.focus_history
+					.iter()
+					.position(|other| other == window)
+					.unwrap_or(usize::MAX)
+			})
+			.collect();
+
+		neighbor(&rects, current, direction, &recency).map(|index| (foreground, windows[index]))
+	}
+
+	/// Swaps the places of the foreground window and the window next to it in `direction`
+	fn swap(&mut self, direction: Direction) {
+		if let Some((window, other)) = self.neighbor(direction) {
+			self.swap_windows(window, other);
+		}
+	}
+
+	/// Swaps the places of `window` and `other`
+	///
+	/// Tiled windows also swap their places in the tiling.
+	fn swap_windows(&mut self, window: Window, other: Window) {
+		for tiling in self.tilings.values_mut() {
+			let first = tiling.windows.iter().position(|tiled| *tiled == window);
+			let second = tiling.windows.iter().position(|tiled| *tiled == other);
+
+			if let (Some(first), Some(second)) = (first, second) {
+				tiling.windows.swap(first, second);
+			}
+		}
+
+		self.commands.push(Command::Restore(window));
+		self.commands.push(Command::Restore(other));
+
+		let rect = self.desktop.window_rect(window);
+		let other_rect = self.desktop.window_rect(other);
+
+		// Windows in a zone move into the other's zone, not just its rectangle
+		let area = self.grid.history.area_of(window, rect).unwrap_or(rect);
+		let other_area = self
+			.grid
+			.history
+			.area_of(other, other_rect)
+			.unwrap_or(other_rect);
+
+		self.move_window(window, other_area, other_rect);
+		self.move_window(other, area, rect);
+
+		self.snap_cycles.remove(&window);
+		self.snap_cycles.remove(&other);
+	}
+
+	/// Labels the visible windows, typing a label applies `action` to that window
+	///
+	/// Pressing the hotkey again while the labels are shown hides them.
+	fn show_hints(&mut self, action: HintAction) {
+		if let Some(hints) = self.close_hints() {
+			self.commands.push(Command::Focus(hints.foreground));
+			return;
+		}
+
+		let windows: Vec<_> = self
+			.managed_windows()
+			.into_iter()
+			.filter(|window| !self.is_minimized(*window) && !self.ignores(*window))
+			.map(|window| (window, self.visible_rect(window)))
+			.collect();
+
+		if windows.is_empty() {
+			return;
+		}
+
+		let monitors: Vec<_> = self
+			.desktop
+			.monitors()
+			.iter()
+			.map(|monitor| monitor.rect)
+			.collect();
+		let area = bounds(&monitors);
+
+		self.hints = Some(Hints::new(
+			action,
+			self.desktop.foreground_window(),
+			area,
+			windows,
+			&self.config.hints.letters(),
+			self.desktop.cursor_position(),
+		));
+
+		self.commands.push(Command::OpenHintWindow(area));
+	}
+
+	/// Ends hint mode and returns its state, if it was active
+	fn close_hints(&mut self) -> Option<Hints> {
+		self.hint_window.take();
+
+		let hints = self.hints.take()?;
+		self.commands.push(Command::CloseHintWindow);
+
+		Some(hints)
+	}
+
+	/// Applies the action of hint mode to `window`, whose label was typed
+	///
+	/// Returns true if hint mode goes on and the labels have to be painted again.
+	fn apply_hint(&mut self, window: Window) -> bool {
+		let action = match &self.hints {
+			Some(hints) => hints.action,
+			None => return false,
+		};
+
+		match action {
+			HintAction::Focus => {
+				self.close_hints();
+				self.commands.push(Command::Focus(window));
+				false
+			}
+			HintAction::Swap => {
+				if let Some(hints) = self.close_hints() {
+					let foreground = hints.foreground;
+
+					if foreground != window
+						&& self.desktop.is_managed(foreground)
+						&& !self.ignores(foreground)
+					{
+						self.swap_windows(foreground, window);
+					}
+
+					self.commands.push(Command::Focus(foreground));
+				}
+
+				false
+			}
+			HintAction::Zone => {
+				let zones = self.hint_zones(window, 0);
+
+				if let Some(hints) = &mut self.hints {
+					hints.zone_set = 0;
+					hints.pick(window, zones);
+				}
+
+				true
+			}
+		}
+	}
+
+	/// The zones of the zone set at `index` on the monitor of `window`
+	fn hint_zones(&self, window: Window, index: usize) -> Vec<Rect> {
+		match self.config.zone_sets.get(index) {
+			Some(zone_set) => zone_set.resolve(self.desktop.monitor_of(window).work_area),
+			None => Vec::new(),
+		}
+	}
+
+	/// Shows the zones of the next zone set to the window that was picked in hint mode
+	fn next_hint_zone_set(&mut self) -> bool {
+		let count = self.config.zone_sets.len();
+
+		let (window, index) = match &self.hints {
+			Some(Hints {
+				picked: Some(window),
+				zone_set,
+				..
+			}) if count > 0 => (*window, (zone_set + 1) % count),
+			_ => return false,
+		};
+
+		let zones = self.hint_zones(window, index);
+
+		if let Some(hints) = &mut self.hints {
+			hints.zone_set = index;
+			hints.zones = zones;
+		}
+
+		true
+	}
+
+	/// Moves the window that was picked in hint mode to the zone at `index` and ends hint mode
+	fn place_in_hint_zone(&mut self, index: usize) {
+		let (window, zone) = match &self.hints {
+			Some(Hints {
+				picked: Some(window),
+				zones,
+				..
+			}) => match zones.get(index) {
+				Some(zone) => (*window, *zone),
+				None => return,
+			},
+			_ => return,
+		};
+
+		if let Some(hints) = self.close_hints() {
+			self.place_window(window, zone);
+			self.commands.push(Command::Focus(hints.foreground));
+			self.snap_cycles.remove(&window);
+		}
+	}
+
+	/// Moves or resizes the foreground window by one step
+	///
+	/// Edges that end up close to the edge of the monitor or another window snap to it.
+	fn nudge(&mut self, adjustment: Adjustment) {
+		let window = self.desktop.foreground_window();
+
+		if self.ignores(window) {
+			return;
+		}
+
+		let area = self.desktop.monitor_of(window).work_area;
+		let step = self.config.nudge.step.pixels(area, adjustment.direction());
+
+		let others: Vec<_> = self
+			.managed_windows()
+			.into_iter()
+			.filter(|other| *other != window && !self.is_minimized(*other))
+			.map(|other| self.visible_rect(other))
+			.collect();
+
+		let rect = snap_edges(
+			adjustment.apply(self.visible_rect(window), step),
+			adjustment,
+			area,
+			&others,
+			self.config.nudge.snap_distance as i32,
+		);
+
+		self.place_window(window, rect);
+		self.snap_cycles.remove(&window);
+	}
+
+	/// Sends the foreground window to the monitor `target` refers to
+	///
+	/// A window that was placed in a zone goes to the matching zone of the other monitor's grid,
+	/// any other window keeps its position and size relative to the monitor.
+	fn send_to_monitor(&mut self, target: MonitorTarget) {
+		let window = self.desktop.foreground_window();
+
+		if self.ignores(window) {
+			return;
+		}
+
+		let monitors = self.desktop.monitors();
+		let areas: Vec<_> = monitors.iter().map(|monitor| monitor.work_area).collect();
+		let monitor = self.desktop.monitor_of(window);
+
+		let current = match monitors.iter().position(|other| other.name == monitor.name) {
+			Some(current) => current,
+			None => return,
+		};
+
+		let next = match target_monitor(&areas, current, target) {
+			Some(next) if next != current => next,
+			_ => return,
+		};
+
+		let maximized = self.desktop.window_state(window) == WindowState::Maximized;
+		self.commands.push(Command::Restore(window));
+
+		let window_rect = self.desktop.window_rect(window);
+
+		match self.grid.history.area_of(window, window_rect) {
+			Some(area) => {
+				let mut grid = Grid::new(&self.config);
+				grid.apply_profile(
+					&self.config.profiles()[self.profile_index(&monitors[next].name)],
+				);
+
+				let zones = grid.span_areas(areas[next]);
+				let area = map_area(area, areas[current], areas[next], &zones);
+
+				self.place_window(window, area);
+			}
+			None => {
+				let rect = relocate(window_rect, areas[current], areas[next]);
+
+				self.move_window(window, rect, rect);
+			}
+		}
+
+		if maximized {
+			self.commands.push(Command::Maximize(window));
+		}
+
+		self.snap_cycles.remove(&window);
+	}
+
+	/// Moves `window` back to where it was before wtm moved it for the first time
+	fn restore_window(&mut self, window: Window) {
+		if let Some(rect) = self.grid.history.original(window) {
+			self.return_window(window, rect);
+			self.grid.history.forget(window);
+		}
+	}
+
+	/// Moves every window that still exists back to where it was before wtm moved it
+	fn restore_all(&mut self) {
+		let desktop = &self.desktop;
+		let originals = self
+			.grid
+			.history
+			.take_originals(|window| desktop.exists(window));
+
+		for (window, rect) in originals {
+			self.return_window(window, rect);
+		}
+	}
+
+	/// Every shown application window, from the top of the z-order to the bottom
+	fn managed_windows(&self) -> Vec<Window> {
+		self.desktop
+			.windows()
+			.into_iter()
+			.filter(|window| self.desktop.is_managed(*window))
+			.collect()
+	}
+
+	/// Is `window` minimized
+	fn is_minimized(&self, window: Window) -> bool {
+		self.desktop.window_state(window) == WindowState::Minimized
+	}
+
+	/// The part of `window` that can be seen, without the invisible border set by its rule or
+	/// drawn by the desktop window manager
+	fn visible_rect(&self, window: Window) -> Rect {
+		self.desktop.window_rect(window).inset(self.insets(window))
+	}
+
+	/// Where `window` is, or where it is moving to while it is animated
+	fn destination(&self, window: Window) -> Rect {
+		self.grid
+			.animator
+			.target(window)
+			.unwrap_or_else(|| self.desktop.window_rect(window))
+	}
+
+	/// Shows and hides the windows of `transition` and moves the focus
+	fn transition(&mut self, transition: Transition) {
+		self.commands.extend(transition.commands());
+	}
+
+	/// The first rule that matches `window`
+	fn rule_for(&self, window: Window) -> Option<&Rule> {
+		let index = *self
+			.matched_rules
+			.borrow_mut()
+			.entry(window)
+			.or_insert_with(|| {
+				let properties = self.desktop.window_properties(window);
+
+				self.config
+					.rules
+					.iter()
+					.position(|rule| rule.matches(&properties))
+			});
+
+		index.map(|index| &self.config.rules[index])
+	}
+
+	/// Does a rule tell wtm to leave `window` alone
+	fn ignores(&self, window: Window) -> bool {
+		matches!(self.rule_for(window), Some(rule) if rule.ignore)
+	}
+
+	/// Is `window` kept from being tiled, by a rule or because it was left floating
+	fn floats(&self, window: Window) -> bool {
+		self.grid.floating.contains(&window)
+			|| matches!(self.rule_for(window), Some(rule) if rule.ignore || rule.float)
+	}
+
+	/// Applies the first rule that matches `window`
+	///
+	/// Returns false if no rule matches the window.
+	fn apply_rules(&mut self, window: Window) -> bool {
+		let rule = match self.rule_for(window) {
+			Some(rule) if !rule.ignore => rule.clone(),
+			Some(_) => return true,
+			None => return false,
+		};
+
+		if let Some(opacity) = rule.opacity {
+			self.commands.push(Command::SetOpacity(window, opacity));
+		}
+
+		let mut monitor = self.desktop.monitor_of(window);
+
+		if let Some(number) = rule.monitor {
+			match self.desktop.monitors().get(number - 1) {
+				Some(target) if target.name != monitor.name => {
+					let rect = relocate(
+						self.desktop.window_rect(window),
+						monitor.work_area,
+						target.work_area,
+					);
+					self.move_window(window, rect, rect);
+					monitor = target.clone();
+				}
+				Some(_) => {}
+				None => warn!("There is no monitor number {}", number),
+			}
+		}
+
+		if let Some(target) = rule.target() {
+			self.snap_to(window, &monitor, &target);
+		}
+
+		if let Some(workspace) = rule.workspace {
+			let workspaces = self.monitor_workspaces(&monitor);
+
+			if let Some(transition) = workspaces.move_window(window, workspace - 1) {
+				self.transition(transition);
+			}
+		}
+
+		true
+	}
+
+	/// The workspaces of `monitor`, updated with the windows that are shown on it
+	fn monitor_workspaces(&mut self, monitor: &Display) -> &mut Workspaces {
+		let shown: Vec<_> = self
+			.managed_windows()
+			.into_iter()
+			.filter(|window| self.desktop.monitor_of(*window).name == monitor.name)
+			.filter(|window| !self.ignores(*window))
+			.collect();
+
+		let count = self.config.workspaces.count;
+		let desktop = &self.desktop;
+		let workspaces = self
+			.workspaces
+			.entry(monitor.name.clone())
+			.or_insert_with(|| Workspaces::new(count));
+
+		workspaces.retain(|window| desktop.exists(window));
+		workspaces.sync(&shown);
+
+		workspaces
+	}
+
+	/// Shows the workspace at `index` on the active monitor
+	fn switch_workspace(&mut self, index: usize) {
+		let monitor = self.desktop.active_monitor();
+
+		if let Some(transition) = self.monitor_workspaces(&monitor).switch(index) {
+			self.transition(transition);
+			self.notify_workspace(&monitor.name);
+		}
+	}
+
+	/// Moves the foreground window to the workspace at `index` of its monitor
+	fn move_to_workspace(&mut self, index: usize) {
+		let window = self.desktop.foreground_window();
+
+		if window.0.is_null() || Some(window) == self.grid_window {
+			return;
+		}
+
+		let monitor = self.desktop.monitor_of(window);
+
+		if let Some(transition) = self.monitor_workspaces(&monitor).move_window(window, index) {
+			self.transition(transition);
+		}
+	}
+
+	/// Changes the tiling of the active monitor with `change` and tiles its windows
+	fn tile(&mut self, change: impl FnOnce(&mut Tiling)) {
+		let monitor = self.desktop.active_monitor();
+		let workspace = self.monitor_workspaces(&monitor).active();
+
+		let windows: Vec<_> = self
+			.desktop
+			.windows()
+			.into_iter()
+			.filter(|window| {
+				self.desktop.is_tileable(*window)
+					&& self.desktop.monitor_of(*window).name == monitor.name
+					&& !self.floats(*window)
+			})
+			.collect();
+
+		let config = &self.config.tiling;
+		let tiling = self
+			.tilings
+			.entry((monitor.name.clone(), workspace))
+			.or_insert_with(|| Tiling::new(config));
+
+		tiling.update_windows(&windows);
+		change(tiling);
+
+		info!(
+			"Tiling {} windows with the {} layout",
+			tiling.windows.len(),
+			tiling.layout(config)
+		);
+
+		for (window, rect) in tiling.arrange(config, monitor.work_area) {
+			self.place_window(window, rect);
+		}
+	}
+
+	/// Moves the foreground window to the target of the snap command at `index`
+	///
+	/// The target is computed from the profile of the monitor the window is on.
+	fn snap(&mut self, index: usize) {
+		let target = match self.config.snap.get(index).and_then(|snap| snap.target()) {
+			Some(target) => target,
+			None => return,
+		};
+
+		let window = self.desktop.foreground_window();
+
+		if window.0.is_null() || Some(window) == self.grid_window || self.ignores(window) {
+			return;
+		}
+
+		let monitor = self.desktop.monitor_of(window);
+
+		let region = match target {
+			SnapTarget::Region(region) => region,
+			target => {
+				self.snap_cycles.remove(&window);
+				self.snap_to(window, &monitor, &target);
+				return;
+			}
+		};
+
+		// A window that is still moving counts as being where it was placed, so the cycle goes on
+		// while the window is animated
+		let placed = self.grid.history.area_of(window, self.destination(window));
+		let previous_step = self
+			.snap_cycles
+			.get(&window)
+			.zip(placed)
+			.and_then(|(cycle, area)| cycle.previous_step(index, area));
+
+		let (monitor, region, step) = match region.cycle(&self.config.cycle.ratios, previous_step) {
+			SnapStep::Stay(region, step) => (monitor, region, Some(step)),
+			SnapStep::Monitor(direction, mirrored) => {
+				let monitors = self.desktop.monitors();
+				let areas: Vec<Rect> = monitors.iter().map(|monitor| monitor.work_area).collect();
+
+				match adjacent(&areas, monitor.work_area, direction) {
+					Some(next) => (monitors[next].clone(), mirrored, None),
+					// There is no monitor beyond the edge, start over
+					None => (monitor, region, Some(0)),
+				}
+			}
+		};
+
+		let area = self.snap_to(window, &monitor, &SnapTarget::Region(region));
+
+		match step {
+			Some(step) => {
+				self.snap_cycles.insert(
+					window,
+					SnapCycle {
+						hotkey: index,
+						step,
+						area,
+					},
+				);
+			}
+			None => {
+				self.snap_cycles.remove(&window);
+			}
+		}
+	}
+
+	/// Moves `window` to `target` on `monitor`, using the margins of the monitor's profile
+	///
+	/// Returns the area the window is placed in.
+	fn snap_to(&mut self, window: Window, monitor: &Display, target: &SnapTarget) -> Rect {
+		let mut grid = Grid::new(&self.config);
+		grid.apply_profile(&self.config.profiles()[self.profile_index(&monitor.name)]);
+
+		let area = grid.snap_area(monitor.work_area, target);
+		self.place_window(window, area);
+
+		area
+	}
+}
+
+impl Drop for TilingManager {
+	/// Stops the threads that still run for the tiling manager
+	fn drop(&mut self) {
+		raise(&mut self.close_signal);
+		raise(&mut self.drag_signal);
+		raise(&mut self.hint_signal);
+		raise(&mut self.hook_signal);
+	}
+}
+
+/// The signal in `slot`, created if there is none yet
+///
+/// Returns None if no signal can be created, the threads it would stop aren't started then.
+fn signal(slot: &mut Option<Signal>) -> Option<Signal> {
+	if slot.is_none() {
+		match Signal::new() {
+			Ok(signal) => *slot = Some(signal),
+			Err(err) => error!("Failed to create a signal to stop threads with: {}", err),
+		}
+	}
+
+	slot.clone()
+}
+
+/// Raises the signal in `slot`, the next threads are stopped by a new one
+fn raise(slot: &mut Option<Signal>) {
+	if let Some(signal) = slot.take() {
+		signal.raise();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::record::{RecordedWindow, SimulatedDesktop, Snapshot};
+	use winapi::um::winuser::{VK_RETURN, VK_RIGHT};
+
+	const GRID_WINDOW: Window = Window(10 as _);
+	const PREVIEW_WINDOW: Window = Window(11 as _);
+
+	/// The window in the foreground
+	fn foreground() -> Window {
+		Window(1 as _)
+	}
+
+	/// A tiling manager on a full HD monitor with a taskbar at the bottom
+	fn manager() -> TilingManager {
+		let monitor = Display {
+			name: String::from(r"\\.\DISPLAY1"),
+			rect: Rect {
+				x: 0,
+				y: 0,
+				w: 1920,
+				h: 1080,
+			},
+			work_area: Rect {
+				x: 0,
+				y: 0,
+				w: 1920,
+				h: 1040,
+			},
+		};
+		let snapshot = Snapshot {
+			monitor: monitor.name.clone(),
+			cursor: (960, 540),
+			foreground: foreground(),
+			windows: vec![RecordedWindow {
+				window: foreground(),
+				properties: WindowProperties {
+					title: String::from("Notes"),
+					class: String::from("Notepad"),
+					..WindowProperties::default()
+				},
+				rect: Rect {
+					x: 100,
+					y: 100,
+					w: 800,
+					h: 600,
+				},
+				insets: Insets::default(),
+				state: WindowState::Normal,
+				monitor: monitor.name.clone(),
+				managed: true,
+				tileable: true,
+			}],
+			monitors: vec![monitor],
+		};
+
+		TilingManager::new(
+			WtmConfig::default(),
+			Box::new(SimulatedDesktop { snapshot }),
+		)
+	}
+
+	/// Presses `hotkey` and answers with the windows `execute` would have created
+	fn open_grid(tm: &mut TilingManager, hotkey: HotkeyType) {
+		let commands = tm.update(Event::HotkeyPressed(hotkey));
+		assert_eq!(
+			commands,
+			vec![Command::OpenGridWindow(tm.grid.window_rect())]
+		);
+
+		assert_eq!(
+			tm.update(Event::GridWindow(GRID_WINDOW)),
+			vec![Command::WatchGridWindow, Command::OpenPreviewWindow]
+		);
+		assert_eq!(
+			tm.update(Event::PreviewWindow(PREVIEW_WINDOW)),
+			vec![Command::ShowGridWindow(GRID_WINDOW)]
+		);
+	}
+
+	/// The top left quarter of the work area, inside of the default margins
+	fn top_left() -> Rect {
+		Rect {
+			x: 10,
+			y: 10,
+			w: 945,
+			h: 505,
+		}
+	}
+
+	#[test]
+	fn main_hotkey_opens_and_closes_the_grid() {
+		let mut tm = manager();
+		open_grid(&mut tm, HotkeyType::Main);
+
+		assert_eq!(
+			tm.update(Event::HotkeyPressed(HotkeyType::Main)),
+			vec![Command::CloseWindows]
+		);
+	}
+
+	#[test]
+	fn main_grid_places_the_foreground_window_in_the_tile_picked_with_the_keyboard() {
+		let mut tm = manager();
+		open_grid(&mut tm, HotkeyType::Main);
+
+		assert_eq!(
+			tm.update(Event::GridKeyDown(VK_RIGHT)),
+			vec![
+				Command::SetPos {
+					window: PREVIEW_WINDOW,
+					rect: top_left(),
+					insert_after: Some(GRID_WINDOW),
+				},
+				Command::Redraw(GRID_WINDOW),
+			]
+		);
+		assert_eq!(
+			tm.update(Event::GridKeyDown(VK_RETURN)),
+			vec![
+				Command::MoveWindow(foreground(), top_left()),
+				Command::CloseWindows,
+			]
+		);
+	}
+
+	#[test]
+	fn main_grid_stays_open_after_a_tile_is_clicked() {
+		let mut tm = manager();
+		open_grid(&mut tm, HotkeyType::Main);
+
+		assert_eq!(
+			tm.update(Event::GridMouseDown((20, 20))),
+			vec![Command::Redraw(GRID_WINDOW)]
+		);
+		assert_eq!(
+			tm.update(Event::GridMouseUp),
+			vec![
+				Command::MoveWindow(foreground(), top_left()),
+				Command::Redraw(GRID_WINDOW),
+			]
+		);
+	}
+
+	#[test]
+	fn undo_and_redo_move_the_placed_window_between_its_rects() {
+		let mut tm = manager();
+		open_grid(&mut tm, HotkeyType::QuickResize);
+
+		tm.update(Event::GridMouseDown((20, 20)));
+		tm.update(Event::GridMouseUp);
+
+		assert_eq!(
+			tm.update(Event::HotkeyPressed(HotkeyType::Undo)),
+			vec![Command::MoveWindow(
+				foreground(),
+				Rect {
+					x: 100,
+					y: 100,
+					w: 800,
+					h: 600,
+				}
+			)]
+		);
+		assert_eq!(
+			tm.update(Event::HotkeyPressed(HotkeyType::Redo)),
+			vec![Command::MoveWindow(foreground(), top_left())]
+		);
+	}
+
+	#[test]
+	fn animated_windows_are_only_moved_by_the_frames() {
+		let mut tm = manager();
+		tm.config.animation.enabled = true;
+		open_grid(&mut tm, HotkeyType::Main);
+
+		tm.update(Event::GridMouseDown((20, 20)));
+
+		assert_eq!(
+			tm.update(Event::GridMouseUp),
+			vec![Command::Redraw(GRID_WINDOW)]
+		);
+		assert_eq!(tm.grid.animator.target(foreground()), Some(top_left()));
+	}
+
+	#[test]
+	fn hotkeys_of_removed_arrangements_are_ignored() {
+		let mut tm = manager();
+
+		for hotkey in [
+			HotkeyType::SaveArrangement(0),
+			HotkeyType::RestoreArrangement(0),
+			HotkeyType::DryRunArrangement(0),
+		] {
+			assert_eq!(tm.update(Event::HotkeyPressed(hotkey)), vec![]);
+		}
+	}
+
+	#[test]
+	fn quick_resize_closes_the_grid_once_the_window_is_placed() {
+		let mut tm = manager();
+		open_grid(&mut tm, HotkeyType::QuickResize);
+
+		assert_eq!(
+			tm.update(Event::GridMouseDown((20, 20))),
+			vec![Command::Redraw(GRID_WINDOW)]
+		);
+		assert_eq!(
+			tm.update(Event::GridMouseUp),
+			vec![
+				Command::MoveWindow(foreground(), top_left()),
+				Command::CloseWindows,
+			]
+		);
 	}
 }
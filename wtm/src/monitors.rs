@@ -0,0 +1,301 @@
+//! Monitors module
+
+use crate::grid::Direction;
+use serde::{Deserialize, Serialize};
+use winsapi::{Monitor, Rect};
+
+/// A monitor to send a window to
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum MonitorTarget {
+	/// The monitor to the right in the numbering, or the first one after the last
+	Next,
+	/// The monitor to the left in the numbering, or the last one before the first
+	Previous,
+	/// The monitor next to the current one in a direction
+	Direction(Direction),
+	/// The monitor with a number, counted from 1 on the left
+	Number(usize),
+}
+
+/// Index of the area next to `current` in `direction`
+///
+/// Only areas whose center lies in `direction` are considered. Areas that line up with `current`
+/// on the other axis are preferred, then the closest one wins.
+pub fn adjacent(areas: &[Rect], current: Rect, direction: Direction) -> Option<usize> {
+	let center = |rect: Rect| {
+		(
+			rect.x as i64 + rect.w as i64 / 2,
+			rect.y as i64 + rect.h as i64 / 2,
+		)
+	};
+	let overlap = |start: i32, length: i32, other_start: i32, other_length: i32| {
+		(start + length).min(other_start + other_length) > start.max(other_start)
+	};
+
+	let (x, y) = center(current);
+
+	areas
+		.iter()
+		.enumerate()
+		.filter(|(_, area)| **area != current)
+		.filter_map(|(index, area)| {
+			let (area_x, area_y) = center(*area);
+
+			let (distance, offset, lines_up) = match direction {
+				Direction::Left => (
+					x - area_x,
+					area_y - y,
+					overlap(area.y, area.h, current.y, current.h),
+				),
+				Direction::Right => (
+					area_x - x,
+					area_y - y,
+					overlap(area.y, area.h, current.y, current.h),
+				),
+				Direction::Up => (
+					y - area_y,
+					area_x - x,
+					overlap(area.x, area.w, current.x, current.w),
+				),
+				Direction::Down => (
+					area_y - y,
+					area_x - x,
+					overlap(area.x, area.w, current.x, current.w),
+				),
+			};
+
+			if distance > 0 {
+				Some((index, (!lines_up, distance, offset.abs())))
+			} else {
+				None
+			}
+		})
+		.min_by_key(|(_, key)| *key)
+		.map(|(index, _)| index)
+}
+
+/// Moves `rect` from the area `from` to the area `to`
+///
+/// The position and size stay the same relative to the areas, so a window in the right half of
+/// one monitor ends up in the right half of the other.
+pub fn relocate(rect: Rect, from: Rect, to: Rect) -> Rect {
+	let scale = |length: i32, from_length: i32, to_length: i32| {
+		(length as i64 * to_length as i64 / from_length.max(1) as i64) as i32
+	};
+
+	Rect {
+		x: to.x + scale(rect.x - from.x, from.w, to.w),
+		y: to.y + scale(rect.y - from.y, from.h, to.h),
+		w: scale(rect.w, from.w, to.w),
+		h: scale(rect.h, from.h, to.h),
+	}
+}
+
+/// Where `area` on the work area `from` goes on the work area `to`
+///
+/// The area keeps its position and size relative to the work areas. If one of `zones` overlaps
+/// the moved area well, that zone is used instead, so a window in a zone stays in a zone even if
+/// the grids of the monitors differ.
+pub fn map_area(area: Rect, from: Rect, to: Rect, zones: &[Rect]) -> Rect {
+	let area = relocate(area, from, to);
+	let size = |rect: Rect| rect.w.max(0) as i64 * rect.h.max(0) as i64;
+
+	zones
+		.iter()
+		.map(|zone| {
+			let overlap = Rect {
+				x: area.x.max(zone.x),
+				y: area.y.max(zone.y),
+				w: (area.x + area.w).min(zone.x + zone.w) - area.x.max(zone.x),
+				h: (area.y + area.h).min(zone.y + zone.h) - area.y.max(zone.y),
+			};
+			let union = size(area) + size(*zone) - size(overlap);
+
+			(*zone, size(overlap) as f64 / union.max(1) as f64)
+		})
+		// At least half of the area covered by either of them has to be shared
+		.filter(|(_, similarity)| *similarity >= 0.5)
+		.max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+		.map(|(zone, _)| zone)
+		.unwrap_or(area)
+}
+
+/// Index of the monitor `target` refers to
+///
+/// `areas` are the work areas of the monitors, numbered from left to right, and `current` is the
+/// index of the monitor the window is on. The next and previous monitor wrap around.
+pub fn target_monitor(areas: &[Rect], current: usize, target: MonitorTarget) -> Option<usize> {
+	if areas.is_empty() {
+		return None;
+	}
+
+	match target {
+		MonitorTarget::Next => Some((current + 1) % areas.len()),
+		MonitorTarget::Previous => Some((current + areas.len() - 1) % areas.len()),
+		MonitorTarget::Direction(direction) => adjacent(areas, areas[current], direction),
+		MonitorTarget::Number(number) if (1..=areas.len()).contains(&number) => Some(number - 1),
+		MonitorTarget::Number(_) => None,
+	}
+}
+
+/// Every monitor, numbered from left to right
+pub fn numbered_monitors() -> Vec<Monitor> {
+	let mut monitors = Monitor::all();
+	monitors.sort_by_key(|monitor| {
+		let area = monitor.area();
+		(area.x, area.y)
+	});
+
+	monitors
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn rect(x: i32, y: i32, w: i32, h: i32) -> Rect {
+		Rect { x, y, w, h }
+	}
+
+	/// A 1080p monitor at the origin
+	fn full_hd() -> Rect {
+		rect(0, 0, 1920, 1080)
+	}
+
+	/// A 4K monitor to the right of [`full_hd`], which has twice as many pixels per side
+	fn ultra_hd() -> Rect {
+		rect(1920, 0, 3840, 2160)
+	}
+
+	/// A 1440p monitor to the left of [`full_hd`] and a bit higher up
+	fn left_of_origin() -> Rect {
+		rect(-2560, -360, 2560, 1440)
+	}
+
+	#[test]
+	fn areas_keep_their_relative_place_on_a_monitor_with_a_higher_resolution() {
+		let right_half = rect(960, 0, 960, 1080);
+
+		assert_eq!(
+			map_area(right_half, full_hd(), ultra_hd(), &[]),
+			rect(3840, 0, 1920, 2160)
+		);
+	}
+
+	#[test]
+	fn areas_keep_their_relative_place_on_a_monitor_with_a_negative_origin() {
+		let center = rect(480, 270, 960, 540);
+		let moved = map_area(center, full_hd(), left_of_origin(), &[]);
+
+		assert_eq!(moved, rect(-1920, 0, 1280, 720));
+		assert_eq!(map_area(moved, left_of_origin(), full_hd(), &[]), center);
+	}
+
+	#[test]
+	fn areas_snap_to_a_zone_that_covers_most_of_them() {
+		let area = rect(0, 0, 1000, 1080);
+		let zones = [rect(1920, 0, 1920, 2160), rect(3840, 0, 1920, 2160)];
+
+		assert_eq!(map_area(area, full_hd(), ultra_hd(), &zones), zones[0]);
+	}
+
+	#[test]
+	fn areas_snap_to_the_zone_that_overlaps_them_best() {
+		// Lands on `1920, 0, 2000, 2160`
+		let area = rect(0, 0, 1000, 1080);
+		let zones = [rect(1920, 0, 2400, 2160), rect(1920, 0, 1920, 2160)];
+
+		assert_eq!(map_area(area, full_hd(), ultra_hd(), &zones), zones[1]);
+	}
+
+	#[test]
+	fn zones_have_to_share_at_least_half_of_the_area() {
+		let area = rect(0, 0, 1000, 1080);
+		let moved = rect(1920, 0, 2000, 2160);
+
+		// 1000 of the 2000 pixels wide area are shared
+		let half = [rect(1920, 0, 1000, 2160)];
+		assert_eq!(map_area(area, full_hd(), ultra_hd(), &half), half[0]);
+
+		let less_than_half = [rect(1920, 0, 960, 2160)];
+		assert_eq!(
+			map_area(area, full_hd(), ultra_hd(), &less_than_half),
+			moved
+		);
+	}
+
+	#[test]
+	fn next_and_previous_wrap_around() {
+		let areas = [left_of_origin(), full_hd(), ultra_hd()];
+
+		assert_eq!(target_monitor(&areas, 1, MonitorTarget::Next), Some(2));
+		assert_eq!(target_monitor(&areas, 2, MonitorTarget::Next), Some(0));
+		assert_eq!(target_monitor(&areas, 1, MonitorTarget::Previous), Some(0));
+		assert_eq!(target_monitor(&areas, 0, MonitorTarget::Previous), Some(2));
+	}
+
+	#[test]
+	fn numbers_start_at_one_and_stop_at_the_last_monitor() {
+		let areas = [left_of_origin(), full_hd(), ultra_hd()];
+
+		assert_eq!(target_monitor(&areas, 1, MonitorTarget::Number(1)), Some(0));
+		assert_eq!(target_monitor(&areas, 1, MonitorTarget::Number(3)), Some(2));
+		assert_eq!(target_monitor(&areas, 1, MonitorTarget::Number(0)), None);
+		assert_eq!(target_monitor(&areas, 1, MonitorTarget::Number(4)), None);
+	}
+
+	#[test]
+	fn there_is_no_target_without_monitors() {
+		for target in [
+			MonitorTarget::Next,
+			MonitorTarget::Previous,
+			MonitorTarget::Direction(Direction::Left),
+			MonitorTarget::Number(1),
+		] {
+			assert_eq!(target_monitor(&[], 0, target), None);
+		}
+	}
+
+	#[test]
+	fn directions_find_monitors_of_other_sizes_and_negative_origins() {
+		let areas = [left_of_origin(), full_hd(), ultra_hd()];
+		let direction = |current, direction| {
+			target_monitor(&areas, current, MonitorTarget::Direction(direction))
+		};
+
+		assert_eq!(direction(1, Direction::Left), Some(0));
+		assert_eq!(direction(1, Direction::Right), Some(2));
+		assert_eq!(direction(2, Direction::Left), Some(1));
+		assert_eq!(direction(0, Direction::Left), None);
+		// Monitors that don't line up are still found if nothing else is in that direction
+		assert_eq!(direction(1, Direction::Up), Some(0));
+		assert_eq!(direction(0, Direction::Up), None);
+	}
+
+	#[test]
+	fn directions_find_monitors_stacked_on_top_of_each_other() {
+		// Both start at the left edge, so the upper one is numbered first
+		let areas = [rect(0, -1440, 2560, 1440), full_hd()];
+		let direction = |current, direction| {
+			target_monitor(&areas, current, MonitorTarget::Direction(direction))
+		};
+
+		assert_eq!(direction(1, Direction::Up), Some(0));
+		assert_eq!(direction(0, Direction::Down), Some(1));
+		assert_eq!(direction(1, Direction::Left), None);
+	}
+
+	#[test]
+	fn monitors_that_line_up_are_preferred_over_closer_ones() {
+		let areas = [
+			full_hd(),
+			rect(1920, -1200, 1920, 1080),
+			rect(3840, 0, 1920, 1080),
+		];
+
+		assert_eq!(
+			target_monitor(&areas, 0, MonitorTarget::Direction(Direction::Right)),
+			Some(2)
+		);
+	}
+}
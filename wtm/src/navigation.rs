@@ -0,0 +1,181 @@
+//! Navigation module
+
+use crate::grid::Direction;
+use winsapi::Rect;
+
+/// Index of the window next to the window at `current` in `direction`
+///
+/// `rects` are the rectangles of the windows in screen coordinates, so windows on other monitors
+/// are found as well. `recency` holds for each window how long ago it was focused, lower is more
+/// recent. Only windows that lie further in `direction` than the current one are considered.
+/// Windows that overlap the current one on the other axis come first, then the closest one wins.
+/// If several windows are equally close, like the stacked windows of a tiling, the one that was
+/// focused last wins, then the one that lines up best.
+pub fn neighbor(
+	rects: &[Rect],
+	current: usize,
+	direction: Direction,
+	recency: &[usize],
+) -> Option<usize> {
+	let from = *rects.get(current)?;
+
+	let center = |rect: Rect| {
+		(
+			rect.x as i64 + rect.w as i64 / 2,
+			rect.y as i64 + rect.h as i64 / 2,
+		)
+	};
+	let overlap = |start: i32, length: i32, other_start: i32, other_length: i32| {
+		(start + length).min(other_start + other_length) > start.max(other_start)
+	};
+
+	let (x, y) = center(from);
+
+	rects
+		.iter()
+		.enumerate()
+		.filter(|(index, _)| *index != current)
+		.filter_map(|(index, rect)| {
+			let (rect_x, rect_y) = center(*rect);
+
+			// How far the window lies in `direction`, the gap between the edges, and how far it is
+			// off on the other axis
+			let (ahead, gap, offset, lines_up) = match direction {
+				Direction::Left => (
+					x - rect_x,
+					from.x as i64 - (rect.x + rect.w) as i64,
+					rect_y - y,
+					overlap(rect.y, rect.h, from.y, from.h),
+				),
+				Direction::Right => (
+					rect_x - x,
+					rect.x as i64 - (from.x + from.w) as i64,
+					rect_y - y,
+					overlap(rect.y, rect.h, from.y, from.h),
+				),
+				Direction::Up => (
+					y - rect_y,
+					from.y as i64 - (rect.y + rect.h) as i64,
+					rect_x - x,
+					overlap(rect.x, rect.w, from.x, from.w),
+				),
+				Direction::Down => (
+					rect_y - y,
+					rect.y as i64 - (from.y + from.h) as i64,
+					rect_x - x,
+					overlap(rect.x, rect.w, from.x, from.w),
+				),
+			};
+
+			if ahead > 0 {
+				let recency = recency.get(index).copied().unwrap_or(usize::MAX);
+
+				Some((index, (!lines_up, gap.max(0), recency, offset.abs())))
+			} else {
+				None
+			}
+		})
+		.min_by_key(|(_, key)| *key)
+		.map(|(index, _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const DIRECTIONS: [Direction; 4] = [
+		Direction::Left,
+		Direction::Right,
+		Direction::Up,
+		Direction::Down,
+	];
+
+	fn rect(x: i32, y: i32, w: i32, h: i32) -> Rect {
+		Rect { x, y, w, h }
+	}
+
+	/// A window on the left half and two stacked on the right half of a 1080p monitor
+	fn tiling() -> Vec<Rect> {
+		vec![
+			rect(0, 0, 960, 1080),
+			rect(960, 0, 960, 540),
+			rect(960, 540, 960, 540),
+		]
+	}
+
+	#[test]
+	fn equally_close_windows_are_decided_by_recency() {
+		let rects = tiling();
+
+		assert_eq!(neighbor(&rects, 0, Direction::Right, &[0, 1, 2]), Some(1));
+		assert_eq!(neighbor(&rects, 0, Direction::Right, &[0, 2, 1]), Some(2));
+	}
+
+	#[test]
+	fn equally_close_and_recent_windows_are_decided_by_how_well_they_line_up() {
+		let rects = [
+			rect(0, 200, 960, 540),
+			rect(960, 0, 960, 540),
+			rect(960, 540, 960, 540),
+		];
+
+		assert_eq!(neighbor(&rects, 0, Direction::Right, &[]), Some(1));
+		assert_eq!(neighbor(&rects, 0, Direction::Right, &[0, 1, 1]), Some(1));
+	}
+
+	#[test]
+	fn stacked_windows_are_found_from_each_other() {
+		let rects = tiling();
+
+		assert_eq!(neighbor(&rects, 1, Direction::Down, &[]), Some(2));
+		assert_eq!(neighbor(&rects, 2, Direction::Up, &[]), Some(1));
+		assert_eq!(neighbor(&rects, 2, Direction::Left, &[]), Some(0));
+	}
+
+	#[test]
+	fn overlapping_windows_count_as_touching() {
+		let rects = [
+			rect(0, 0, 1000, 1000),
+			rect(1600, 0, 1000, 1000),
+			rect(500, 0, 1000, 1000),
+		];
+
+		assert_eq!(neighbor(&rects, 0, Direction::Right, &[]), Some(2));
+		assert_eq!(neighbor(&rects, 2, Direction::Left, &[]), Some(0));
+	}
+
+	#[test]
+	fn windows_that_line_up_are_preferred_over_closer_ones() {
+		let rects = [
+			rect(0, 0, 960, 540),
+			rect(2000, 0, 960, 540),
+			rect(1000, -600, 500, 500),
+		];
+
+		assert_eq!(neighbor(&rects, 0, Direction::Right, &[0, 2, 1]), Some(1));
+	}
+
+	#[test]
+	fn windows_with_the_same_center_are_in_no_direction() {
+		let rects = [rect(0, 0, 1920, 1080), rect(480, 270, 960, 540)];
+
+		for direction in DIRECTIONS.iter() {
+			assert_eq!(neighbor(&rects, 0, *direction, &[]), None);
+			assert_eq!(neighbor(&rects, 1, *direction, &[]), None);
+		}
+	}
+
+	#[test]
+	fn there_is_no_neighbor_without_a_candidate() {
+		let rects = tiling();
+
+		assert_eq!(neighbor(&rects, 0, Direction::Left, &[]), None);
+		assert_eq!(neighbor(&rects, 1, Direction::Up, &[]), None);
+		assert_eq!(neighbor(&rects, 2, Direction::Right, &[]), None);
+
+		for direction in DIRECTIONS.iter() {
+			assert_eq!(neighbor(&rects[..1], 0, *direction, &[]), None);
+			assert_eq!(neighbor(&rects, 3, *direction, &[]), None);
+		}
+	}
+}
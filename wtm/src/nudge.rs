@@ -0,0 +1,354 @@
+//! Nudge module
+
+use crate::grid::Direction;
+use serde::{Deserialize, Serialize};
+use winsapi::Rect;
+
+/// The smallest width and height a window can be shrunk to
+const MIN_SIZE: i32 = 50;
+
+/// How far a window is moved or resized by one key press
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NudgeStep {
+	/// A fixed amount of pixels
+	Pixels(u32),
+	/// A fraction of the width or height of the work area
+	Fraction(f32),
+}
+
+/// A change of a window's position or size by one step
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Adjustment {
+	/// Moves the whole window
+	Move(Direction),
+	/// Moves the edge on that side of the window outwards
+	Grow(Direction),
+	/// Moves the edge on that side of the window inwards
+	Shrink(Direction),
+}
+
+impl NudgeStep {
+	/// The step in pixels along the axis of `direction` of `area`
+	pub fn pixels(self, area: Rect, direction: Direction) -> i32 {
+		match self {
+			NudgeStep::Pixels(pixels) => pixels as i32,
+			NudgeStep::Fraction(fraction) => {
+				let length = match direction {
+					Direction::Left | Direction::Right => area.w,
+					Direction::Up | Direction::Down => area.h,
+				};
+
+				((length as f32 * fraction).round() as i32).max(1)
+			}
+		}
+	}
+}
+
+impl Adjustment {
+	/// The direction of the adjustment
+	pub fn direction(self) -> Direction {
+		match self {
+			Adjustment::Move(direction)
+			| Adjustment::Grow(direction)
+			| Adjustment::Shrink(direction) => direction,
+		}
+	}
+
+	/// Applies the adjustment to `rect`, moving it or one of its edges by `step` pixels
+	///
+	/// Windows aren't shrunk below a minimum size.
+	pub fn apply(self, rect: Rect, step: i32) -> Rect {
+		let Rect { x, y, w, h } = rect;
+		let shrink = |length: i32| (length - step).max(MIN_SIZE.min(length));
+
+		match self {
+			Adjustment::Move(Direction::Left) => Rect {
+				x: x - step,
+				..rect
+			},
+			Adjustment::Move(Direction::Right) => Rect {
+				x: x + step,
+				..rect
+			},
+			Adjustment::Move(Direction::Up) => Rect {
+				y: y - step,
+				..rect
+			},
+			Adjustment::Move(Direction::Down) => Rect {
+				y: y + step,
+				..rect
+			},
+			Adjustment::Grow(Direction::Left) => Rect {
+				x: x - step,
+				w: w + step,
+				..rect
+			},
+			Adjustment::Grow(Direction::Right) => Rect {
+				w: w + step,
+				..rect
+			},
+			Adjustment::Grow(Direction::Up) => Rect {
+				y: y - step,
+				h: h + step,
+				..rect
+			},
+			Adjustment::Grow(Direction::Down) => Rect {
+				h: h + step,
+				..rect
+			},
+			Adjustment::Shrink(Direction::Left) => Rect {
+				x: x + w - shrink(w),
+				w: shrink(w),
+				..rect
+			},
+			Adjustment::Shrink(Direction::Right) => Rect {
+				w: shrink(w),
+				..rect
+			},
+			Adjustment::Shrink(Direction::Up) => Rect {
+				y: y + h - shrink(h),
+				h: shrink(h),
+				..rect
+			},
+			Adjustment::Shrink(Direction::Down) => Rect {
+				h: shrink(h),
+				..rect
+			},
+		}
+	}
+}
+
+/// Pulls the edges `adjustment` moved onto a nearby edge of the work area or another window
+///
+/// Only edges within `threshold` pixels that lie further in the direction the edges travelled
+/// are used, so a window never snaps back to where it came from. Edges of other windows only
+/// count if the windows overlap `rect` on the other axis.
+pub fn snap_edges(
+	rect: Rect,
+	adjustment: Adjustment,
+	area: Rect,
+	others: &[Rect],
+	threshold: i32,
+) -> Rect {
+	let horizontal = matches!(adjustment.direction(), Direction::Left | Direction::Right);
+
+	// Start and length of a rectangle along the axis of the adjustment, and across it
+	let along = |rect: &Rect| {
+		if horizontal {
+			(rect.x, rect.w)
+		} else {
+			(rect.y, rect.h)
+		}
+	};
+	let across = |rect: &Rect| {
+		if horizontal {
+			(rect.y, rect.h)
+		} else {
+			(rect.x, rect.w)
+		}
+	};
+
+	let (start, length) = along(&rect);
+	let (across_start, across_length) = across(&rect);
+
+	let lines: Vec<i32> = others
+		.iter()
+		.filter(|other| {
+			let (other_start, other_length) = across(other);
+
+			(across_start + across_length).min(other_start + other_length)
+				> across_start.max(other_start)
+		})
+		.chain(Some(&area))
+		.flat_map(|other| {
+			let (other_start, other_length) = along(other);
+
+			vec![other_start, other_start + other_length]
+		})
+		.collect();
+
+	// The edges that moved, and if they moved towards larger coordinates
+	let (edges, forward) = match adjustment {
+		Adjustment::Move(direction) => (
+			vec![start, start + length],
+			matches!(direction, Direction::Right | Direction::Down),
+		),
+		Adjustment::Grow(Direction::Left) | Adjustment::Grow(Direction::Up) => (vec![start], false),
+		Adjustment::Grow(_) => (vec![start + length], true),
+		Adjustment::Shrink(Direction::Left) | Adjustment::Shrink(Direction::Up) => {
+			(vec![start], true)
+		}
+		Adjustment::Shrink(_) => (vec![start + length], false),
+	};
+
+	let delta = edges
+		.iter()
+		.flat_map(|edge| lines.iter().map(move |line| line - edge))
+		.filter(|delta| if forward { *delta > 0 } else { *delta < 0 })
+		.filter(|delta| delta.abs() <= threshold)
+		.min_by_key(|delta| delta.abs());
+
+	let delta = match delta {
+		Some(delta) => delta,
+		None => return rect,
+	};
+
+	let (start, length) = match adjustment {
+		Adjustment::Move(_) => (start + delta, length),
+		Adjustment::Grow(Direction::Left)
+		| Adjustment::Grow(Direction::Up)
+		| Adjustment::Shrink(Direction::Left)
+		| Adjustment::Shrink(Direction::Up) => (start + delta, length - delta),
+		_ => (start, length + delta),
+	};
+
+	if length < MIN_SIZE.min(along(&rect).1) {
+		return rect;
+	}
+
+	if horizontal {
+		Rect {
+			x: start,
+			w: length,
+			..rect
+		}
+	} else {
+		Rect {
+			y: start,
+			h: length,
+			..rect
+		}
+	}
+}
+
+/// `rect` moved to the center of `area`, shrunk to fit into it if needed
+pub fn center(rect: Rect, area: Rect) -> Rect {
+	let w = rect.w.min(area.w);
+	let h = rect.h.min(area.h);
+
+	Rect {
+		x: area.x + (area.w - w) / 2,
+		y: area.y + (area.h - h) / 2,
+		w,
+		h,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const THRESHOLD: i32 = 20;
+
+	const AREA: Rect = Rect {
+		x: 0,
+		y: 0,
+		w: 1920,
+		h: 1080,
+	};
+
+	fn rect(x: i32, y: i32, w: i32, h: i32) -> Rect {
+		Rect { x, y, w, h }
+	}
+
+	fn snap(rect: Rect, adjustment: Adjustment, others: &[Rect]) -> Rect {
+		snap_edges(rect, adjustment, AREA, others, THRESHOLD)
+	}
+
+	#[test]
+	fn moved_windows_snap_to_the_monitor_edges() {
+		let right = Adjustment::Move(Direction::Right);
+		assert_eq!(
+			snap(rect(1000, 100, 905, 400), right, &[]),
+			rect(1015, 100, 905, 400)
+		);
+
+		let left = Adjustment::Move(Direction::Left);
+		assert_eq!(
+			snap(rect(12, 100, 500, 400), left, &[]),
+			rect(0, 100, 500, 400)
+		);
+
+		let down = Adjustment::Grow(Direction::Down);
+		assert_eq!(
+			snap(rect(100, 600, 400, 465), down, &[]),
+			rect(100, 600, 400, 480)
+		);
+	}
+
+	#[test]
+	fn edges_snap_up_to_the_threshold() {
+		let right = Adjustment::Move(Direction::Right);
+
+		assert_eq!(
+			snap(rect(1400, 100, 500, 400), right, &[]),
+			rect(1420, 100, 500, 400)
+		);
+		assert_eq!(
+			snap(rect(1399, 100, 500, 400), right, &[]),
+			rect(1399, 100, 500, 400)
+		);
+	}
+
+	#[test]
+	fn edges_never_snap_back_to_where_they_came_from() {
+		let moved = rect(5, 100, 500, 400);
+
+		assert_eq!(snap(moved, Adjustment::Move(Direction::Right), &[]), moved);
+	}
+
+	#[test]
+	fn edges_snap_to_the_zones_of_other_windows() {
+		let grown = rect(0, 100, 950, 400);
+		let right_half = rect(960, 0, 960, 1080);
+
+		assert_eq!(
+			snap(grown, Adjustment::Grow(Direction::Right), &[right_half]),
+			rect(0, 100, 960, 400)
+		);
+	}
+
+	#[test]
+	fn zones_that_dont_overlap_on_the_other_axis_are_ignored() {
+		let grown = rect(0, 100, 950, 400);
+		let bottom_right = rect(960, 600, 960, 480);
+
+		assert_eq!(
+			snap(grown, Adjustment::Grow(Direction::Right), &[bottom_right]),
+			grown
+		);
+	}
+
+	#[test]
+	fn the_closest_edge_wins() {
+		let grown = rect(0, 100, 950, 400);
+		let zones = [rect(965, 0, 955, 1080), rect(955, 0, 965, 1080)];
+
+		assert_eq!(
+			snap(grown, Adjustment::Grow(Direction::Right), &zones),
+			rect(0, 100, 955, 400)
+		);
+	}
+
+	#[test]
+	fn shrunk_edges_snap_inwards() {
+		let shrunk = rect(10, 100, 500, 400);
+		let left = rect(-500, 0, 520, 1080);
+
+		assert_eq!(
+			snap(shrunk, Adjustment::Shrink(Direction::Left), &[left]),
+			rect(20, 100, 490, 400)
+		);
+	}
+
+	#[test]
+	fn edges_dont_snap_below_the_minimum_size() {
+		let shrunk = rect(0, 100, 60, 400);
+		let left = rect(-100, 0, 145, 1080);
+
+		assert_eq!(
+			snap(shrunk, Adjustment::Shrink(Direction::Right), &[left]),
+			shrunk
+		);
+	}
+}
@@ -0,0 +1,157 @@
+//! Placement module
+
+use crate::{
+	arrangements::WindowState,
+	command::Command,
+	fit::{fit, Fit},
+	grid::window_insets,
+	history::Placement,
+	TilingManager,
+};
+use std::time::Duration;
+use winsapi::{Insets, Rect, SizeLimits, Window};
+
+impl TilingManager {
+	/// Moves `window` into `rect`, compensating for its invisible border
+	///
+	/// Windows that can't be resized to `rect` are placed according to the `placement` config.
+	pub(crate) fn place_window(&mut self, window: Window, rect: Rect) {
+		self.restore(window);
+
+		let insets = self.insets(window);
+		let monitor = self.desktop.monitor_of(window);
+		let work_area = self
+			.desktop
+			.monitors()
+			.into_iter()
+			.map(|monitor| monitor.work_area)
+			.find(|area| area.contains_point((rect.x + rect.w / 2, rect.y + rect.h / 2)))
+			.unwrap_or(monitor.work_area);
+
+		let mut zones = self.grid.span_areas(work_area);
+		if let Some(zone_set) = self.grid.active_zone_set() {
+			zones.extend(zone_set.resolve(work_area));
+		}
+
+		// The limits include the border, the areas don't
+		let limits = self.desktop.size_limits(window);
+		let (border_width, border_height) =
+			(insets.left + insets.right, insets.top + insets.bottom);
+		let limits = SizeLimits {
+			min_width: limits.min_width - border_width,
+			min_height: limits.min_height - border_height,
+			max_width: limits.max_width - border_width,
+			max_height: limits.max_height - border_height,
+		};
+
+		let too_small = self.config.placement.too_small;
+		let placed = match fit(rect, work_area, limits, too_small, &zones) {
+			Fit::Place(placed) => placed,
+			Fit::Float => {
+				self.grid.floating.insert(window);
+				return;
+			}
+		};
+
+		self.grid.floating.remove(&window);
+
+		// The window counts as placed in `rect` even if it didn't fit, so it can be found in there
+		self.move_window(window, rect, placed.outset(insets));
+
+		// The border changes when the window moves to a monitor with a different scale, which can
+		// only be measured once it was moved there
+		if work_area != monitor.work_area && self.grid.animator.target(window).is_none() {
+			self.border_checks.insert(window, (placed, insets));
+			self.commands.push(Command::CheckBorder(window));
+		}
+	}
+
+	/// Moves `window` to `window_rect` and remembers where it was before
+	///
+	/// `area` is what the window was placed in, before compensating for its border.
+	pub(crate) fn move_window(&mut self, window: Window, area: Rect, window_rect: Rect) {
+		let from = self.animate(window, window_rect);
+
+		self.grid.history.record(Placement {
+			window,
+			area,
+			from,
+			to: window_rect,
+		});
+	}
+
+	/// Moves `window` back to `window_rect` without recording the move, like when a placement is
+	/// undone
+	///
+	/// Minimized and maximized windows are restored first.
+	pub(crate) fn return_window(&mut self, window: Window, window_rect: Rect) {
+		self.restore(window);
+		self.animate(window, window_rect);
+	}
+
+	/// Moves `window` again if its border changed since it was moved to another monitor
+	pub(crate) fn check_border(&mut self, window: Window) {
+		let (placed, insets) = match self.border_checks.remove(&window) {
+			Some(check) => check,
+			None => return,
+		};
+
+		let moved_insets = self.insets(window);
+
+		if moved_insets != insets && self.desktop.exists(window) {
+			let window_rect = placed.outset(moved_insets);
+
+			self.commands.push(Command::MoveWindow(window, window_rect));
+
+			if let Some(placement) = self
+				.grid
+				.history
+				.last_mut()
+				.filter(|placement| placement.window == window)
+			{
+				placement.to = window_rect;
+			}
+		}
+	}
+
+	/// The invisible border around `window`, as set by its rule or drawn by the desktop window
+	/// manager
+	pub(crate) fn insets(&self, window: Window) -> Insets {
+		let frame = self.rule_for(window).and_then(|rule| rule.frame);
+
+		window_insets(frame, || self.desktop.frame_insets(window))
+	}
+
+	/// Moves `window` to `window_rect`, animated if the `animation` config says so
+	///
+	/// A running animation of the window is cancelled first, so it can't move the window away
+	/// again. Returns where the window was, or was moving to.
+	fn animate(&mut self, window: Window, window_rect: Rect) -> Rect {
+		let current = self.desktop.window_rect(window);
+
+		// A window that is still moving counts as being where it was moving to
+		let from = self.grid.animator.cancel(window).unwrap_or(current);
+		let animation = &self.config.animation;
+
+		if animation.enabled && !self.is_minimized(window) {
+			self.grid.animator.start(
+				window,
+				current,
+				window_rect,
+				Duration::from_millis(animation.duration as u64),
+				animation.easing,
+			);
+		} else {
+			self.commands.push(Command::MoveWindow(window, window_rect));
+		}
+
+		from
+	}
+
+	/// Restores `window` if it is minimized or maximized
+	fn restore(&mut self, window: Window) {
+		if self.desktop.window_state(window) != WindowState::Normal {
+			self.commands.push(Command::Restore(window));
+		}
+	}
+}
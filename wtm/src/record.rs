@@ -0,0 +1,648 @@
+//! Recording module
+
+use crate::{
+	arrangements::WindowState,
+	command::Command,
+	config::WtmConfig,
+	desktop::Desktop,
+	displays::Display,
+	event::{Event, HotkeyType},
+	rules::WindowProperties,
+	TilingManager,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+	error::Error,
+	fmt,
+	fs::{self, File},
+	io::{self, BufRead, BufReader, LineWriter, Write},
+	path::{Path, PathBuf},
+	time::Instant,
+};
+use winsapi::{Insets, Rect, SizeLimits, Window};
+
+/// File next to the config file that the events are recorded to
+const RECORDING_FILE: &str = "recording.jsonl";
+
+/// An event as it was recorded, one line of a recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Record {
+	/// Milliseconds since the recording started
+	pub time: u64,
+	/// The event
+	pub event: Event,
+	/// What the desktop looked like when the event arrived
+	pub desktop: Snapshot,
+}
+
+/// The parts of the desktop the tiling manager reads while it handles an event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+	/// Every monitor, numbered from left to right
+	pub monitors: Vec<Display>,
+	/// Device name of the monitor the cursor is on
+	pub monitor: String,
+	/// Where the cursor is, in screen coordinates
+	pub cursor: (i32, i32),
+	/// The window in the foreground
+	#[serde(with = "handle")]
+	pub foreground: Window,
+	/// The shown application windows and the foreground window, from the top of the z-order to
+	/// the bottom
+	pub windows: Vec<RecordedWindow>,
+}
+
+/// A window as it was recorded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedWindow {
+	/// The window
+	#[serde(with = "handle")]
+	pub window: Window,
+	/// What the rules are matched against
+	pub properties: WindowProperties,
+	/// Rectangle of the window, including its invisible border
+	#[serde(with = "rect")]
+	pub rect: Rect,
+	/// The invisible border around the window
+	#[serde(with = "insets")]
+	pub insets: Insets,
+	/// Is the window maximized or minimized
+	pub state: WindowState,
+	/// Device name of the monitor most of the window is on
+	pub monitor: String,
+	/// Is the window a shown application window
+	pub managed: bool,
+	/// Is the window a normal application window that can be tiled
+	pub tileable: bool,
+}
+
+/// Writes every event the tiling manager handles to the recording file
+#[derive(Debug)]
+pub struct Recorder {
+	/// The recording file, each record is written as soon as its line is complete
+	file: LineWriter<File>,
+	/// When the recording started
+	start: Instant,
+}
+
+/// Answers with a snapshot instead of asking Windows, so recorded events can be replayed
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulatedDesktop {
+	/// The desktop as it was recorded
+	pub snapshot: Snapshot,
+}
+
+/// A window the tiling manager placed while a recording was replayed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReplayedPlacement {
+	/// When the event that placed the window was recorded, in milliseconds
+	pub time: u64,
+	/// The window
+	pub window: Window,
+	/// The area the window was placed in
+	pub rect: Rect,
+}
+
+/// Errors that can occur while recording or replaying events
+#[derive(Debug)]
+pub enum RecordError {
+	/// The user has no config directory
+	NoDirectory,
+	/// The file couldn't be read or written
+	Io(PathBuf, io::Error),
+	/// A line of the file isn't a valid record, holds the number of the line
+	Parse(PathBuf, usize, serde_json::Error),
+	/// A record couldn't be written as json
+	Serialize(serde_json::Error),
+}
+
+impl Snapshot {
+	/// Takes a snapshot of `desktop`
+	pub fn of(desktop: &dyn Desktop) -> Self {
+		let foreground = desktop.foreground_window();
+
+		let windows = desktop
+			.windows()
+			.into_iter()
+			.filter(|window| *window == foreground || desktop.is_managed(*window))
+			.map(|window| RecordedWindow {
+				window,
+				properties: desktop.window_properties(window),
+				rect: desktop.window_rect(window),
+				insets: desktop.frame_insets(window),
+				state: desktop.window_state(window),
+				monitor: desktop.monitor_of(window).name,
+				managed: desktop.is_managed(window),
+				tileable: desktop.is_tileable(window),
+			})
+			.collect();
+
+		Self {
+			monitors: desktop.monitors(),
+			monitor: desktop.active_monitor().name,
+			cursor: desktop.cursor_position(),
+			foreground,
+			windows,
+		}
+	}
+}
+
+impl SimulatedDesktop {
+	/// The recorded monitor called `name`, or the first one if it wasn't recorded
+	fn display(&self, name: &str) -> Display {
+		let monitors = &self.snapshot.monitors;
+
+		monitors
+			.iter()
+			.find(|monitor| monitor.name == name)
+			.or_else(|| monitors.first())
+			.cloned()
+			.unwrap_or_else(|| Display {
+				name: name.to_owned(),
+				rect: Rect::zero(),
+				work_area: Rect::zero(),
+			})
+	}
+
+	/// The recorded window
+	fn window(&self, window: Window) -> Option<&RecordedWindow> {
+		self.snapshot
+			.windows
+			.iter()
+			.find(|recorded| recorded.window == window)
+	}
+}
+
+impl Recorder {
+	/// The file the events are recorded to
+	pub fn path() -> Option<PathBuf> {
+		WtmConfig::path().and_then(|path| path.parent().map(|dir| dir.join(RECORDING_FILE)))
+	}
+
+	/// Starts a new recording, replacing the previous one
+	pub fn create() -> Result<Self, RecordError> {
+		let path = Self::path().ok_or(RecordError::NoDirectory)?;
+
+		if let Some(dir) = path.parent() {
+			fs::create_dir_all(dir).map_err(|err| RecordError::Io(dir.to_owned(), err))?;
+		}
+
+		let file = File::create(&path).map_err(|err| RecordError::Io(path, err))?;
+
+		Ok(Self {
+			file: LineWriter::new(file),
+			start: Instant::now(),
+		})
+	}
+
+	/// Writes `event` to the recording, along with a snapshot of `desktop`
+	pub fn record(&mut self, event: Event, desktop: &dyn Desktop) -> Result<(), RecordError> {
+		let record = Record {
+			time: self.start.elapsed().as_millis() as u64,
+			event,
+			desktop: Snapshot::of(desktop),
+		};
+
+		let line = serde_json::to_string(&record).map_err(RecordError::Serialize)?;
+
+		writeln!(self.file, "{}", line).map_err(|err| {
+			RecordError::Io(Self::path().unwrap_or_else(|| RECORDING_FILE.into()), err)
+		})
+	}
+}
+
+impl Desktop for SimulatedDesktop {
+	fn active_monitor(&self) -> Display {
+		self.display(&self.snapshot.monitor)
+	}
+
+	fn monitors(&self) -> Vec<Display> {
+		self.snapshot.monitors.clone()
+	}
+
+	/// Windows that weren't recorded count as being on the monitor the cursor was on
+	fn monitor_of(&self, window: Window) -> Display {
+		match self.window(window) {
+			Some(recorded) => self.display(&recorded.monitor),
+			None => self.active_monitor(),
+		}
+	}
+
+	fn foreground_window(&self) -> Window {
+		self.snapshot.foreground
+	}
+
+	fn cursor_position(&self) -> (i32, i32) {
+		self.snapshot.cursor
+	}
+
+	fn windows(&self) -> Vec<Window> {
+		self.snapshot
+			.windows
+			.iter()
+			.map(|recorded| recorded.window)
+			.collect()
+	}
+
+	fn exists(&self, window: Window) -> bool {
+		self.window(window).is_some()
+	}
+
+	fn is_managed(&self, window: Window) -> bool {
+		self.window(window).is_some_and(|recorded| recorded.managed)
+	}
+
+	fn is_tileable(&self, window: Window) -> bool {
+		self.window(window)
+			.is_some_and(|recorded| recorded.tileable)
+	}
+
+	fn window_properties(&self, window: Window) -> WindowProperties {
+		self.window(window)
+			.map(|recorded| recorded.properties.clone())
+			.unwrap_or_default()
+	}
+
+	fn window_rect(&self, window: Window) -> Rect {
+		self.window(window)
+			.map_or_else(Rect::zero, |recorded| recorded.rect)
+	}
+
+	fn frame_insets(&self, window: Window) -> Insets {
+		self.window(window)
+			.map(|recorded| recorded.insets)
+			.unwrap_or_default()
+	}
+
+	fn window_state(&self, window: Window) -> WindowState {
+		self.window(window)
+			.map_or(WindowState::Normal, |recorded| recorded.state)
+	}
+
+	/// The limits aren't recorded, asking every window for them would slow down each event, so
+	/// the windows can have any size
+	fn size_limits(&self, _: Window) -> SizeLimits {
+		SizeLimits {
+			min_width: 0,
+			min_height: 0,
+			max_width: i32::MAX,
+			max_height: i32::MAX,
+		}
+	}
+}
+
+impl TilingManager {
+	/// Feeds the events recorded in `path` to a new tiling manager that uses `config`
+	///
+	/// The tiling manager sees the desktop as it was recorded and doesn't touch any window, the
+	/// moves it asks for are returned instead, including the ones of undo and redo. Events that
+	/// read or write files are skipped.
+	pub fn replay(
+		mut config: WtmConfig,
+		path: &Path,
+	) -> Result<Vec<ReplayedPlacement>, RecordError> {
+		// Animated windows would only be moved by the frames, which aren't replayed
+		config.animation.enabled = false;
+
+		let mut tm = Self::with_config(config);
+		let mut placements = Vec::new();
+
+		for record in read_recording(path)? {
+			if !is_replayable(&record.event) {
+				continue;
+			}
+
+			tm.desktop = Box::new(SimulatedDesktop {
+				snapshot: record.desktop,
+			});
+
+			for command in tm.update(record.event) {
+				if let Command::MoveWindow(window, rect) = command {
+					placements.push(ReplayedPlacement {
+						time: record.time,
+						window,
+						rect,
+					});
+				}
+			}
+		}
+
+		Ok(placements)
+	}
+}
+
+/// Can `event` be replayed without reading or writing files
+///
+/// Animation frames are skipped too, replayed windows are moved without animations.
+fn is_replayable(event: &Event) -> bool {
+	match event {
+		Event::HotkeyPressed(hotkey) => !matches!(
+			hotkey,
+			HotkeyType::SaveArrangement(_)
+				| HotkeyType::RestoreArrangement(_)
+				| HotkeyType::DryRunArrangement(_)
+		),
+		Event::ConfigChanged | Event::AnimationFrame => false,
+		_ => true,
+	}
+}
+
+/// Reads the records of the recording at `path`
+pub fn read_recording(path: &Path) -> Result<Vec<Record>, RecordError> {
+	let file = File::open(path).map_err(|err| RecordError::Io(path.to_owned(), err))?;
+
+	BufReader::new(file)
+		.lines()
+		.enumerate()
+		.filter(|(_, line)| !matches!(line, Ok(line) if line.trim().is_empty()))
+		.map(|(index, line)| {
+			let line = line.map_err(|err| RecordError::Io(path.to_owned(), err))?;
+
+			serde_json::from_str(&line)
+				.map_err(|err| RecordError::Parse(path.to_owned(), index + 1, err))
+		})
+		.collect()
+}
+
+/// Stores a window as the number of its handle
+pub(crate) mod handle {
+	use super::*;
+
+	pub fn serialize<S: Serializer>(window: &Window, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_u64(window.0 as usize as u64)
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Window, D::Error> {
+		Ok(Window(u64::deserialize(deserializer)? as usize as _))
+	}
+}
+
+/// Stores a rectangle as `[x, y, width, height]`
+pub(crate) mod rect {
+	use super::*;
+
+	pub fn serialize<S: Serializer>(rect: &Rect, serializer: S) -> Result<S::Ok, S::Error> {
+		[rect.x, rect.y, rect.w, rect.h].serialize(serializer)
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Rect, D::Error> {
+		let [x, y, w, h] = <[i32; 4]>::deserialize(deserializer)?;
+
+		Ok(Rect { x, y, w, h })
+	}
+}
+
+/// Stores an invisible border as `[left, top, right, bottom]`
+mod insets {
+	use super::*;
+
+	pub fn serialize<S: Serializer>(insets: &Insets, serializer: S) -> Result<S::Ok, S::Error> {
+		[insets.left, insets.top, insets.right, insets.bottom].serialize(serializer)
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Insets, D::Error> {
+		let [left, top, right, bottom] = <[i32; 4]>::deserialize(deserializer)?;
+
+		Ok(Insets {
+			left,
+			top,
+			right,
+			bottom,
+		})
+	}
+}
+
+impl Error for RecordError {}
+
+impl fmt::Display for RecordError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			RecordError::NoDirectory => write!(f, "there is no config directory"),
+			RecordError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+			RecordError::Parse(path, line, err) => {
+				write!(
+					f,
+					"invalid record in {} on line {}: {}",
+					path.display(),
+					line,
+					err
+				)
+			}
+			RecordError::Serialize(err) => write!(f, "could not convert to json: {}", err),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::{env, process};
+	use winapi::um::winuser::{VK_RETURN, VK_RIGHT};
+
+	fn foreground() -> Window {
+		Window(1 as _)
+	}
+
+	fn snapshot() -> Snapshot {
+		let monitor = Display {
+			name: String::from(r"\\.\DISPLAY1"),
+			rect: Rect {
+				x: 0,
+				y: 0,
+				w: 1920,
+				h: 1080,
+			},
+			work_area: Rect {
+				x: 0,
+				y: 0,
+				w: 1920,
+				h: 1040,
+			},
+		};
+
+		Snapshot {
+			monitor: monitor.name.clone(),
+			cursor: (960, 540),
+			foreground: foreground(),
+			windows: vec![RecordedWindow {
+				window: foreground(),
+				properties: WindowProperties {
+					title: String::from("Notes"),
+					class: String::from("Notepad"),
+					..WindowProperties::default()
+				},
+				rect: Rect {
+					x: 100,
+					y: 100,
+					w: 800,
+					h: 600,
+				},
+				insets: Insets {
+					left: 7,
+					top: 0,
+					right: 7,
+					bottom: 7,
+				},
+				state: WindowState::Normal,
+				monitor: monitor.name.clone(),
+				managed: true,
+				tileable: true,
+			}],
+			monitors: vec![monitor],
+		}
+	}
+
+	/// A recording file that no other test writes to
+	fn recording_path(name: &str) -> PathBuf {
+		env::temp_dir().join(format!("wtm-{}-{}.jsonl", name, process::id()))
+	}
+
+	/// Opening the grid and placing the foreground window in the first tile with the keyboard
+	fn events() -> Vec<Event> {
+		vec![
+			Event::HotkeyPressed(HotkeyType::Main),
+			Event::GridWindow(Window(10 as _)),
+			Event::PreviewWindow(Window(11 as _)),
+			Event::GridKeyDown(VK_RIGHT),
+			Event::GridKeyDown(VK_RETURN),
+		]
+	}
+
+	/// Handles `events` against the simulated desktop while recording them to `path`
+	///
+	/// The windows of the simulated desktop are moved like `execute` would move them. Returns the
+	/// commands of each event.
+	fn record(path: &Path, events: &[Event]) -> Vec<Vec<Command>> {
+		let mut desktop = SimulatedDesktop {
+			snapshot: snapshot(),
+		};
+		let mut recorder = Recorder {
+			file: LineWriter::new(File::create(path).unwrap()),
+			start: Instant::now(),
+		};
+		let mut tm = TilingManager::new(WtmConfig::default(), Box::new(desktop.clone()));
+
+		events
+			.iter()
+			.map(|event| {
+				recorder.record(*event, &desktop).unwrap();
+				tm.desktop = Box::new(desktop.clone());
+				let commands = tm.update(*event);
+
+				for command in &commands {
+					if let Command::MoveWindow(window, rect) = *command {
+						for recorded in &mut desktop.snapshot.windows {
+							if recorded.window == window {
+								recorded.rect = rect;
+							}
+						}
+					}
+				}
+
+				commands
+			})
+			.collect()
+	}
+
+	/// The windows and rects of the moves in `commands`
+	fn moves(commands: Vec<Vec<Command>>) -> Vec<(Window, Rect)> {
+		commands
+			.into_iter()
+			.flatten()
+			.filter_map(|command| match command {
+				Command::MoveWindow(window, rect) => Some((window, rect)),
+				_ => None,
+			})
+			.collect()
+	}
+
+	/// The windows and rects of replayed placements
+	fn replayed_moves(placements: &[ReplayedPlacement]) -> Vec<(Window, Rect)> {
+		placements
+			.iter()
+			.map(|placement| (placement.window, placement.rect))
+			.collect()
+	}
+
+	#[test]
+	fn recordings_are_read_back_as_they_were_written() {
+		let path = recording_path("round-trip");
+		record(&path, &events());
+
+		let records = read_recording(&path).unwrap();
+		fs::remove_file(&path).unwrap();
+
+		let recorded: Vec<Event> = records.iter().map(|record| record.event).collect();
+		assert_eq!(recorded, events());
+		assert!(records.iter().all(|record| record.desktop == snapshot()));
+		assert!(records.windows(2).all(|pair| pair[0].time <= pair[1].time));
+	}
+
+	#[test]
+	fn replaying_a_recording_emits_the_same_commands() {
+		let path = recording_path("replay");
+		let commands = record(&path, &events());
+
+		let mut tm = TilingManager::new(
+			WtmConfig::default(),
+			Box::new(SimulatedDesktop {
+				snapshot: snapshot(),
+			}),
+		);
+		let replayed: Vec<Vec<Command>> = read_recording(&path)
+			.unwrap()
+			.into_iter()
+			.map(|record| {
+				tm.desktop = Box::new(SimulatedDesktop {
+					snapshot: record.desktop,
+				});
+				tm.update(record.event)
+			})
+			.collect();
+		assert_eq!(replayed, commands);
+
+		let placements = TilingManager::replay(WtmConfig::default(), &path).unwrap();
+		fs::remove_file(&path).unwrap();
+
+		let placed = moves(commands);
+		assert_eq!(placed.len(), 1);
+		assert_eq!(replayed_moves(&placements), placed);
+	}
+
+	#[test]
+	fn replayed_undo_and_redo_move_the_window_back_and_forth() {
+		let path = recording_path("undo");
+		let mut events = events();
+		events.extend(vec![
+			Event::HotkeyPressed(HotkeyType::Undo),
+			Event::HotkeyPressed(HotkeyType::Redo),
+		]);
+		let commands = record(&path, &events);
+
+		let placements = TilingManager::replay(WtmConfig::default(), &path).unwrap();
+		fs::remove_file(&path).unwrap();
+
+		let original = snapshot().windows[0].rect;
+		let placed = moves(commands);
+		assert_eq!(placed.len(), 3);
+		assert_eq!(placed[1], (foreground(), original));
+		assert_eq!(placed[2], placed[0]);
+		assert_eq!(replayed_moves(&placements), placed);
+	}
+
+	#[test]
+	fn invalid_lines_are_reported_with_their_number() {
+		let path = recording_path("invalid");
+		record(&path, &events()[..1]);
+		fs::OpenOptions::new()
+			.append(true)
+			.open(&path)
+			.and_then(|mut file| writeln!(file, "\n{{\"time\": 3}}"))
+			.unwrap();
+
+		let result = read_recording(&path);
+		fs::remove_file(&path).unwrap();
+
+		assert!(matches!(result, Err(RecordError::Parse(_, 3, _))));
+	}
+}
@@ -0,0 +1,655 @@
+//! Rules module
+
+use crate::{
+	config::MergedCell,
+	snap::{Region, SnapTarget},
+	zones::Zone,
+};
+use regex::{Regex, RegexBuilder};
+use serde::{de, ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, path::Path, str::FromStr};
+use winapi::um::winuser::{
+	WS_BORDER, WS_CAPTION, WS_CHILD, WS_DLGFRAME, WS_EX_APPWINDOW, WS_EX_DLGMODALFRAME,
+	WS_EX_LAYERED, WS_EX_NOACTIVATE, WS_EX_TOOLWINDOW, WS_EX_TOPMOST, WS_MAXIMIZEBOX,
+	WS_MINIMIZEBOX, WS_POPUP, WS_SYSMENU, WS_THICKFRAME,
+};
+use winsapi::Window;
+
+/// Names of the style flags that can be used in the config, and if they are extended styles
+const STYLE_FLAGS: &[(&str, u32, bool)] = &[
+	("caption", WS_CAPTION, false),
+	("popup", WS_POPUP, false),
+	("child", WS_CHILD, false),
+	("border", WS_BORDER, false),
+	("dialog-frame", WS_DLGFRAME, false),
+	("sizable", WS_THICKFRAME, false),
+	("system-menu", WS_SYSMENU, false),
+	("minimize-box", WS_MINIMIZEBOX, false),
+	("maximize-box", WS_MAXIMIZEBOX, false),
+	("tool-window", WS_EX_TOOLWINDOW, true),
+	("app-window", WS_EX_APPWINDOW, true),
+	("modal-frame", WS_EX_DLGMODALFRAME, true),
+	("topmost", WS_EX_TOPMOST, true),
+	("layered", WS_EX_LAYERED, true),
+	("no-activate", WS_EX_NOACTIVATE, true),
+];
+
+/// What rules know about a window
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WindowProperties {
+	/// Title of the window
+	pub title: String,
+	/// Name of the window's class
+	pub class: String,
+	/// File name of the executable that created the window, like `slack.exe`
+	pub executable: String,
+	/// Style flags
+	pub styles: u32,
+	/// Extended style flags
+	pub extended_styles: u32,
+}
+
+/// Changes the behavior of wtm for the windows it matches
+///
+/// Every matcher that is set has to match. Only the first matching rule is used.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+	/// Shown when explaining which rule matched a window
+	pub name: Option<String>,
+	/// Matches the title of the window
+	pub title: Option<Pattern>,
+	/// Matches the class name of the window
+	pub class: Option<Pattern>,
+	/// Matches the file name of the window's executable
+	pub executable: Option<Pattern>,
+	/// Style flags the window has to have, or not have when prefixed with `!`
+	#[serde(default)]
+	pub style: Vec<StyleFlag>,
+	/// wtm leaves the window alone
+	#[serde(default)]
+	pub ignore: bool,
+	/// The window is never tiled
+	#[serde(default)]
+	pub float: bool,
+	/// Moves the window to a named part of the monitor
+	pub region: Option<Region>,
+	/// Moves the window to tiles of the monitor's grid
+	pub cell: Option<MergedCell>,
+	/// Moves the window to a freely placed zone
+	pub zone: Option<Zone>,
+	/// Moves the window to a monitor, counted from the left starting at 1
+	pub monitor: Option<usize>,
+	/// Moves the window to a workspace of its monitor, starting at 1
+	pub workspace: Option<usize>,
+	/// Opacity of the window, from 1 to 255
+	pub opacity: Option<u8>,
+	/// The invisible border on the left, top, right and bottom of the window, for windows that
+	/// draw their own frame
+	pub frame: Option<[i32; 4]>,
+}
+
+/// A pattern text is matched against
+///
+/// Written as a glob like `"*.exe"`, or as `{ exact = "..." }`, `{ glob = "..." }` or
+/// `{ regex = "..." }`. Exact text and globs ignore the case.
+#[derive(Debug, Clone)]
+pub struct Pattern {
+	kind: PatternKind,
+	source: String,
+	regex: Regex,
+}
+
+/// How the source of a pattern is interpreted
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PatternKind {
+	Exact,
+	Glob,
+	Regex,
+}
+
+/// A style flag a window has to have, or not have
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StyleFlag {
+	name: &'static str,
+	flag: u32,
+	extended: bool,
+	negated: bool,
+}
+
+/// Which rules matched a window, and why the others didn't
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+	/// The properties the rules were matched against
+	pub properties: WindowProperties,
+	/// Each rule with the result of each of its matchers
+	pub rules: Vec<(String, Vec<(String, bool)>)>,
+	/// Index of the rule that is used
+	pub matched: Option<usize>,
+}
+
+impl WindowProperties {
+	/// Reads the properties of `window`
+	pub fn of(window: Window) -> Self {
+		let info = window.info();
+
+		Self {
+			title: window.title(),
+			class: window.class_name(),
+			executable: window
+				.executable()
+				.and_then(|path| {
+					Path::new(&path)
+						.file_name()
+						.map(|name| name.to_string_lossy().into_owned())
+				})
+				.unwrap_or_default(),
+			styles: info.styles,
+			extended_styles: info.extended_styles,
+		}
+	}
+}
+
+impl Rule {
+	/// Where the window is moved to
+	///
+	/// Only valid if at most one of `region`, `cell` and `zone` is set.
+	pub fn target(&self) -> Option<SnapTarget> {
+		match (self.region, self.cell, self.zone) {
+			(Some(region), _, _) => Some(SnapTarget::Region(region)),
+			(None, Some(cell), _) => Some(SnapTarget::Cell(cell)),
+			(None, None, Some(zone)) => Some(SnapTarget::Zone(zone)),
+			(None, None, None) => None,
+		}
+	}
+
+	/// Does the rule match a window with `properties`
+	pub fn matches(&self, properties: &WindowProperties) -> bool {
+		self.checks(properties).iter().all(|(_, matched)| *matched)
+	}
+
+	/// Has the rule any matcher at all
+	pub fn has_matchers(&self) -> bool {
+		self.title.is_some()
+			|| self.class.is_some()
+			|| self.executable.is_some()
+			|| !self.style.is_empty()
+	}
+
+	/// The result of each matcher of the rule
+	fn checks(&self, properties: &WindowProperties) -> Vec<(String, bool)> {
+		let patterns = [
+			("title", &self.title, &properties.title),
+			("class", &self.class, &properties.class),
+			("executable", &self.executable, &properties.executable),
+		];
+
+		let mut checks: Vec<_> = patterns
+			.iter()
+			.filter_map(|(name, pattern, text)| {
+				pattern
+					.as_ref()
+					.map(|pattern| (name.to_string(), pattern.matches(text)))
+			})
+			.collect();
+
+		checks.extend(
+			self.style
+				.iter()
+				.map(|flag| (flag.to_string(), flag.matches(properties))),
+		);
+
+		checks
+	}
+
+	/// The name of the rule, or its position if it has none
+	fn display_name(&self, index: usize) -> String {
+		match &self.name {
+			Some(name) => name.clone(),
+			None => format!("rules[{}]", index),
+		}
+	}
+}
+
+/// The first rule that matches a window with `properties`
+pub fn matching_rule<'a>(rules: &'a [Rule], properties: &WindowProperties) -> Option<&'a Rule> {
+	rules.iter().find(|rule| rule.matches(properties))
+}
+
+/// Matches every rule against `properties` and records the results
+pub fn explain(rules: &[Rule], properties: WindowProperties) -> Explanation {
+	Explanation {
+		rules: rules
+			.iter()
+			.enumerate()
+			.map(|(index, rule)| (rule.display_name(index), rule.checks(&properties)))
+			.collect(),
+		matched: rules.iter().position(|rule| rule.matches(&properties)),
+		properties,
+	}
+}
+
+impl Pattern {
+	/// Does `text` match the pattern
+	pub fn matches(&self, text: &str) -> bool {
+		self.regex.is_match(text)
+	}
+
+	/// A pattern that matches exactly `text`, ignoring the case
+	pub fn exact(text: &str) -> Self {
+		Self::new(PatternKind::Exact, text.to_owned()).expect("escaped text is a valid regex")
+	}
+
+	fn new(kind: PatternKind, source: String) -> Result<Self, String> {
+		let expression = match kind {
+			PatternKind::Exact => format!("^{}$", regex::escape(&source)),
+			PatternKind::Glob => {
+				let parts: Vec<_> = source
+					.split('*')
+					.map(|part| {
+						part.split('?')
+							.map(regex::escape)
+							.collect::<Vec<_>>()
+							.join(".")
+					})
+					.collect();
+
+				format!("^{}$", parts.join(".*"))
+			}
+			PatternKind::Regex => source.clone(),
+		};
+
+		let regex = RegexBuilder::new(&expression)
+			.case_insensitive(kind != PatternKind::Regex)
+			.build()
+			.map_err(|err| format!("invalid pattern `{}`: {}", source, err))?;
+
+		Ok(Self {
+			kind,
+			source,
+			regex,
+		})
+	}
+}
+
+impl PartialEq for Pattern {
+	fn eq(&self, other: &Pattern) -> bool {
+		self.kind == other.kind && self.source == other.source
+	}
+}
+
+impl fmt::Display for Pattern {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self.kind {
+			PatternKind::Exact => write!(f, "exact `{}`", self.source),
+			PatternKind::Glob => write!(f, "glob `{}`", self.source),
+			PatternKind::Regex => write!(f, "regex `{}`", self.source),
+		}
+	}
+}
+
+impl Serialize for Pattern {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		let key = match self.kind {
+			PatternKind::Exact => "exact",
+			PatternKind::Glob => return serializer.serialize_str(&self.source),
+			PatternKind::Regex => "regex",
+		};
+
+		let mut map = serializer.serialize_map(Some(1))?;
+		map.serialize_entry(key, &self.source)?;
+		map.end()
+	}
+}
+
+impl<'de> Deserialize<'de> for Pattern {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		#[derive(Deserialize)]
+		#[serde(untagged)]
+		enum Raw {
+			Glob(String),
+			Table {
+				exact: Option<String>,
+				glob: Option<String>,
+				regex: Option<String>,
+			},
+		}
+
+		let (kind, source) = match Raw::deserialize(deserializer)? {
+			Raw::Glob(source) => (PatternKind::Glob, source),
+			Raw::Table {
+				exact: Some(source),
+				glob: None,
+				regex: None,
+			} => (PatternKind::Exact, source),
+			Raw::Table {
+				exact: None,
+				glob: Some(source),
+				regex: None,
+			} => (PatternKind::Glob, source),
+			Raw::Table {
+				exact: None,
+				glob: None,
+				regex: Some(source),
+			} => (PatternKind::Regex, source),
+			Raw::Table { .. } => {
+				return Err(de::Error::custom(
+					"a pattern needs exactly one of `exact`, `glob` and `regex`",
+				))
+			}
+		};
+
+		Pattern::new(kind, source).map_err(de::Error::custom)
+	}
+}
+
+impl StyleFlag {
+	/// Does a window with `properties` have, or not have, the flag
+	pub fn matches(self, properties: &WindowProperties) -> bool {
+		let styles = if self.extended {
+			properties.extended_styles
+		} else {
+			properties.styles
+		};
+
+		(styles & self.flag == self.flag) != self.negated
+	}
+}
+
+impl FromStr for StyleFlag {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let s = s.trim();
+		let (negated, name) = match s.strip_prefix('!') {
+			Some(name) => (true, name.trim()),
+			None => (false, s),
+		};
+
+		STYLE_FLAGS
+			.iter()
+			.find(|(flag_name, _, _)| flag_name.eq_ignore_ascii_case(name))
+			.map(|(name, flag, extended)| StyleFlag {
+				name,
+				flag: *flag,
+				extended: *extended,
+				negated,
+			})
+			.ok_or_else(|| format!("unknown style flag `{}`", s))
+	}
+}
+
+impl fmt::Display for StyleFlag {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.negated {
+			f.write_str("!")?;
+		}
+
+		f.write_str(self.name)
+	}
+}
+
+impl<'de> Deserialize<'de> for StyleFlag {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		String::deserialize(deserializer)?
+			.parse()
+			.map_err(de::Error::custom)
+	}
+}
+
+impl fmt::Display for Explanation {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		writeln!(
+			f,
+			"title `{}`, class `{}`, executable `{}`, styles {:#010x}, extended styles {:#010x}",
+			self.properties.title,
+			self.properties.class,
+			self.properties.executable,
+			self.properties.styles,
+			self.properties.extended_styles
+		)?;
+
+		for (index, (name, checks)) in self.rules.iter().enumerate() {
+			let result = match self.matched {
+				Some(matched) if matched == index => "used",
+				_ if checks.iter().all(|(_, matched)| *matched) => {
+					"matches, but an earlier rule is used"
+				}
+				_ => "no match",
+			};
+
+			let checks: Vec<_> = checks
+				.iter()
+				.map(|(matcher, matched)| {
+					let result = if *matched { "matches" } else { "differs" };
+					format!("{} {}", matcher, result)
+				})
+				.collect();
+
+			writeln!(f, "  {}: {} ({})", name, result, checks.join(", "))?;
+		}
+
+		if self.matched.is_none() {
+			writeln!(f, "  no rule matched")?;
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// A pattern as it is written in the config, like `"*.exe"` or `{ regex = "..." }`
+	fn pattern(value: &str) -> Result<Pattern, toml::de::Error> {
+		#[derive(Deserialize)]
+		struct Matcher {
+			pattern: Pattern,
+		}
+
+		toml::from_str::<Matcher>(&format!("pattern = {}", value)).map(|matcher| matcher.pattern)
+	}
+
+	fn rule(toml: &str) -> Rule {
+		toml::from_str(toml).unwrap()
+	}
+
+	/// The main window of Slack
+	fn slack() -> WindowProperties {
+		WindowProperties {
+			title: String::from("Inbox - Slack"),
+			class: String::from("Chrome_WidgetWin_1"),
+			executable: String::from("slack.exe"),
+			styles: WS_CAPTION | WS_THICKFRAME,
+			extended_styles: WS_EX_APPWINDOW,
+		}
+	}
+
+	#[test]
+	fn globs_match_the_whole_text_ignoring_the_case() {
+		let glob = pattern(r#""*.exe""#).unwrap();
+
+		assert!(glob.matches("slack.exe"));
+		assert!(glob.matches("SLACK.EXE"));
+		assert!(glob.matches(".exe"));
+		assert!(!glob.matches("slack.exe.bak"));
+		assert!(!glob.matches("slack.exe "));
+	}
+
+	#[test]
+	fn question_marks_match_a_single_character() {
+		let glob = pattern(r#""Untitled - ?""#).unwrap();
+
+		assert!(glob.matches("Untitled - 1"));
+		assert!(!glob.matches("Untitled - 12"));
+		assert!(!glob.matches("Untitled - "));
+	}
+
+	#[test]
+	fn globs_only_treat_stars_and_question_marks_as_wildcards() {
+		let glob = pattern(r#""[draft] (1)+*""#).unwrap();
+
+		assert!(glob.matches("[draft] (1)+ notes"));
+		assert!(!glob.matches("d (1)+ notes"));
+		assert!(!glob.matches("[draft] 11 notes"));
+	}
+
+	#[test]
+	fn exact_patterns_match_only_the_whole_text() {
+		let exact = pattern(r#"{ exact = "Notes*" }"#).unwrap();
+
+		assert!(exact.matches("notes*"));
+		assert!(!exact.matches("Notes - 2"));
+		assert_eq!(exact.to_string(), "exact `Notes*`");
+	}
+
+	#[test]
+	fn regexes_match_anywhere_and_keep_the_case() {
+		let regex = pattern(r#"{ regex = "ack$" }"#).unwrap();
+
+		assert!(regex.matches("Inbox - Slack"));
+		assert!(!regex.matches("Inbox - SLACK"));
+		assert!(!regex.matches("Slack - Inbox"));
+		assert_eq!(regex.to_string(), "regex `ack$`");
+	}
+
+	#[test]
+	fn patterns_are_read_as_globs_exact_text_or_regexes() {
+		assert_eq!(pattern(r#""*.exe""#).unwrap().kind, PatternKind::Glob);
+		assert_eq!(
+			pattern(r#"{ glob = "*.exe" }"#).unwrap(),
+			pattern(r#""*.exe""#).unwrap()
+		);
+		assert_eq!(
+			pattern(r#"{ exact = "a" }"#).unwrap().kind,
+			PatternKind::Exact
+		);
+		assert_eq!(
+			pattern(r#"{ regex = "a" }"#).unwrap().kind,
+			PatternKind::Regex
+		);
+	}
+
+	#[test]
+	fn patterns_need_exactly_one_valid_kind() {
+		assert!(pattern("{}").is_err());
+		assert!(pattern(r#"{ exact = "a", glob = "a" }"#).is_err());
+		assert!(pattern(r#"{ regex = "(" }"#).is_err());
+		assert!(pattern("42").is_err());
+	}
+
+	#[test]
+	fn negated_style_flags_match_windows_without_the_flag() {
+		let caption: StyleFlag = "caption".parse().unwrap();
+		let no_caption: StyleFlag = "!caption".parse().unwrap();
+		let app_window: StyleFlag = " ! APP-WINDOW ".parse().unwrap();
+
+		assert!(caption.matches(&slack()));
+		assert!(!no_caption.matches(&slack()));
+		assert!(!app_window.matches(&slack()));
+		assert!(app_window.matches(&WindowProperties::default()));
+		assert_eq!(no_caption.to_string(), "!caption");
+	}
+
+	#[test]
+	fn style_flags_have_to_be_known() {
+		assert!("sticky".parse::<StyleFlag>().is_err());
+		assert!("!".parse::<StyleFlag>().is_err());
+	}
+
+	#[test]
+	fn every_matcher_of_a_rule_has_to_match() {
+		let rule = rule(
+			r#"
+			executable = "slack.exe"
+			title = "Inbox*"
+			style = ["sizable"]
+			"#,
+		);
+
+		assert!(rule.matches(&slack()));
+		assert!(!rule.matches(&WindowProperties {
+			title: String::from("Huddle"),
+			..slack()
+		}));
+	}
+
+	#[test]
+	fn the_first_matching_rule_is_used() {
+		let rules = vec![
+			rule(r#"title = "Huddle*""#),
+			rule(
+				r#"
+				name = "Slack"
+				executable = "slack.exe"
+				"#,
+			),
+			rule(
+				r#"
+				name = "Electron"
+				class = "Chrome_*"
+				"#,
+			),
+		];
+
+		let matched = matching_rule(&rules, &slack()).unwrap();
+		assert_eq!(matched.name.as_deref(), Some("Slack"));
+
+		assert_eq!(matching_rule(&rules[..1], &slack()), None);
+	}
+
+	#[test]
+	fn explanations_list_the_result_of_every_matcher() {
+		let rules = vec![
+			rule(
+				r#"
+				name = "Dialogs"
+				style = ["!caption"]
+				"#,
+			),
+			rule(
+				r#"
+				executable = "slack.exe"
+				title = { regex = "^Inbox" }
+				"#,
+			),
+			rule(
+				r#"
+				name = "Electron"
+				class = "chrome_*"
+				"#,
+			),
+		];
+
+		let explanation = explain(&rules, slack());
+		assert_eq!(explanation.matched, Some(1));
+
+		assert_eq!(
+			explanation.to_string(),
+			"title `Inbox - Slack`, class `Chrome_WidgetWin_1`, executable `slack.exe`, styles \
+			 0x00c40000, extended styles 0x00040000\n  Dialogs: no match (!caption differs)\n  \
+			 rules[1]: used (title matches, executable matches)\n  Electron: matches, but an \
+			 earlier rule is used (class matches)\n"
+		);
+	}
+
+	#[test]
+	fn explanations_say_when_no_rule_matched() {
+		let explanation = explain(&[rule(r#"class = "Notepad""#)], slack());
+
+		assert_eq!(explanation.matched, None);
+		assert!(explanation
+			.to_string()
+			.ends_with("  rules[0]: no match (class differs)\n  no rule matched\n"));
+	}
+}
@@ -0,0 +1,541 @@
+//! Snap module
+
+use crate::{config::MergedCell, grid::Direction, zones::Zone};
+use serde::{de, Deserialize, Deserializer};
+use std::{fmt, str::FromStr};
+use winsapi::Rect;
+
+/// Edges closer than this to the border of the work area are treated as touching it
+const EDGE_EPSILON: f32 = 0.001;
+
+/// Where a snap command moves the foreground window
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnapTarget {
+	/// A named part of the work area
+	Region(Region),
+	/// Tiles of the grid
+	Cell(MergedCell),
+	/// A freely placed zone
+	Zone(Zone),
+}
+
+/// What pressing the hotkey of a region does
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SnapStep {
+	/// Snap to the region on the window's monitor, the step of the cycle is kept for the next press
+	Stay(Region, usize),
+	/// Snap to the region on the monitor next to the window's monitor
+	Monitor(Direction, Region),
+}
+
+/// The last snap hotkey that was used on a window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SnapCycle {
+	/// Index of the snap hotkey
+	pub hotkey: usize,
+	/// Step of the cycle the window is at
+	pub step: usize,
+	/// The area the window was placed in
+	pub area: Rect,
+}
+
+/// The axis a region cycles its size along
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Axis {
+	Horizontal,
+	Vertical,
+}
+
+/// The edge of the work area a region keeps while its size cycles
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Anchor {
+	Start,
+	End,
+	Center,
+}
+
+/// A part of the work area, as fractions of its size
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Region {
+	/// Distance from the left edge
+	pub x: f32,
+	/// Distance from the top edge
+	pub y: f32,
+	/// Width of the region
+	pub width: f32,
+	/// Height of the region
+	pub height: f32,
+}
+
+/// Names of the regions that can be used in the config
+const REGIONS: &[(&str, Region)] = &[
+	("maximize", Region::new(0.0, 0.0, 1.0, 1.0)),
+	("left-half", Region::new(0.0, 0.0, 0.5, 1.0)),
+	("right-half", Region::new(0.5, 0.0, 0.5, 1.0)),
+	("top-half", Region::new(0.0, 0.0, 1.0, 0.5)),
+	("bottom-half", Region::new(0.0, 0.5, 1.0, 0.5)),
+	("center-half", Region::new(0.25, 0.0, 0.5, 1.0)),
+	("top-left", Region::new(0.0, 0.0, 0.5, 0.5)),
+	("top-right", Region::new(0.5, 0.0, 0.5, 0.5)),
+	("bottom-left", Region::new(0.0, 0.5, 0.5, 0.5)),
+	("bottom-right", Region::new(0.5, 0.5, 0.5, 0.5)),
+	("left-third", Region::new(0.0, 0.0, 1.0 / 3.0, 1.0)),
+	("center-third", Region::new(1.0 / 3.0, 0.0, 1.0 / 3.0, 1.0)),
+	("right-third", Region::new(2.0 / 3.0, 0.0, 1.0 / 3.0, 1.0)),
+	("left-two-thirds", Region::new(0.0, 0.0, 2.0 / 3.0, 1.0)),
+	(
+		"center-two-thirds",
+		Region::new(1.0 / 6.0, 0.0, 2.0 / 3.0, 1.0),
+	),
+	(
+		"right-two-thirds",
+		Region::new(1.0 / 3.0, 0.0, 2.0 / 3.0, 1.0),
+	),
+];
+
+impl Region {
+	/// Creates a region from fractions of the work area
+	pub const fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+		Self {
+			x,
+			y,
+			width,
+			height,
+		}
+	}
+
+	/// The region as an absolute rectangle inside of `work_area`
+	///
+	/// `border_margins` are kept to the edges of the work area and `zone_margins` between the
+	/// region and the rest of the work area, so regions line up with the zones of a grid.
+	pub fn resolve(self, work_area: Rect, border_margins: i32, zone_margins: i32) -> Rect {
+		let (x, w) = resolve_axis(
+			work_area.x,
+			work_area.w,
+			self.x,
+			self.width,
+			border_margins,
+			zone_margins,
+		);
+		let (y, h) = resolve_axis(
+			work_area.y,
+			work_area.h,
+			self.y,
+			self.height,
+			border_margins,
+			zone_margins,
+		);
+
+		Rect { x, y, w, h }
+	}
+
+	/// Is the region inside of the work area and not empty
+	pub fn is_valid(self) -> bool {
+		let axis_is_valid = |start: f32, size: f32| {
+			start >= 0.0 && size > 0.0 && start + size <= 1.0 + EDGE_EPSILON
+		};
+
+		axis_is_valid(self.x, self.width) && axis_is_valid(self.y, self.height)
+	}
+
+	/// The region after its hotkey was pressed again
+	///
+	/// Regions that don't fill the work area cycle through `ratios` along their width, or their
+	/// height if they are as wide as the work area. The cycle starts at the region's own size.
+	/// Once every size was used, a region at the edge of the work area moves to the monitor
+	/// beyond that edge, mirrored to the opposite edge. Centered regions start over instead.
+	///
+	/// `previous_step` is the step the window is at, or `None` for the first press.
+	pub fn cycle(self, ratios: &[f32], previous_step: Option<usize>) -> SnapStep {
+		let axis = if self.width < 1.0 - EDGE_EPSILON {
+			Axis::Horizontal
+		} else if self.height < 1.0 - EDGE_EPSILON {
+			Axis::Vertical
+		} else {
+			return SnapStep::Stay(self, 0);
+		};
+
+		let (start, size) = match axis {
+			Axis::Horizontal => (self.x, self.width),
+			Axis::Vertical => (self.y, self.height),
+		};
+
+		let anchor = if start < EDGE_EPSILON {
+			Anchor::Start
+		} else if start + size > 1.0 - EDGE_EPSILON {
+			Anchor::End
+		} else {
+			Anchor::Center
+		};
+
+		let sizes = cycle_sizes(size, ratios);
+		let step = match previous_step {
+			Some(step) => step + 1,
+			None => 0,
+		};
+
+		let resize = |anchor: Anchor, new_size: f32| {
+			let new_start = match anchor {
+				Anchor::Start => 0.0,
+				Anchor::End => 1.0 - new_size,
+				Anchor::Center => (start + (size - new_size) / 2.0)
+					.max(0.0)
+					.min(1.0 - new_size),
+			};
+
+			match axis {
+				Axis::Horizontal => Region::new(new_start, self.y, new_size, self.height),
+				Axis::Vertical => Region::new(self.x, new_start, self.width, new_size),
+			}
+		};
+
+		if let Some(&new_size) = sizes.get(step) {
+			return SnapStep::Stay(resize(anchor, new_size), step);
+		}
+
+		match (anchor, axis) {
+			(Anchor::Start, Axis::Horizontal) => {
+				SnapStep::Monitor(Direction::Left, resize(Anchor::End, size))
+			}
+			(Anchor::Start, Axis::Vertical) => {
+				SnapStep::Monitor(Direction::Up, resize(Anchor::End, size))
+			}
+			(Anchor::End, Axis::Horizontal) => {
+				SnapStep::Monitor(Direction::Right, resize(Anchor::Start, size))
+			}
+			(Anchor::End, Axis::Vertical) => {
+				SnapStep::Monitor(Direction::Down, resize(Anchor::Start, size))
+			}
+			(Anchor::Center, _) => SnapStep::Stay(self, 0),
+		}
+	}
+}
+
+impl SnapCycle {
+	/// The step to continue the cycle from when `hotkey` is pressed on a window that was placed
+	/// in `area`
+	///
+	/// The cycle only continues if the same hotkey was used last and the window hasn't been moved
+	/// since.
+	pub fn previous_step(&self, hotkey: usize, area: Rect) -> Option<usize> {
+		if self.hotkey == hotkey && self.area == area {
+			Some(self.step)
+		} else {
+			None
+		}
+	}
+}
+
+/// The sizes a region of `size` cycles through, starting with `size`
+fn cycle_sizes(size: f32, ratios: &[f32]) -> Vec<f32> {
+	match ratios
+		.iter()
+		.position(|ratio| (ratio - size).abs() < EDGE_EPSILON)
+	{
+		Some(position) => ratios[position..]
+			.iter()
+			.chain(&ratios[..position])
+			.copied()
+			.collect(),
+		None => Some(size)
+			.into_iter()
+			.chain(ratios.iter().copied())
+			.collect(),
+	}
+}
+
+/// Resolves one axis of a region, returns the start and the length
+fn resolve_axis(
+	start: i32,
+	length: i32,
+	from: f32,
+	size: f32,
+	border_margins: i32,
+	zone_margins: i32,
+) -> (i32, i32) {
+	let available = (length - border_margins * 2) as f32;
+	let to = from + size;
+
+	let mut first = start + border_margins + (available * from).round() as i32;
+	let mut last = start + border_margins + (available * to).round() as i32;
+
+	// Only edges inside of the work area are shared with other zones
+	if from > EDGE_EPSILON {
+		first += zone_margins / 2;
+	}
+
+	if to < 1.0 - EDGE_EPSILON {
+		last -= zone_margins - zone_margins / 2;
+	}
+
+	(first, last - first)
+}
+
+impl FromStr for Region {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		REGIONS
+			.iter()
+			.find(|(name, _)| name.eq_ignore_ascii_case(s.trim()))
+			.map(|(_, region)| *region)
+			.ok_or_else(|| format!("unknown region `{}`", s))
+	}
+}
+
+impl fmt::Display for Region {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match REGIONS.iter().find(|(_, region)| region == self) {
+			Some((name, _)) => f.write_str(name),
+			None => write!(f, "{}x{} at {},{}", self.width, self.height, self.x, self.y),
+		}
+	}
+}
+
+impl<'de> Deserialize<'de> for Region {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		String::deserialize(deserializer)?
+			.parse()
+			.map_err(de::Error::custom)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const RATIOS: &[f32] = &[0.5, 2.0 / 3.0, 1.0 / 3.0];
+
+	fn region(name: &str) -> Region {
+		name.parse().unwrap()
+	}
+
+	fn assert_close(actual: Region, expected: Region) {
+		let close = |a: f32, b: f32| (a - b).abs() < EDGE_EPSILON;
+
+		assert!(
+			close(actual.x, expected.x)
+				&& close(actual.y, expected.y)
+				&& close(actual.width, expected.width)
+				&& close(actual.height, expected.height),
+			"{:?} != {:?}",
+			actual,
+			expected
+		);
+	}
+
+	fn stay(step: SnapStep, expected: Region, expected_step: usize) {
+		match step {
+			SnapStep::Stay(region, step) => {
+				assert_close(region, expected);
+				assert_eq!(step, expected_step);
+			}
+			other => panic!("expected to stay, got {:?}", other),
+		}
+	}
+
+	fn monitor(step: SnapStep, expected_direction: Direction, expected: Region) {
+		match step {
+			SnapStep::Monitor(direction, region) => {
+				assert_eq!(direction, expected_direction);
+				assert_close(region, expected);
+			}
+			other => panic!("expected to change the monitor, got {:?}", other),
+		}
+	}
+
+	#[test]
+	fn sizes_start_at_the_size_of_the_region() {
+		assert_eq!(cycle_sizes(0.5, RATIOS), vec![0.5, 2.0 / 3.0, 1.0 / 3.0]);
+		assert_eq!(
+			cycle_sizes(2.0 / 3.0, RATIOS),
+			vec![2.0 / 3.0, 1.0 / 3.0, 0.5]
+		);
+		assert_eq!(
+			cycle_sizes(0.25, RATIOS),
+			vec![0.25, 0.5, 2.0 / 3.0, 1.0 / 3.0]
+		);
+		assert_eq!(cycle_sizes(0.25, &[]), vec![0.25]);
+	}
+
+	#[test]
+	fn regions_at_the_left_edge_grow_and_shrink_then_move_left() {
+		let left = region("left-half");
+
+		stay(left.cycle(RATIOS, None), left, 0);
+		stay(left.cycle(RATIOS, Some(0)), region("left-two-thirds"), 1);
+		stay(left.cycle(RATIOS, Some(1)), region("left-third"), 2);
+		monitor(
+			left.cycle(RATIOS, Some(2)),
+			Direction::Left,
+			region("right-half"),
+		);
+	}
+
+	#[test]
+	fn regions_at_the_right_edge_keep_it_then_move_right() {
+		let right = region("right-half");
+
+		stay(right.cycle(RATIOS, Some(0)), region("right-two-thirds"), 1);
+		stay(right.cycle(RATIOS, Some(1)), region("right-third"), 2);
+		monitor(
+			right.cycle(RATIOS, Some(2)),
+			Direction::Right,
+			region("left-half"),
+		);
+	}
+
+	#[test]
+	fn full_width_regions_cycle_their_height() {
+		let top = region("top-half");
+		let bottom = region("bottom-half");
+
+		stay(
+			top.cycle(RATIOS, Some(0)),
+			Region::new(0.0, 0.0, 1.0, 2.0 / 3.0),
+			1,
+		);
+		monitor(top.cycle(RATIOS, Some(2)), Direction::Up, bottom);
+
+		stay(
+			bottom.cycle(RATIOS, Some(1)),
+			Region::new(0.0, 2.0 / 3.0, 1.0, 1.0 / 3.0),
+			2,
+		);
+		monitor(bottom.cycle(RATIOS, Some(2)), Direction::Down, top);
+	}
+
+	#[test]
+	fn centered_regions_start_over() {
+		let center = region("center-half");
+
+		stay(center.cycle(RATIOS, None), center, 0);
+		stay(
+			center.cycle(RATIOS, Some(0)),
+			region("center-two-thirds"),
+			1,
+		);
+		stay(center.cycle(RATIOS, Some(1)), region("center-third"), 2);
+		stay(center.cycle(RATIOS, Some(2)), center, 0);
+	}
+
+	#[test]
+	fn maximized_regions_never_change() {
+		let maximize = region("maximize");
+
+		stay(maximize.cycle(RATIOS, None), maximize, 0);
+		stay(maximize.cycle(RATIOS, Some(0)), maximize, 0);
+	}
+
+	#[test]
+	fn sizes_that_are_not_ratios_come_first() {
+		let quarter = Region::new(0.0, 0.0, 0.25, 1.0);
+
+		stay(quarter.cycle(RATIOS, None), quarter, 0);
+		stay(quarter.cycle(RATIOS, Some(0)), region("left-half"), 1);
+		monitor(
+			quarter.cycle(RATIOS, Some(3)),
+			Direction::Left,
+			Region::new(0.75, 0.0, 0.25, 1.0),
+		);
+	}
+
+	#[test]
+	fn cycle_continues_for_the_same_hotkey_and_area() {
+		let area = Rect {
+			x: 0,
+			y: 0,
+			w: 960,
+			h: 1080,
+		};
+		let cycle = SnapCycle {
+			hotkey: 2,
+			step: 1,
+			area,
+		};
+
+		assert_eq!(cycle.previous_step(2, area), Some(1));
+	}
+
+	#[test]
+	fn cycle_resets_when_another_hotkey_is_pressed() {
+		let area = Rect {
+			x: 0,
+			y: 0,
+			w: 960,
+			h: 1080,
+		};
+		let cycle = SnapCycle {
+			hotkey: 2,
+			step: 1,
+			area,
+		};
+
+		assert_eq!(cycle.previous_step(3, area), None);
+
+		let left = region("left-half");
+		stay(left.cycle(RATIOS, cycle.previous_step(3, area)), left, 0);
+	}
+
+	#[test]
+	fn cycle_resets_when_the_window_moved_in_between() {
+		let area = Rect {
+			x: 0,
+			y: 0,
+			w: 960,
+			h: 1080,
+		};
+		let cycle = SnapCycle {
+			hotkey: 2,
+			step: 1,
+			area,
+		};
+
+		let moved = Rect { x: 10, ..area };
+		let resized = Rect { w: 900, ..area };
+
+		assert_eq!(cycle.previous_step(2, moved), None);
+		assert_eq!(cycle.previous_step(2, resized), None);
+	}
+
+	#[test]
+	fn axis_without_margins_is_split_by_the_fractions() {
+		assert_eq!(resolve_axis(0, 1000, 0.0, 0.5, 0, 0), (0, 500));
+		assert_eq!(resolve_axis(0, 1000, 0.5, 0.5, 0, 0), (500, 500));
+		assert_eq!(resolve_axis(0, 1000, 0.0, 1.0, 0, 0), (0, 1000));
+	}
+
+	#[test]
+	fn axis_keeps_the_margins() {
+		// Borders only at the edges of the work area, zone margins split between neighbours
+		assert_eq!(resolve_axis(0, 1000, 0.0, 0.5, 10, 8), (10, 486));
+		assert_eq!(resolve_axis(0, 1000, 0.5, 0.5, 10, 8), (504, 486));
+		assert_eq!(resolve_axis(0, 1000, 0.0, 1.0, 10, 8), (10, 980));
+
+		// Odd margins still leave exactly the margin between neighbours
+		let (left, left_width) = resolve_axis(0, 1000, 0.0, 0.5, 0, 7);
+		let (right, _) = resolve_axis(0, 1000, 0.5, 0.5, 0, 7);
+		assert_eq!(right - (left + left_width), 7);
+	}
+
+	#[test]
+	fn axis_rounding_leaves_no_gaps() {
+		let thirds: Vec<_> = (0..3)
+			.map(|i| resolve_axis(0, 1000, i as f32 / 3.0, 1.0 / 3.0, 0, 0))
+			.collect();
+
+		assert_eq!(thirds[0].0, 0);
+		assert_eq!(thirds[0].0 + thirds[0].1, thirds[1].0);
+		assert_eq!(thirds[1].0 + thirds[1].1, thirds[2].0);
+		assert_eq!(thirds[2].0 + thirds[2].1, 1000);
+	}
+
+	#[test]
+	fn axis_is_offset_by_the_start_of_the_work_area() {
+		assert_eq!(resolve_axis(-1920, 1920, 0.5, 0.5, 0, 0), (-960, 960));
+		assert_eq!(resolve_axis(40, 1000, 0.0, 0.5, 0, 0), (40, 500));
+	}
+}
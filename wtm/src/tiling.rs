@@ -0,0 +1,113 @@
+//! Tiling module
+
+use crate::{
+	config::TilingConfig,
+	layout::{Layout, LayoutOptions},
+};
+use winapi::um::winuser::{WS_CAPTION, WS_EX_TOOLWINDOW};
+use winsapi::{Rect, Window};
+
+/// The narrowest the master area can get
+const MIN_MASTER_RATIO: f32 = 0.1;
+
+/// The widest the master area can get
+const MAX_MASTER_RATIO: f32 = 0.9;
+
+/// The tiled windows of a monitor and how they are laid out
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tiling {
+	/// Index of the layout in the layouts of the config
+	pub layout: usize,
+	/// Width of the master area, as a fraction of the work area
+	pub master_ratio: f32,
+	/// Amount of windows in the master area
+	pub master_count: usize,
+	/// The tiled windows, the masters come first
+	pub windows: Vec<Window>,
+}
+
+impl Tiling {
+	/// Creates an empty tiling with the settings of `config`
+	pub fn new(config: &TilingConfig) -> Self {
+		Self {
+			layout: 0,
+			master_ratio: config.master_ratio,
+			master_count: config.master_count,
+			windows: Vec::new(),
+		}
+	}
+
+	/// The active layout
+	pub fn layout(&self, config: &TilingConfig) -> Layout {
+		config.layouts[self.layout % config.layouts.len()]
+	}
+
+	/// Replaces the tiled windows with `windows`
+	///
+	/// Windows that were already tiled keep their place, the others are added in the order of
+	/// `windows`.
+	pub fn update_windows(&mut self, windows: &[Window]) {
+		self.windows.retain(|window| windows.contains(window));
+
+		for window in windows {
+			if !self.windows.contains(window) {
+				self.windows.push(*window);
+			}
+		}
+	}
+
+	/// Where each tiled window goes inside of `work_area`
+	pub fn arrange(&self, config: &TilingConfig, work_area: Rect) -> Vec<(Window, Rect)> {
+		let options = LayoutOptions {
+			master_ratio: self.master_ratio,
+			master_count: self.master_count,
+			gaps: config.gaps as i32,
+		};
+
+		self.windows
+			.iter()
+			.copied()
+			.zip(
+				self.layout(config)
+					.arrange(self.windows.len(), work_area, &options),
+			)
+			.collect()
+	}
+
+	/// Switches to the next layout, or back to the first one after the last
+	pub fn next_layout(&mut self, config: &TilingConfig) {
+		self.layout = (self.layout + 1) % config.layouts.len();
+	}
+
+	/// Widens the master area by `delta`, or narrows it if `delta` is negative
+	pub fn change_master_ratio(&mut self, delta: f32) {
+		self.master_ratio = (self.master_ratio + delta).clamp(MIN_MASTER_RATIO, MAX_MASTER_RATIO);
+	}
+
+	/// Moves one window into the master area, or out of it if `grow` is false
+	pub fn change_master_count(&mut self, grow: bool) {
+		self.master_count = if grow {
+			(self.master_count + 1).min(self.windows.len().max(1))
+		} else {
+			self.master_count.saturating_sub(1)
+		};
+	}
+}
+
+/// Is `window` a normal application window that can be tiled
+///
+/// Minimized windows and windows without a title bar are left alone.
+pub fn is_tileable(window: Window) -> bool {
+	is_managed(window) && !window.is_minimized() && window.info().styles & WS_CAPTION == WS_CAPTION
+}
+
+/// Is `window` a shown application window
+///
+/// Hidden windows, dialogs and tool windows don't belong to the user's applications.
+pub fn is_managed(window: Window) -> bool {
+	window.is_visible()
+		&& !window.is_cloaked()
+		&& window.owner().is_none()
+		&& window.info().extended_styles & WS_EX_TOOLWINDOW == 0
+		&& !window.title().is_empty()
+}
@@ -0,0 +1,86 @@
+use crate::event::{send, Event};
+use std::{mem, ptr, thread};
+use winapi::{
+	shared::{
+		minwindef::{LPARAM, LRESULT, UINT, WPARAM},
+		windef::HWND,
+	},
+	um::{
+		libloaderapi::GetModuleHandleW,
+		winuser::{
+			CreateWindowExW, DefWindowProcW, KillTimer, RegisterClassExW, SetTimer,
+			WM_DISPLAYCHANGE, WM_SETTINGCHANGE, WM_TIMER, WNDCLASSEXW, WS_EX_TOOLWINDOW, WS_POPUP,
+		},
+	},
+};
+use winsapi::{run_message_loop, str_to_wide};
+
+/// The timer that checks the monitor setup again once it stopped changing
+const SETTLE_TIMER: usize = 1;
+
+/// How long the monitor setup has to stay the same before a change is handled, in milliseconds
+const SETTLE_DELAY: u32 = 500;
+
+/// Asks to check the monitor setup whenever a monitor is added, removed or changes its
+/// resolution, work area or scaling, for as long as wtm runs
+///
+/// A change is only handled once a second check finds the same setup, so the setup is checked
+/// again after it settled.
+///
+/// The window is never shown, but it can't be a message-only window because those don't receive
+/// the broadcasts of display and setting changes.
+pub fn spawn_display_window() {
+	thread::spawn(move || unsafe {
+		let h_instance = GetModuleHandleW(ptr::null());
+
+		let class_name = str_to_wide!("Wtm Displays");
+
+		let mut class = mem::zeroed::<WNDCLASSEXW>();
+		class.cbSize = mem::size_of::<WNDCLASSEXW>() as u32;
+		class.lpfnWndProc = Some(callback);
+		class.hInstance = h_instance;
+		class.lpszClassName = class_name.as_ptr();
+
+		RegisterClassExW(&class);
+
+		CreateWindowExW(
+			WS_EX_TOOLWINDOW,
+			class_name.as_ptr(),
+			ptr::null(),
+			WS_POPUP,
+			0,
+			0,
+			0,
+			0,
+			ptr::null_mut(),
+			ptr::null_mut(),
+			h_instance,
+			ptr::null_mut(),
+		);
+
+		run_message_loop();
+	});
+}
+
+/// Forwards display and setting changes to the tiling manager
+unsafe extern "system" fn callback(
+	hwnd: HWND,
+	msg: UINT,
+	wparam: WPARAM,
+	lparam: LPARAM,
+) -> LRESULT {
+	match msg {
+		// Setting the timer again restarts it, so changes in quick succession are checked once
+		WM_DISPLAYCHANGE | WM_SETTINGCHANGE => {
+			send(Event::CheckDisplays);
+			SetTimer(hwnd, SETTLE_TIMER, SETTLE_DELAY, None);
+		}
+		WM_TIMER if wparam == SETTLE_TIMER => {
+			KillTimer(hwnd, SETTLE_TIMER);
+			send(Event::CheckDisplays);
+		}
+		_ => {}
+	}
+
+	DefWindowProcW(hwnd, msg, wparam, lparam)
+}
@@ -1,6 +1,5 @@
-use crate::{Event, INSTANCE};
-use crossbeam_channel::{select, Receiver};
-use std::{mem, ptr, thread, time::Duration};
+use crate::event::{send, Event};
+use std::{mem, ptr, thread};
 use winapi::{
 	shared::{
 		minwindef::{HIWORD, LOWORD, LPARAM, LRESULT, UINT, WPARAM},
@@ -8,20 +7,18 @@ use winapi::{
 	},
 	um::{
 		libloaderapi::GetModuleHandleW,
-		wingdi::{CreateSolidBrush, RGB},
 		winuser::{
-			CreateWindowExW, DefWindowProcW, DispatchMessageW, InvalidateRect, LoadCursorW,
-			PeekMessageW, RegisterClassExW, SendMessageW, TranslateMessage, IDC_ARROW, VK_CONTROL,
-			VK_DOWN, VK_ESCAPE, VK_F1, VK_F2, VK_F3, VK_F4, VK_F5, VK_F6, VK_LEFT, VK_RIGHT,
-			VK_SHIFT, VK_UP, WM_KEYDOWN, WM_KEYUP, WM_LBUTTONDOWN, WM_LBUTTONUP, WM_MOUSELEAVE,
-			WM_MOUSEMOVE, WM_PAINT, WNDCLASSEXW, WS_EX_TOOLWINDOW, WS_EX_TOPMOST, WS_POPUP,
+			BeginPaint, CreateWindowExW, DefWindowProcW, EndPaint, LoadCursorW, RegisterClassExW,
+			IDC_ARROW, PAINTSTRUCT, WM_ERASEBKGND, WM_KEYDOWN, WM_KEYUP, WM_LBUTTONDOWN,
+			WM_LBUTTONUP, WM_MOUSELEAVE, WM_MOUSEMOVE, WM_PAINT, WNDCLASSEXW, WS_EX_TOOLWINDOW,
+			WS_EX_TOPMOST, WS_POPUP,
 		},
 	},
 };
-use winsapi::{str_to_wide, Monitor, Rect, Window};
+use winsapi::{run_message_loop_until, str_to_wide, Color, Rect, Signal, Window};
 
-/// Draw's the grid selection window
-pub fn spawn_grid_window(close_msg: Receiver<()>) {
+/// Draw's the grid selection window at `rect`
+pub fn spawn_grid_window(stop: Signal, rect: Rect, background: Color) {
 	thread::spawn(move || unsafe {
 		let h_instance = GetModuleHandleW(ptr::null());
 
@@ -32,225 +29,66 @@ pub fn spawn_grid_window(close_msg: Receiver<()>) {
 		class.lpfnWndProc = Some(callback);
 		class.hInstance = h_instance;
 		class.lpszClassName = class_name.as_ptr();
-		class.hbrBackground = CreateSolidBrush(RGB(44, 44, 44));
 		class.hCursor = LoadCursorW(ptr::null_mut(), IDC_ARROW);
 
 		RegisterClassExW(&class);
 
-		let work_area = Monitor::get_active().area();
-		let dimensions = INSTANCE.get_mut().unwrap().grid.dimensions();
-
 		let hwnd = CreateWindowExW(
 			WS_EX_TOPMOST | WS_EX_TOOLWINDOW,
 			class_name.as_ptr(),
 			ptr::null(),
 			WS_POPUP,
-			work_area.w / 2 - dimensions.0 as i32 / 2 + work_area.x,
-			work_area.h / 2 - dimensions.1 as i32 / 2 + work_area.y,
-			dimensions.0 as i32,
-			dimensions.1 as i32,
+			rect.x,
+			rect.y,
+			rect.w,
+			rect.h,
 			ptr::null_mut(),
 			ptr::null_mut(),
 			h_instance,
 			ptr::null_mut(),
 		);
 
-		let _ = INSTANCE
-			.get()
-			.unwrap()
-			.channel
-			.sender
-			.clone()
-			.send(Event::GridWindow(Window(hwnd)));
+		// The class outlives the window, so the color has to be set every time
+		Window(hwnd).set_background(background);
 
-		let mut msg = mem::zeroed();
-		loop {
-			if PeekMessageW(&mut msg, ptr::null_mut(), 0, 0, 1) > 0 {
-				TranslateMessage(&msg);
-				DispatchMessageW(&msg);
-			};
+		send(Event::GridWindow(Window(hwnd)));
 
-			select! {
-				recv(close_msg) -> _ => {
-					break;
-				}
-				default(Duration::from_millis(10)) => {}
-			}
-		}
+		run_message_loop_until(&stop);
 	});
 }
 
+/// Forwards the input and paint requests of the grid window to the tiling manager
 unsafe extern "system" fn callback(
 	hwnd: HWND,
 	msg: UINT,
 	wparam: WPARAM,
 	lparam: LPARAM,
 ) -> LRESULT {
-	let sender = INSTANCE.get().unwrap().channel.sender.clone();
+	let point = || (LOWORD(lparam as u32) as i32, HIWORD(lparam as u32) as i32);
 
-	let repaint = match msg {
+	match msg {
 		WM_PAINT => {
-			INSTANCE.get_mut().unwrap().grid.draw(Window(hwnd));
-			false
-		}
-		WM_KEYDOWN => match wparam as i32 {
-			VK_ESCAPE => {
-				let _ = sender.send(Event::CloseWindows);
-				false
-			}
-			VK_CONTROL => {
-				INSTANCE.get_mut().unwrap().grid.control_down = true;
-				false
-			}
-			VK_SHIFT => {
-				INSTANCE.get_mut().unwrap().grid.shift_down = true;
-				false
-			}
-			VK_RIGHT => {
-				if INSTANCE.get().unwrap().grid.control_down {
-					INSTANCE.get_mut().unwrap().grid.add_column();
-					INSTANCE.get_mut().unwrap().grid.reposition();
-				}
-				false
-			}
-			VK_LEFT => {
-				if INSTANCE.get().unwrap().grid.control_down {
-					INSTANCE.get_mut().unwrap().grid.remove_column();
-					INSTANCE.get_mut().unwrap().grid.reposition();
-				}
-				false
-			}
-			VK_UP => {
-				if INSTANCE.get().unwrap().grid.control_down {
-					INSTANCE.get_mut().unwrap().grid.add_row();
-					INSTANCE.get_mut().unwrap().grid.reposition();
-				}
-				false
-			}
-			VK_DOWN => {
-				if INSTANCE.get().unwrap().grid.control_down {
-					INSTANCE.get_mut().unwrap().grid.remove_row();
-					INSTANCE.get_mut().unwrap().grid.reposition();
-				}
-				false
-			}
-			_ => false,
-		},
-		WM_KEYUP => match wparam as i32 {
-			VK_CONTROL => {
-				INSTANCE.get_mut().unwrap().grid.control_down = false;
-				false
-			}
-			VK_SHIFT => {
-				INSTANCE.get_mut().unwrap().grid.shift_down = false;
-				false
-			}
-			VK_F1 => {
-				let _ = sender.send(Event::ProfileChange("Default"));
-				false
-			}
-			VK_F2 => {
-				let _ = sender.send(Event::ProfileChange("Profile2"));
-				false
-			}
-			VK_F3 => {
-				let _ = sender.send(Event::ProfileChange("Profile3"));
-				false
-			}
-			VK_F4 => {
-				let _ = sender.send(Event::ProfileChange("Profile4"));
-				false
-			}
-			VK_F5 => {
-				let _ = sender.send(Event::ProfileChange("Profile5"));
-				false
-			}
-			VK_F6 => {
-				let _ = sender.send(Event::ProfileChange("Profile6"));
-				false
-			}
-			_ => false,
-		},
-		WM_MOUSEMOVE => {
-			let x = LOWORD(lparam as u32) as i32;
-			let y = HIWORD(lparam as u32) as i32;
-
-			let _ = sender.send(Event::TrackMouse(Window(hwnd)));
+			let mut paint: PAINTSTRUCT = mem::zeroed();
+			BeginPaint(hwnd, &mut paint);
+			EndPaint(hwnd, &paint);
 
-			if let Some(rect) = INSTANCE.get_mut().unwrap().grid.highlight_tiles((x, y)) {
-				let _ = sender.send(Event::HighlightZone(rect));
+			send(Event::Paint(Window(hwnd)));
 
-				true
-			} else {
-				false
-			}
+			return 0;
 		}
-		WM_LBUTTONDOWN => {
-			let x = LOWORD(lparam as u32) as i32;
-			let y = HIWORD(lparam as u32) as i32;
-
-			let mut grid = &mut INSTANCE.get_mut().unwrap().grid;
-
-			let repaint = grid.select_tile((x, y));
-
-			grid.cursor_down = true;
-
-			repaint
-		}
-		WM_LBUTTONUP => {
-			let mut grid = &mut INSTANCE.get_mut().unwrap().grid;
-
-			let repaint = if let Some(mut rect) = grid.selected_area() {
-				if let Some(mut active_window) = grid.active_window {
-					if grid.previous_resize != Some((active_window, rect)) {
-						active_window.restore();
-
-						rect.adjust_for_border(active_window.transparent_border());
-
-						active_window.set_pos(rect, None);
-
-						grid.previous_resize = Some((active_window, rect));
-
-						if grid.quick_resize {
-							let _ = sender.send(Event::CloseWindows);
-						}
-					}
-
-					grid.unselect_all_tiles();
-				}
-
-				true
-			} else {
-				false
-			};
-
-			grid.cursor_down = false;
-
-			repaint
-		}
-		WM_MOUSELEAVE => {
-			INSTANCE.get_mut().unwrap().grid.unhighlight_all_tiles();
-
-			let _ = sender.send(Event::MouseLeft);
-			let _ = sender.send(Event::HighlightZone(Rect::zero()));
-
-			true
+		// The tiling manager paints the whole window
+		WM_ERASEBKGND => return 1,
+		WM_KEYDOWN => send(Event::GridKeyDown(wparam as i32)),
+		WM_KEYUP => send(Event::GridKeyUp(wparam as i32)),
+		WM_MOUSEMOVE => {
+			send(Event::TrackMouse(Window(hwnd)));
+			send(Event::GridMouseMove(point()))
 		}
+		WM_LBUTTONDOWN => send(Event::GridMouseDown(point())),
+		WM_LBUTTONUP => send(Event::GridMouseUp),
+		WM_MOUSELEAVE => send(Event::MouseLeft),
 		_ => false,
 	};
 
-	if repaint {
-		let dimensions = INSTANCE.get_mut().unwrap().grid.dimensions();
-		let rect = Rect {
-			x: 0,
-			y: 0,
-			w: dimensions.0 as i32,
-			h: dimensions.1 as i32,
-		};
-
-		InvalidateRect(hwnd, &rect.into(), 0);
-		SendMessageW(hwnd, WM_PAINT, 0, 0);
-	}
-
 	DefWindowProcW(hwnd, msg, wparam, lparam)
 }
@@ -0,0 +1,90 @@
+use crate::{
+	event::{send, Event},
+	grid::OVERLAY_COLOR_KEY,
+};
+use std::{mem, ptr, thread};
+use winapi::{
+	shared::{
+		minwindef::{LOWORD, LPARAM, LRESULT, UINT, WPARAM},
+		windef::HWND,
+	},
+	um::{
+		libloaderapi::GetModuleHandleW,
+		winuser::{
+			BeginPaint, CreateWindowExW, DefWindowProcW, EndPaint, RegisterClassExW, PAINTSTRUCT,
+			WA_INACTIVE, WM_ACTIVATE, WM_KEYDOWN, WM_PAINT, WNDCLASSEXW, WS_EX_LAYERED,
+			WS_EX_TOOLWINDOW, WS_EX_TOPMOST, WS_POPUP, WS_VISIBLE,
+		},
+	},
+};
+use winsapi::{run_message_loop_until, str_to_wide, Rect, Signal, Window};
+
+/// Draw's the labels of hint mode over `rect`, which covers every monitor
+///
+/// Only the labels are visible, but the window takes the focus so the labels can be typed.
+pub fn spawn_hint_window(stop: Signal, rect: Rect) {
+	thread::spawn(move || unsafe {
+		let h_instance = GetModuleHandleW(ptr::null());
+
+		let class_name = str_to_wide!("Wtm Hints");
+
+		let mut class = mem::zeroed::<WNDCLASSEXW>();
+		class.cbSize = mem::size_of::<WNDCLASSEXW>() as u32;
+		class.lpfnWndProc = Some(callback);
+		class.hInstance = h_instance;
+		class.lpszClassName = class_name.as_ptr();
+
+		RegisterClassExW(&class);
+
+		let hwnd = CreateWindowExW(
+			WS_EX_LAYERED | WS_EX_TOPMOST | WS_EX_TOOLWINDOW,
+			class_name.as_ptr(),
+			ptr::null(),
+			WS_POPUP | WS_VISIBLE,
+			rect.x,
+			rect.y,
+			rect.w,
+			rect.h,
+			ptr::null_mut(),
+			ptr::null_mut(),
+			h_instance,
+			ptr::null_mut(),
+		);
+
+		// The background is erased with the color key, so nothing shows before the labels are
+		// painted
+		Window(hwnd).set_background(OVERLAY_COLOR_KEY);
+		Window(hwnd).set_color_key(OVERLAY_COLOR_KEY, 255);
+
+		send(Event::HintWindow(Window(hwnd)));
+
+		run_message_loop_until(&stop);
+	});
+}
+
+/// Forwards the typed keys and paint requests of the hint window to the tiling manager
+unsafe extern "system" fn callback(
+	hwnd: HWND,
+	msg: UINT,
+	wparam: WPARAM,
+	lparam: LPARAM,
+) -> LRESULT {
+	match msg {
+		WM_PAINT => {
+			let mut paint: PAINTSTRUCT = mem::zeroed();
+			BeginPaint(hwnd, &mut paint);
+			EndPaint(hwnd, &paint);
+
+			send(Event::Paint(Window(hwnd)));
+
+			return 0;
+		}
+		WM_KEYDOWN => send(Event::HintKeyDown(wparam as i32)),
+		WM_ACTIVATE if LOWORD(wparam as u32) == WA_INACTIVE => {
+			send(Event::HintWindowDeactivated(Window(hwnd)))
+		}
+		_ => false,
+	};
+
+	DefWindowProcW(hwnd, msg, wparam, lparam)
+}
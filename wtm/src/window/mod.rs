@@ -1,7 +1,11 @@
 //! Window module
 
+pub use display::spawn_display_window;
 pub use grid::spawn_grid_window;
+pub use hints::spawn_hint_window;
 pub use preview::spawn_preview_window;
 
+mod display;
 mod grid;
+mod hints;
 mod preview;
@@ -1,6 +1,5 @@
-use crate::{Event, INSTANCE};
-use crossbeam_channel::{select, Receiver};
-use std::{mem, ptr, thread, time::Duration};
+use crate::event::{send, Event};
+use std::{mem, ptr, thread};
 use winapi::{
 	shared::{
 		minwindef::{LPARAM, LRESULT, UINT, WPARAM},
@@ -8,18 +7,18 @@ use winapi::{
 	},
 	um::{
 		libloaderapi::GetModuleHandleW,
-		wingdi::{CreateSolidBrush, RGB},
 		winuser::{
-			CreateWindowExW, DefWindowProcW, DispatchMessageW, PeekMessageW, RegisterClassExW,
-			SetLayeredWindowAttributes, TranslateMessage, LWA_ALPHA, WNDCLASSEXW, WS_EX_LAYERED,
-			WS_EX_NOACTIVATE, WS_EX_TOPMOST, WS_EX_TRANSPARENT, WS_POPUP, WS_SYSMENU, WS_VISIBLE,
+			BeginPaint, CreateWindowExW, DefWindowProcW, EndPaint, RegisterClassExW,
+			SetLayeredWindowAttributes, LWA_ALPHA, PAINTSTRUCT, WM_ERASEBKGND, WM_PAINT,
+			WNDCLASSEXW, WS_EX_LAYERED, WS_EX_NOACTIVATE, WS_EX_TOPMOST, WS_EX_TRANSPARENT,
+			WS_POPUP, WS_SYSMENU, WS_VISIBLE,
 		},
 	},
 };
-use winsapi::{str_to_wide, Window};
+use winsapi::{run_message_loop_until, str_to_wide, Color, Signal, Window};
 
-/// Draw's a blue preview over the highlighted part of the grid
-pub fn spawn_preview_window(close_msg: Receiver<()>) {
+/// Draw's a blue preview over the highlighted part of the grid, with `opacity` from 1 to 255
+pub fn spawn_preview_window(stop: Signal, color: Color, opacity: u8) {
 	thread::spawn(move || unsafe {
 		let h_instance = GetModuleHandleW(ptr::null());
 
@@ -30,7 +29,6 @@ pub fn spawn_preview_window(close_msg: Receiver<()>) {
 		class.lpfnWndProc = Some(callback);
 		class.hInstance = h_instance;
 		class.lpszClassName = class_name.as_ptr();
-		class.hbrBackground = CreateSolidBrush(RGB(0, 77, 128));
 
 		RegisterClassExW(&class);
 
@@ -49,38 +47,35 @@ pub fn spawn_preview_window(close_msg: Receiver<()>) {
 			ptr::null_mut(),
 		);
 
-		SetLayeredWindowAttributes(hwnd, 0, 107, LWA_ALPHA);
+		// The class outlives the window, so the color has to be set every time
+		Window(hwnd).set_background(color);
 
-		let _ = INSTANCE
-			.get()
-			.unwrap()
-			.channel
-			.sender
-			.clone()
-			.send(Event::PreviewWindow(Window(hwnd)));
+		SetLayeredWindowAttributes(hwnd, 0, opacity, LWA_ALPHA);
 
-		let mut msg = mem::zeroed();
-		loop {
-			if PeekMessageW(&mut msg, ptr::null_mut(), 0, 0, 1) > 0 {
-				TranslateMessage(&msg);
-				DispatchMessageW(&msg);
-			};
+		send(Event::PreviewWindow(Window(hwnd)));
 
-			select! {
-				recv(close_msg) -> _ => {
-					break;
-				}
-				default(Duration::from_millis(10)) => {}
-			}
-		}
+		run_message_loop_until(&stop);
 	});
 }
 
+/// Leaves painting the preview window to the tiling manager
 unsafe extern "system" fn callback(
 	hwnd: HWND,
 	msg: UINT,
 	wparam: WPARAM,
 	lparam: LPARAM,
 ) -> LRESULT {
-	DefWindowProcW(hwnd, msg, wparam, lparam)
+	match msg {
+		WM_PAINT => {
+			let mut paint: PAINTSTRUCT = mem::zeroed();
+			BeginPaint(hwnd, &mut paint);
+			EndPaint(hwnd, &paint);
+
+			send(Event::Paint(Window(hwnd)));
+
+			0
+		}
+		WM_ERASEBKGND => 1,
+		_ => DefWindowProcW(hwnd, msg, wparam, lparam),
+	}
 }
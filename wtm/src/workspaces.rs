@@ -0,0 +1,361 @@
+//! Workspace module
+
+use crate::command::Command;
+use winsapi::Window;
+
+/// The largest amount of workspaces a monitor can have, one for each number key
+pub const MAX_WORKSPACES: usize = 9;
+
+/// The windows to hide and show when the shown workspace changes
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Transition {
+	/// Windows that leave the monitor
+	pub hide: Vec<Window>,
+	/// Windows that appear on the monitor
+	pub show: Vec<Window>,
+	/// The window that should be focused afterwards
+	pub focus: Option<Window>,
+}
+
+/// The workspaces of a monitor and the windows on each of them
+///
+/// Only the active workspace is shown, the windows of the others are hidden.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Workspaces {
+	/// Index of the workspace that is shown
+	active: usize,
+	/// The windows of each workspace, the most recently focused one first
+	windows: Vec<Vec<Window>>,
+}
+
+impl Workspaces {
+	/// Creates `count` empty workspaces, the first one is active
+	pub fn new(count: usize) -> Self {
+		Self {
+			active: 0,
+			windows: vec![Vec::new(); count.max(1)],
+		}
+	}
+
+	/// Index of the workspace that is shown
+	pub fn active(&self) -> usize {
+		self.active
+	}
+
+	/// Index of the workspace `window` is on
+	pub fn workspace_of(&self, window: Window) -> Option<usize> {
+		self.windows
+			.iter()
+			.position(|windows| windows.contains(&window))
+	}
+
+	/// The windows of every workspace but the active one
+	pub fn hidden(&self) -> Vec<Window> {
+		self.windows
+			.iter()
+			.enumerate()
+			.filter(|(index, _)| *index != self.active)
+			.flat_map(|(_, windows)| windows.iter().copied())
+			.collect()
+	}
+
+	/// Updates the active workspace with the windows that are shown on the monitor
+	///
+	/// Windows of the active workspace that aren't shown anymore were closed or moved to another
+	/// monitor and are forgotten. Hidden windows that are shown again were brought back by their
+	/// application, they join the active workspace.
+	pub fn sync(&mut self, shown: &[Window]) {
+		let active = self.active;
+
+		for (index, windows) in self.windows.iter_mut().enumerate() {
+			if index == active {
+				windows.retain(|window| shown.contains(window));
+			} else {
+				windows.retain(|window| !shown.contains(window));
+			}
+		}
+
+		for window in shown {
+			if !self.windows[active].contains(window) {
+				self.windows[active].push(*window);
+			}
+		}
+	}
+
+	/// Forgets the windows `exists` returns false for
+	pub fn retain(&mut self, exists: impl Fn(Window) -> bool) {
+		for windows in &mut self.windows {
+			windows.retain(|window| exists(*window));
+		}
+	}
+
+	/// Moves `window` to the front of the focus history of its workspace
+	///
+	/// Returns false if `window` isn't on any workspace.
+	pub fn focus(&mut self, window: Window) -> bool {
+		match self.workspace_of(window) {
+			Some(index) => {
+				let windows = &mut self.windows[index];
+
+				windows.retain(|other| *other != window);
+				windows.insert(0, window);
+
+				true
+			}
+			None => false,
+		}
+	}
+
+	/// Shows the workspace at `index`
+	///
+	/// Returns `None` if there is no such workspace or it is already shown. The most recently
+	/// focused window of the new workspace gets the focus.
+	pub fn switch(&mut self, index: usize) -> Option<Transition> {
+		if index >= self.windows.len() || index == self.active {
+			return None;
+		}
+
+		let hide = self.windows[self.active].clone();
+		let show = self.windows[index].clone();
+		self.active = index;
+
+		Some(Transition {
+			hide,
+			focus: show.first().copied(),
+			show,
+		})
+	}
+
+	/// Moves `window` to the workspace at `index`
+	///
+	/// Returns `None` if the window isn't on any workspace, there is no workspace at `index` or
+	/// the window is already on it. A window that leaves the active workspace is hidden and the
+	/// next window in the focus history gets the focus.
+	pub fn move_window(&mut self, window: Window, index: usize) -> Option<Transition> {
+		let from = self.workspace_of(window)?;
+
+		if index >= self.windows.len() || index == from {
+			return None;
+		}
+
+		self.windows[from].retain(|other| *other != window);
+		self.windows[index].insert(0, window);
+
+		let transition = if from == self.active {
+			Transition {
+				hide: vec![window],
+				show: Vec::new(),
+				focus: self.windows[self.active].first().copied(),
+			}
+		} else if index == self.active {
+			Transition {
+				hide: Vec::new(),
+				show: vec![window],
+				focus: Some(window),
+			}
+		} else {
+			Transition::default()
+		};
+
+		Some(transition)
+	}
+}
+
+impl Transition {
+	/// The commands that show and hide the windows and move the focus
+	pub fn commands(self) -> Vec<Command> {
+		self.show
+			.into_iter()
+			.map(Command::Show)
+			.chain(self.hide.into_iter().map(Command::Hide))
+			.chain(self.focus.map(Command::Focus))
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn window(id: usize) -> Window {
+		Window(id as _)
+	}
+
+	fn windows(ids: &[usize]) -> Vec<Window> {
+		ids.iter().map(|id| window(*id)).collect()
+	}
+
+	/// Three workspaces with windows 1 and 2 on the first and 3 on the second one
+	fn workspaces() -> Workspaces {
+		let mut workspaces = Workspaces::new(3);
+		workspaces.sync(&windows(&[1, 2, 3]));
+		workspaces.move_window(window(3), 1);
+
+		workspaces
+	}
+
+	#[test]
+	fn there_is_always_a_workspace() {
+		let workspaces = Workspaces::new(0);
+
+		assert_eq!(workspaces.active(), 0);
+		assert_eq!(workspaces.windows.len(), 1);
+	}
+
+	#[test]
+	fn shown_windows_join_the_active_workspace() {
+		let mut workspaces = Workspaces::new(2);
+
+		workspaces.sync(&windows(&[1, 2]));
+
+		assert_eq!(workspaces.workspace_of(window(1)), Some(0));
+		assert_eq!(workspaces.workspace_of(window(2)), Some(0));
+		assert_eq!(workspaces.hidden(), Vec::new());
+	}
+
+	#[test]
+	fn windows_that_are_gone_are_forgotten() {
+		let mut workspaces = workspaces();
+
+		workspaces.sync(&windows(&[2]));
+		assert_eq!(workspaces.workspace_of(window(1)), None);
+		assert_eq!(workspaces.workspace_of(window(3)), Some(1));
+
+		workspaces.retain(|other| other != window(3));
+		assert_eq!(workspaces.workspace_of(window(3)), None);
+		assert_eq!(workspaces.hidden(), Vec::new());
+	}
+
+	#[test]
+	fn hidden_windows_shown_by_their_application_move_to_the_active_workspace() {
+		let mut workspaces = workspaces();
+
+		workspaces.sync(&windows(&[1, 2, 3]));
+
+		assert_eq!(workspaces.workspace_of(window(3)), Some(0));
+		assert_eq!(workspaces.windows[1], Vec::new());
+	}
+
+	#[test]
+	fn switching_hides_the_old_windows_and_focuses_the_last_focused_new_one() {
+		let mut workspaces = workspaces();
+		workspaces.focus(window(2));
+
+		let transition = workspaces.switch(1).unwrap();
+
+		assert_eq!(workspaces.active(), 1);
+		assert_eq!(transition.hide, windows(&[2, 1]));
+		assert_eq!(transition.show, windows(&[3]));
+		assert_eq!(transition.focus, Some(window(3)));
+		assert_eq!(workspaces.hidden(), windows(&[2, 1]));
+	}
+
+	#[test]
+	fn switching_to_an_empty_workspace_focuses_nothing() {
+		let mut workspaces = workspaces();
+
+		let transition = workspaces.switch(2).unwrap();
+
+		assert_eq!(transition.hide, windows(&[1, 2]));
+		assert_eq!(transition.show, Vec::new());
+		assert_eq!(transition.focus, None);
+	}
+
+	#[test]
+	fn transitions_show_the_new_windows_before_hiding_the_old_ones() {
+		let mut workspaces = workspaces();
+
+		assert_eq!(
+			workspaces.switch(1).unwrap().commands(),
+			vec![
+				Command::Show(window(3)),
+				Command::Hide(window(1)),
+				Command::Hide(window(2)),
+				Command::Focus(window(3)),
+			]
+		);
+	}
+
+	#[test]
+	fn switching_to_the_shown_or_a_missing_workspace_does_nothing() {
+		let mut workspaces = workspaces();
+
+		assert_eq!(workspaces.switch(0), None);
+		assert_eq!(workspaces.switch(3), None);
+		assert_eq!(workspaces.active(), 0);
+	}
+
+	#[test]
+	fn focus_moves_the_window_to_the_front_of_its_workspace() {
+		let mut workspaces = workspaces();
+
+		assert!(workspaces.focus(window(2)));
+		assert_eq!(workspaces.windows[0], windows(&[2, 1]));
+		assert!(!workspaces.focus(window(4)));
+	}
+
+	#[test]
+	fn moving_away_from_the_active_workspace_hides_the_window() {
+		let mut workspaces = workspaces();
+
+		let transition = workspaces.move_window(window(1), 2).unwrap();
+
+		assert_eq!(
+			transition,
+			Transition {
+				hide: windows(&[1]),
+				show: Vec::new(),
+				focus: Some(window(2)),
+			}
+		);
+		assert_eq!(workspaces.workspace_of(window(1)), Some(2));
+	}
+
+	#[test]
+	fn moving_the_last_window_away_focuses_nothing() {
+		let mut workspaces = Workspaces::new(2);
+		workspaces.sync(&windows(&[1]));
+
+		let transition = workspaces.move_window(window(1), 1).unwrap();
+
+		assert_eq!(transition.hide, windows(&[1]));
+		assert_eq!(transition.focus, None);
+	}
+
+	#[test]
+	fn moving_to_the_active_workspace_shows_and_focuses_the_window() {
+		let mut workspaces = workspaces();
+
+		let transition = workspaces.move_window(window(3), 0).unwrap();
+
+		assert_eq!(
+			transition,
+			Transition {
+				hide: Vec::new(),
+				show: windows(&[3]),
+				focus: Some(window(3)),
+			}
+		);
+		assert_eq!(workspaces.windows[0], windows(&[3, 1, 2]));
+	}
+
+	#[test]
+	fn moving_between_hidden_workspaces_changes_nothing_on_screen() {
+		let mut workspaces = workspaces();
+
+		let transition = workspaces.move_window(window(3), 2).unwrap();
+
+		assert_eq!(transition, Transition::default());
+		assert_eq!(workspaces.workspace_of(window(3)), Some(2));
+	}
+
+	#[test]
+	fn moving_nowhere_does_nothing() {
+		let mut workspaces = workspaces();
+
+		assert_eq!(workspaces.move_window(window(1), 0), None);
+		assert_eq!(workspaces.move_window(window(1), 3), None);
+		assert_eq!(workspaces.move_window(window(4), 1), None);
+		assert_eq!(workspaces.workspace_of(window(1)), Some(0));
+	}
+}
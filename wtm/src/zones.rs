@@ -0,0 +1,287 @@
+//! Zone set module
+
+use serde::{de, Deserialize, Deserializer};
+use std::{fmt, str::FromStr};
+use winsapi::Rect;
+
+/// The largest amount of zones a zone set can have, one for each number key
+pub const MAX_ZONES: usize = 10;
+
+/// A named set of zones that can be placed anywhere on the monitor
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ZoneSet {
+	/// Name of the zone set
+	pub name: String,
+	/// The zones, numbered in the order they are listed
+	pub zones: Vec<Zone>,
+}
+
+/// A rectangle relative to the work area of a monitor
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Zone {
+	/// Distance from the left edge of the work area
+	pub x: Length,
+	/// Distance from the top edge of the work area
+	pub y: Length,
+	/// Width of the zone
+	pub width: Length,
+	/// Height of the zone
+	pub height: Length,
+}
+
+/// A distance, either relative to the size of the work area or in pixels
+///
+/// Written as `"50%"` or as `400` / `"400px"` in the config.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Length {
+	/// Percentage of the work area, from 0 to 100
+	Percent(f32),
+	/// Absolute amount of pixels
+	Pixels(i32),
+}
+
+impl ZoneSet {
+	/// The zones as absolute rectangles inside of `work_area`
+	pub fn resolve(&self, work_area: Rect) -> Vec<Rect> {
+		self.zones
+			.iter()
+			.map(|zone| zone.resolve(work_area))
+			.collect()
+	}
+
+	/// Index of the zone at `point`
+	///
+	/// Zones may overlap, in that case the smallest zone wins so that every zone stays reachable.
+	pub fn zone_at(&self, work_area: Rect, point: (i32, i32)) -> Option<usize> {
+		self.resolve(work_area)
+			.iter()
+			.enumerate()
+			.filter(|(_, rect)| rect.contains_point(point))
+			.min_by_key(|(_, rect)| rect.w as i64 * rect.h as i64)
+			.map(|(index, _)| index)
+	}
+}
+
+impl Zone {
+	/// The zone as an absolute rectangle inside of `work_area`
+	///
+	/// Zones that reach past the work area are cut off at its edge.
+	pub fn resolve(&self, work_area: Rect) -> Rect {
+		let x = self.x.resolve(work_area.w).min(work_area.w);
+		let y = self.y.resolve(work_area.h).min(work_area.h);
+
+		Rect {
+			x: work_area.x + x,
+			y: work_area.y + y,
+			w: self.width.resolve(work_area.w).min(work_area.w - x),
+			h: self.height.resolve(work_area.h).min(work_area.h - y),
+		}
+	}
+}
+
+impl Length {
+	/// The length in pixels, relative to `size`
+	pub fn resolve(self, size: i32) -> i32 {
+		match self {
+			Length::Percent(percent) => (size as f32 * percent / 100.0).round() as i32,
+			Length::Pixels(pixels) => pixels,
+		}
+	}
+
+	/// Is the length inside of the allowed range
+	///
+	/// Sizes have to be larger than zero, offsets may also be zero.
+	pub fn is_valid(self, allow_zero: bool) -> bool {
+		match self {
+			Length::Percent(percent) if allow_zero => (0.0..=100.0).contains(&percent),
+			Length::Percent(percent) => percent > 0.0 && percent <= 100.0,
+			Length::Pixels(pixels) if allow_zero => pixels >= 0,
+			Length::Pixels(pixels) => pixels > 0,
+		}
+	}
+}
+
+impl FromStr for Length {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let s = s.trim();
+		let error = || format!("`{}` is neither a percentage nor an amount of pixels", s);
+
+		if let Some(percent) = s.strip_suffix('%') {
+			percent
+				.trim()
+				.parse()
+				.map(Length::Percent)
+				.map_err(|_| error())
+		} else {
+			s.strip_suffix("px")
+				.unwrap_or(s)
+				.trim()
+				.parse()
+				.map(Length::Pixels)
+				.map_err(|_| error())
+		}
+	}
+}
+
+impl fmt::Display for Length {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Length::Percent(percent) => write!(f, "{}%", percent),
+			Length::Pixels(pixels) => write!(f, "{}px", pixels),
+		}
+	}
+}
+
+impl<'de> Deserialize<'de> for Length {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		#[derive(Deserialize)]
+		#[serde(untagged)]
+		enum Raw {
+			Pixels(i32),
+			Text(String),
+		}
+
+		match Raw::deserialize(deserializer)? {
+			Raw::Pixels(pixels) => Ok(Length::Pixels(pixels)),
+			Raw::Text(text) => text.parse().map_err(de::Error::custom),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const WORK_AREA: Rect = Rect {
+		x: 100,
+		y: 50,
+		w: 1000,
+		h: 800,
+	};
+
+	fn zone(x: Length, y: Length, width: Length, height: Length) -> Zone {
+		Zone {
+			x,
+			y,
+			width,
+			height,
+		}
+	}
+
+	#[test]
+	fn lengths_are_parsed_as_percentages_or_pixels() {
+		assert_eq!("50%".parse(), Ok(Length::Percent(50.0)));
+		assert_eq!(" 12.5 % ".parse(), Ok(Length::Percent(12.5)));
+		assert_eq!("400px".parse(), Ok(Length::Pixels(400)));
+		assert_eq!("400".parse(), Ok(Length::Pixels(400)));
+
+		for garbage in ["", "%", "px", "half", "50%%", "4OOpx", "1.5px"] {
+			assert!(garbage.parse::<Length>().is_err(), "{}", garbage);
+		}
+	}
+
+	#[test]
+	fn lengths_are_read_from_numbers_and_strings() {
+		let zone: Zone = toml::from_str(
+			r#"
+			x = 0
+			y = "10%"
+			width = "400px"
+			height = "50%"
+			"#,
+		)
+		.unwrap();
+
+		assert_eq!(
+			zone,
+			self::zone(
+				Length::Pixels(0),
+				Length::Percent(10.0),
+				Length::Pixels(400),
+				Length::Percent(50.0)
+			)
+		);
+		assert!(toml::from_str::<Zone>(r#"x = 0 y = 0 width = "wide" height = 1"#).is_err());
+	}
+
+	#[test]
+	fn zones_are_placed_relative_to_the_work_area() {
+		let zone = zone(
+			Length::Percent(50.0),
+			Length::Pixels(100),
+			Length::Percent(25.0),
+			Length::Pixels(200),
+		);
+
+		assert_eq!(
+			zone.resolve(WORK_AREA),
+			Rect {
+				x: 600,
+				y: 150,
+				w: 250,
+				h: 200,
+			}
+		);
+	}
+
+	#[test]
+	fn zones_are_cut_off_at_the_edges_of_the_work_area() {
+		let zone = zone(
+			Length::Percent(75.0),
+			Length::Pixels(700),
+			Length::Percent(50.0),
+			Length::Pixels(400),
+		);
+
+		assert_eq!(
+			zone.resolve(WORK_AREA),
+			Rect {
+				x: 850,
+				y: 750,
+				w: 250,
+				h: 100,
+			}
+		);
+
+		let outside = self::zone(
+			Length::Pixels(2000),
+			Length::Pixels(0),
+			Length::Pixels(100),
+			Length::Pixels(100),
+		);
+		assert_eq!(outside.resolve(WORK_AREA).x, 1100);
+		assert_eq!(outside.resolve(WORK_AREA).w, 0);
+	}
+
+	#[test]
+	fn the_smallest_zone_at_a_point_wins() {
+		let zone_set = ZoneSet {
+			name: String::from("overlapping"),
+			zones: vec![
+				zone(
+					Length::Percent(0.0),
+					Length::Percent(0.0),
+					Length::Percent(100.0),
+					Length::Percent(100.0),
+				),
+				zone(
+					Length::Percent(25.0),
+					Length::Percent(25.0),
+					Length::Percent(50.0),
+					Length::Percent(50.0),
+				),
+			],
+		};
+
+		assert_eq!(zone_set.zone_at(WORK_AREA, (600, 450)), Some(1));
+		assert_eq!(zone_set.zone_at(WORK_AREA, (150, 100)), Some(0));
+		assert_eq!(zone_set.zone_at(WORK_AREA, (50, 450)), None);
+	}
+}